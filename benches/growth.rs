@@ -0,0 +1,55 @@
+//! Benchmarks of the growth loop, run by `cargo bench --features bench`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use transport::{
+    terrain::TerrainBuilder,
+    transport::{bench::PathTreeBench, preset::CityPreset, transport::TransportNetworkBuilder},
+};
+
+fn builder(iterations: usize) -> TransportNetworkBuilder {
+    TransportNetworkBuilder::new()
+        .set_city_preset(CityPreset::EuropeanOrganic)
+        .set_start(100.0, 50.0)
+        .set_iterations(iterations)
+}
+
+/// The throughput of the build is the candidates evaluated by the growth.
+fn bench_build(c: &mut Criterion) {
+    let terrain = TerrainBuilder::new()
+        .set_bound_max(200.0, 100.0)
+        .set_node_num(5000)
+        .build(100);
+    let mut group = c.benchmark_group("build");
+    group.sample_size(10);
+    [1000, 4000, 16000].into_iter().for_each(|iterations| {
+        let (_, stats) = builder(iterations)
+            .build_with_stats(0, &terrain)
+            .expect("the benchmark parameters are valid");
+        group.throughput(Throughput::Elements(stats.candidate_evaluations as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(iterations),
+            &iterations,
+            |b, &iterations| b.iter(|| builder(iterations).build(0, black_box(&terrain))),
+        );
+    });
+    group.finish();
+}
+
+fn bench_path_tree_find(c: &mut Criterion) {
+    let mut group = c.benchmark_group("path_tree_find");
+    [1000, 10000, 100000].into_iter().for_each(|num_paths| {
+        let bench = PathTreeBench::new(num_paths, 0.5, 0);
+        group.throughput(Throughput::Elements(num_paths as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_paths),
+            &bench,
+            |b, bench| b.iter(|| bench.run(black_box(0.5))),
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_build, bench_path_tree_find);
+criterion_main!(benches);
@@ -0,0 +1,73 @@
+import init, {
+    TerrainBuilder,
+    TransportNetworkBuilder,
+} from '../pkg/transport.js';
+
+// A list of builder setters to call in order, e.g. [['set_node_num', 20000]].
+export type BuilderCalls = [string, ...number[]][];
+
+export interface GenerationTask {
+    terrain: BuilderCalls;
+    terrain_seed: number;
+    transport: BuilderCalls;
+    transport_seed: number;
+}
+
+export interface GenerationResult {
+    terrain_seed: number;
+    transport_seed: number;
+    sites: Float64Array;
+    edges: Uint32Array;
+}
+
+export interface GenerationRequest {
+    id: number;
+    task: GenerationTask;
+}
+
+export interface GenerationResponse {
+    id: number;
+    result?: GenerationResult;
+    error?: string;
+}
+
+const applyCalls = <T>(builder: T, calls: BuilderCalls): T =>
+    calls.reduce((builder: any, [name, ...args]) => {
+        if (typeof builder[name] !== 'function') {
+            throw new Error(`unknown builder method: ${name}`);
+        }
+        return builder[name](...args);
+    }, builder);
+
+const ready = init();
+
+self.onmessage = async (event: MessageEvent<GenerationRequest>) => {
+    await ready;
+    const { id, task } = event.data;
+    try {
+        const terrain = applyCalls(new TerrainBuilder(), task.terrain).build(
+            task.terrain_seed
+        );
+        const network = applyCalls(
+            new TransportNetworkBuilder(),
+            task.transport
+        ).build(task.transport_seed, terrain);
+
+        const result: GenerationResult = {
+            terrain_seed: task.terrain_seed,
+            transport_seed: task.transport_seed,
+            sites: network.export_sites(),
+            edges: network.export_edges(),
+        };
+        network.free();
+        terrain.free();
+
+        const response: GenerationResponse = { id, result };
+        self.postMessage(response, {
+            transfer: [result.sites.buffer, result.edges.buffer],
+        });
+    } catch (e) {
+        const response: GenerationResponse = { id, error: String(e) };
+        self.postMessage(response);
+    }
+};
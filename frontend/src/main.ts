@@ -1,50 +1,32 @@
 import './style.css';
 import init, {
+    RoadStyle,
     TerrainBuilder,
     TransportNetworkBuilder,
 } from '../pkg/transport.js';
 
 window.onload = async () => {
     await init();
-    const node_num = 20000;
     const bound_max = { x: 200.0, y: 100.0 };
 
-    const terrain = new TerrainBuilder()
-        .set_bound_max(bound_max.x, bound_max.y)
-        .set_node_num(node_num)
-        .build(100);
+    const terrain = TerrainBuilder.default_terrain().build(100);
 
     const img_width = 1000;
     const img_height = 500;
     let image_buf = new Uint8ClampedArray(img_width * img_height * 4);
-    for (let imgx = 0; imgx < img_width; imgx++) {
-        for (let imgy = 0; imgy < img_height; imgy++) {
-            const x = bound_max.x * (imgx / img_width);
-            const y = bound_max.y * (imgy / img_height);
-            const altitude = terrain.get_altitude(x, y);
-            if (altitude) {
-                const color = get_color(altitude);
-                const index = (imgx + imgy * img_width) * 4;
-                image_buf[index] = color[0];
-                image_buf[index + 1] = color[1];
-                image_buf[index + 2] = color[2];
-                image_buf[index + 3] = 255;
-            }
+    const altitudes = terrain.rasterize(img_width, img_height);
+    for (let i = 0; i < altitudes.length; i++) {
+        const altitude = altitudes[i];
+        if (!Number.isNaN(altitude)) {
+            const color = get_color(altitude);
+            image_buf[i * 4] = color[0];
+            image_buf[i * 4 + 1] = color[1];
+            image_buf[i * 4 + 2] = color[2];
+            image_buf[i * 4 + 3] = 255;
         }
     }
 
-    const transport = new TransportNetworkBuilder()
-        .set_start(bound_max.x / 2.0, bound_max.y / 2.0)
-        .set_iterations(34000)
-        .set_branch_angle_deviation(Math.PI / 40.0)
-        .set_branch_length(0.5)
-        .set_normal_rotation_probability(0.8)
-        .set_highway_rotation_probability(0.02)
-        .set_highway_construction_priority(30.0)
-        .set_even_path_length_weight(1.5)
-        .set_highway_path_length_weight(1.5)
-        .set_branch_max_angle(Math.PI / 40.0)
-        .build(0, terrain);
+    const transport = TransportNetworkBuilder.default_city().build(0, terrain);
 
     let canvas = document.getElementById('canvasMain') as HTMLCanvasElement;
     canvas.width = img_width;
@@ -58,34 +40,45 @@ window.onload = async () => {
     ctx.fillStyle = 'rgba(255, 255, 255, 0.7)';
     ctx.fillRect(0, 0, img_width, img_height);
 
-    for (let i = 0; i < transport.num_nodes(); i++) {
-        const site = transport.get_site(i);
-        const neighbors = transport.get_neighbors(i);
-        for (let j = 0; j < neighbors.length; j++) {
-            const neighbor = transport.get_site(neighbors[j].index);
-            const [sx, sy] = [
-                (site.x / bound_max.x) * img_width,
-                (site.y / bound_max.y) * img_height,
-            ];
-            const [ex, ey] = [
-                (neighbor.x / bound_max.x) * img_width,
-                (neighbor.y / bound_max.y) * img_height,
-            ];
-            let lineWidth = 0.0;
-            if (neighbors[j].is_highway) {
-                lineWidth = 2;
-            } else {
-                lineWidth = 0.5;
+    const style = new RoadStyle();
+    const edges = transport
+        .get_edges()
+        .map((edge) => ({
+            edge,
+            style_class: RoadStyle.get_class(edge.is_highway, edge.is_bridge),
+        }))
+        .sort(
+            (a, b) =>
+                style.get_z_order(a.style_class) -
+                style.get_z_order(b.style_class),
+        );
+    const draw = (casing: boolean) => {
+        for (const { edge, style_class } of edges) {
+            const width = style.get_width(style_class);
+            const casing_width = style.get_casing_width(style_class);
+            if (casing && casing_width <= 0.0) {
+                continue;
             }
+            const start = transport.get_site(edge.start_index);
+            const end = transport.get_site(edge.end_index);
             ctx.beginPath();
-            ctx.moveTo(sx, sy);
-            ctx.lineTo(ex, ey);
-
-            ctx.lineWidth = lineWidth;
-            ctx.strokeStyle = 'rgba(50, 50, 50, 0.7)';
+            ctx.moveTo(
+                (start.x / bound_max.x) * img_width,
+                (start.y / bound_max.y) * img_height,
+            );
+            ctx.lineTo(
+                (end.x / bound_max.x) * img_width,
+                (end.y / bound_max.y) * img_height,
+            );
+            ctx.lineWidth = casing ? width + casing_width * 2.0 : width;
+            ctx.strokeStyle = casing
+                ? style.get_casing_color(style_class)
+                : style.get_color(style_class);
             ctx.stroke();
         }
-    }
+    };
+    draw(true);
+    draw(false);
 };
 
 const color_table: [[number, number, number], number][] = [
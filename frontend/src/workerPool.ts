@@ -0,0 +1,92 @@
+import type {
+    GenerationRequest,
+    GenerationResponse,
+    GenerationResult,
+    GenerationTask,
+} from './generationWorker';
+
+export type {
+    BuilderCalls,
+    GenerationResult,
+    GenerationTask,
+} from './generationWorker';
+
+interface PendingTask {
+    id: number;
+    task: GenerationTask;
+    resolve: (result: GenerationResult) => void;
+    reject: (error: Error) => void;
+}
+
+// Runs terrain and network generation for many seeds on a pool of web workers.
+// Each worker initializes its own wasm instance; results come back as transferable buffers.
+export class GenerationWorkerPool {
+    private idle: Worker[] = [];
+    private queue: PendingTask[] = [];
+    private running = new Map<Worker, PendingTask>();
+    private nextId = 0;
+
+    constructor(size: number = navigator.hardwareConcurrency || 4) {
+        for (let i = 0; i < size; i++) {
+            const worker = new Worker(
+                new URL('./generationWorker.ts', import.meta.url),
+                { type: 'module' }
+            );
+            worker.onmessage = (event: MessageEvent<GenerationResponse>) =>
+                this.onResponse(worker, event.data);
+            this.idle.push(worker);
+        }
+    }
+
+    run(task: GenerationTask): Promise<GenerationResult> {
+        return new Promise((resolve, reject) => {
+            this.queue.push({ id: this.nextId++, task, resolve, reject });
+            this.dispatch();
+        });
+    }
+
+    runAll(tasks: GenerationTask[]): Promise<GenerationResult[]> {
+        return Promise.all(tasks.map((task) => this.run(task)));
+    }
+
+    terminate() {
+        this.idle.forEach((worker) => worker.terminate());
+        this.running.forEach((pending, worker) => {
+            pending.reject(new Error('worker pool terminated'));
+            worker.terminate();
+        });
+        this.queue.forEach((pending) =>
+            pending.reject(new Error('worker pool terminated'))
+        );
+        this.idle = [];
+        this.queue = [];
+        this.running.clear();
+    }
+
+    private dispatch() {
+        while (this.idle.length > 0 && this.queue.length > 0) {
+            const worker = this.idle.pop() as Worker;
+            const pending = this.queue.shift() as PendingTask;
+            this.running.set(worker, pending);
+            const request: GenerationRequest = {
+                id: pending.id,
+                task: pending.task,
+            };
+            worker.postMessage(request);
+        }
+    }
+
+    private onResponse(worker: Worker, response: GenerationResponse) {
+        const pending = this.running.get(worker);
+        this.running.delete(worker);
+        this.idle.push(worker);
+        if (pending && pending.id === response.id) {
+            if (response.result) {
+                pending.resolve(response.result);
+            } else {
+                pending.reject(new Error(response.error));
+            }
+        }
+        this.dispatch();
+    }
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::Site2D;
+
+/// The kinds of the land cover.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Biome {
+    #[default]
+    Grassland,
+    Forest,
+    Marsh,
+    Desert,
+}
+
+pub(crate) const BIOMES: [Biome; 4] =
+    [Biome::Grassland, Biome::Forest, Biome::Marsh, Biome::Desert];
+
+impl Biome {
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The land cover given on a regular grid over a rectangle.
+/// Each point takes the biome of the nearest grid point, and the points outside have no biome.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedBiomeMap")]
+pub struct BiomeMap {
+    width: usize,
+    height: usize,
+    bound_min: Site2D,
+    bound_max: Site2D,
+    biomes: Vec<Biome>,
+}
+
+/// The serialized form of a biome map, which is checked to have a biome for each grid point.
+#[derive(Deserialize)]
+struct SerializedBiomeMap {
+    width: usize,
+    height: usize,
+    bound_min: Site2D,
+    bound_max: Site2D,
+    biomes: Vec<Biome>,
+}
+
+impl TryFrom<SerializedBiomeMap> for BiomeMap {
+    type Error = String;
+
+    fn try_from(serialized: SerializedBiomeMap) -> Result<Self, Self::Error> {
+        if serialized.width.checked_mul(serialized.height) != Some(serialized.biomes.len()) {
+            return Err("the biomes of the biome map do not match its size".to_string());
+        }
+        Ok(Self {
+            width: serialized.width,
+            height: serialized.height,
+            bound_min: serialized.bound_min,
+            bound_max: serialized.bound_max,
+            biomes: serialized.biomes,
+        })
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BiomeMap {
+    /// Create a biome map from row-major `values` of a `width` x `height` grid, which are the indices of `Biome`.
+    /// The grid points at the corners are placed on `bound_min` and `bound_max`.
+    /// The unknown indices and the missing values are `Biome::Grassland`.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(
+        width: usize,
+        height: usize,
+        bound_min_x: f64,
+        bound_min_y: f64,
+        bound_max_x: f64,
+        bound_max_y: f64,
+        values: Vec<u8>,
+    ) -> Self {
+        let mut biomes = values
+            .iter()
+            .map(|value| {
+                BIOMES
+                    .get(*value as usize)
+                    .copied()
+                    .unwrap_or(Biome::Grassland)
+            })
+            .collect::<Vec<_>>();
+        biomes.resize(width * height, Biome::Grassland);
+        Self {
+            width,
+            height,
+            bound_min: Site2D {
+                x: bound_min_x,
+                y: bound_min_y,
+            },
+            bound_max: Site2D {
+                x: bound_max_x,
+                y: bound_max_y,
+            },
+            biomes,
+        }
+    }
+
+    pub fn sample(&self, x: f64, y: f64) -> Option<Biome> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let fx = (x - self.bound_min.x) / (self.bound_max.x - self.bound_min.x);
+        let fy = (y - self.bound_min.y) / (self.bound_max.y - self.bound_min.y);
+        if !(0.0..=1.0).contains(&fx) || !(0.0..=1.0).contains(&fy) {
+            return None;
+        }
+        let ix = (fx * (self.width - 1) as f64).round() as usize;
+        let iy = (fy * (self.height - 1) as f64).round() as usize;
+        Some(self.biomes[iy * self.width + ix])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn biomes_not_matching_the_size_are_rejected() {
+        let map = BiomeMap::new(2, 1, 0.0, 0.0, 1.0, 1.0, vec![1, 3]);
+        let json = serde_json::to_string(&map).unwrap();
+        assert!(serde_json::from_str::<BiomeMap>(&json).is_ok());
+        let short = json.replace(r#"["Forest","Desert"]"#, r#"["Forest"]"#);
+        assert_ne!(short, json);
+        assert!(serde_json::from_str::<BiomeMap>(&short).is_err());
+    }
+}
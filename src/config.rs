@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    terrain::TerrainBuilder,
+    transport::{error::TransportError, transport::TransportNetworkBuilder},
+    world::World,
+};
+
+/// Derive an independent seed from `seed` for each `stream` by SplitMix64.
+pub(crate) fn derive_seed(seed: u64, stream: u64) -> u64 {
+    let mut z = seed.wrapping_add(stream.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Everything which decides a generated world: the parameters of the terrain and the network,
+/// and a single 64-bit seed from which the seeds of both are derived.
+///
+/// The same config generates a bit-identical world on every run, as every random decision,
+/// including the placement of the sites of the terrain, is drawn from the seed.
+/// The growth of the network computes its directions by `libm`, so it is also identical across platforms
+/// on the same terrain, while the erosion of the terrain uses the `powf` of the platform.
+///
+/// The config can be saved as JSON to share the parameters, where the missing parameters take the values of `new` of the builders.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GenerationConfig {
+    #[serde(default)]
+    seed: u64,
+    #[serde(default)]
+    terrain: TerrainBuilder,
+    #[serde(default)]
+    transport: TransportNetworkBuilder,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl GenerationConfig {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(seed: u64, terrain: TerrainBuilder, transport: TransportNetworkBuilder) -> Self {
+        Self {
+            seed,
+            terrain,
+            transport,
+        }
+    }
+
+    pub fn get_terrain(&self) -> TerrainBuilder {
+        self.terrain.clone()
+    }
+
+    pub fn get_transport(&self) -> TransportNetworkBuilder {
+        self.transport.clone()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Load a config written by `to_json`.
+    /// Returns `None` if the JSON is broken or has a parameter of a wrong type.
+    pub fn from_json(json: &str) -> Option<GenerationConfig> {
+        serde_json::from_str(json).ok()
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_terrain_seed(&self) -> u64 {
+        derive_seed(self.seed, 1)
+    }
+
+    pub fn get_transport_seed(&self) -> u64 {
+        derive_seed(self.seed, 2)
+    }
+
+    /// Generate the terrain and the network grown on it.
+    pub fn generate(&self) -> Result<World, TransportError> {
+        let terrain = self.terrain.clone().build_u64(self.get_terrain_seed());
+        let network = self
+            .transport
+            .clone()
+            .build_u64(self.get_transport_seed(), &terrain)?;
+        let mut world = World::new(terrain);
+        world.add_network(network);
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(seed: u64) -> GenerationConfig {
+        GenerationConfig::new(
+            seed,
+            TerrainBuilder::new()
+                .set_bound_max(20.0, 10.0)
+                .set_node_num(2000),
+            TransportNetworkBuilder::default_city()
+                .set_start(10.0, 5.0)
+                .set_iterations(2000),
+        )
+    }
+
+    /// Get the bits of the altitudes of the terrain and the hash of the network.
+    fn hashes(config: &GenerationConfig) -> (Vec<u64>, u64) {
+        let world = config.generate().unwrap();
+        let altitudes = world
+            .get_terrain()
+            .altitudes()
+            .iter()
+            .map(|altitude| altitude.to_bits())
+            .collect();
+        (altitudes, world.get_network(0).content_hash())
+    }
+
+    #[test]
+    fn same_config_generates_the_same_world() {
+        assert_eq!(hashes(&config(42)), hashes(&config(42)));
+        let seed = u64::MAX - 1;
+        assert_eq!(hashes(&config(seed)), hashes(&config(seed)));
+    }
+
+    #[test]
+    fn different_seeds_generate_different_terrains() {
+        assert_ne!(hashes(&config(1)).0, hashes(&config(2)).0);
+    }
+}
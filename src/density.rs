@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::Site2D;
+
+/// A population density field sampled on a regular grid over a rectangle.
+/// Values between the grid points are interpolated bilinearly, and the density outside is 0.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedDensityMap")]
+pub struct DensityMap {
+    width: usize,
+    height: usize,
+    bound_min: Site2D,
+    bound_max: Site2D,
+    values: Vec<f64>,
+}
+
+/// The serialized form of a density map, which is checked to have a value for each grid point.
+#[derive(Deserialize)]
+struct SerializedDensityMap {
+    width: usize,
+    height: usize,
+    bound_min: Site2D,
+    bound_max: Site2D,
+    values: Vec<f64>,
+}
+
+impl TryFrom<SerializedDensityMap> for DensityMap {
+    type Error = String;
+
+    fn try_from(serialized: SerializedDensityMap) -> Result<Self, Self::Error> {
+        if serialized.width.checked_mul(serialized.height) != Some(serialized.values.len()) {
+            return Err("the values of the density map do not match its size".to_string());
+        }
+        Ok(Self {
+            width: serialized.width,
+            height: serialized.height,
+            bound_min: serialized.bound_min,
+            bound_max: serialized.bound_max,
+            values: serialized.values,
+        })
+    }
+}
+
+impl DensityMap {
+    /// Sample `density(x, y)` on a `width` x `height` grid.
+    pub fn from_fn<F>(
+        width: usize,
+        height: usize,
+        bound_min: Site2D,
+        bound_max: Site2D,
+        density: F,
+    ) -> Self
+    where
+        F: Fn(f64, f64) -> f64,
+    {
+        let mut values = Vec::with_capacity(width * height);
+        (0..height).for_each(|iy| {
+            (0..width).for_each(|ix| {
+                let site = Self::grid_site(width, height, bound_min, bound_max, ix, iy);
+                values.push(density(site.x, site.y));
+            })
+        });
+        Self {
+            width,
+            height,
+            bound_min,
+            bound_max,
+            values,
+        }
+    }
+
+    fn grid_site(
+        width: usize,
+        height: usize,
+        bound_min: Site2D,
+        bound_max: Site2D,
+        ix: usize,
+        iy: usize,
+    ) -> Site2D {
+        let fx = if width > 1 {
+            ix as f64 / (width - 1) as f64
+        } else {
+            0.0
+        };
+        let fy = if height > 1 {
+            iy as f64 / (height - 1) as f64
+        } else {
+            0.0
+        };
+        Site2D {
+            x: bound_min.x + (bound_max.x - bound_min.x) * fx,
+            y: bound_min.y + (bound_max.y - bound_min.y) * fy,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DensityMap {
+    /// Create a density map from row-major `values` of a `width` x `height` grid.
+    /// The grid points at the corners are placed on `bound_min` and `bound_max`.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(
+        width: usize,
+        height: usize,
+        bound_min_x: f64,
+        bound_min_y: f64,
+        bound_max_x: f64,
+        bound_max_y: f64,
+        values: Vec<f64>,
+    ) -> Self {
+        let mut values = values;
+        values.resize(width * height, 0.0);
+        Self {
+            width,
+            height,
+            bound_min: Site2D {
+                x: bound_min_x,
+                y: bound_min_y,
+            },
+            bound_max: Site2D {
+                x: bound_max_x,
+                y: bound_max_y,
+            },
+            values,
+        }
+    }
+
+    /// Create a density map by calling the JS function `density(x, y)` at each grid point.
+    #[cfg(feature = "wasm")]
+    pub fn from_function(
+        width: usize,
+        height: usize,
+        bound_min_x: f64,
+        bound_min_y: f64,
+        bound_max_x: f64,
+        bound_max_y: f64,
+        density: &js_sys::Function,
+    ) -> Self {
+        Self::from_fn(
+            width,
+            height,
+            Site2D {
+                x: bound_min_x,
+                y: bound_min_y,
+            },
+            Site2D {
+                x: bound_max_x,
+                y: bound_max_y,
+            },
+            |x, y| {
+                density
+                    .call2(&JsValue::NULL, &JsValue::from_f64(x), &JsValue::from_f64(y))
+                    .ok()
+                    .and_then(|value| value.as_f64())
+                    .unwrap_or(0.0)
+            },
+        )
+    }
+
+    pub fn sample(&self, x: f64, y: f64) -> f64 {
+        if self.width == 0 || self.height == 0 {
+            return 0.0;
+        }
+        let fx = (x - self.bound_min.x) / (self.bound_max.x - self.bound_min.x);
+        let fy = (y - self.bound_min.y) / (self.bound_max.y - self.bound_min.y);
+        if !(0.0..=1.0).contains(&fx) || !(0.0..=1.0).contains(&fy) {
+            return 0.0;
+        }
+        let gx = fx * (self.width - 1) as f64;
+        let gy = fy * (self.height - 1) as f64;
+        let (ix, iy) = (gx.floor() as usize, gy.floor() as usize);
+        let (ix1, iy1) = ((ix + 1).min(self.width - 1), (iy + 1).min(self.height - 1));
+        let (tx, ty) = (gx - ix as f64, gy - iy as f64);
+        let value = |ix: usize, iy: usize| self.values[iy * self.width + ix];
+        (value(ix, iy) * (1.0 - tx) + value(ix1, iy) * tx) * (1.0 - ty)
+            + (value(ix, iy1) * (1.0 - tx) + value(ix1, iy1) * tx) * ty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_not_matching_the_size_are_rejected() {
+        let map = DensityMap::new(2, 2, 0.0, 0.0, 1.0, 1.0, vec![1.0; 4]);
+        let json = serde_json::to_string(&map).unwrap();
+        assert!(serde_json::from_str::<DensityMap>(&json).is_ok());
+        let short = json.replace("[1.0,1.0,1.0,1.0]", "[1.0,1.0,1.0]");
+        assert_ne!(short, json);
+        assert!(serde_json::from_str::<DensityMap>(&short).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt::Write;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::transport::TransportNetwork;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Export the topology of the network as an undirected Graphviz graph.
+    /// The nodes are pinned at their sites by `pos` for the layout of `neato`, and the edges carry their attributes and cost.
+    /// Highways are drawn bold.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph transport {\n");
+        self.nodes().iter().enumerate().for_each(|(index, site)| {
+            let _ = writeln!(dot, "  {} [pos=\"{},{}!\"];", index, site.x, site.y);
+        });
+        self.edges().for_each(|edge| {
+            let _ = writeln!(
+                dot,
+                "  {} -- {} [road_class={}, is_one_way={}, is_even={}, is_bridge={}, cost={}{}];",
+                edge.start_index,
+                edge.end_index,
+                edge.road_class.name(),
+                edge.is_one_way,
+                edge.is_even,
+                edge.is_bridge,
+                edge.cost,
+                if edge.is_highway { ", style=bold" } else { "" }
+            );
+        });
+        dot.push_str("}\n");
+        dot
+    }
+}
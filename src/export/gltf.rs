@@ -0,0 +1,180 @@
+use serde_json::{json, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    export::mesh::{Mesh, MeshExporter},
+    terrain::Terrain,
+    transport::transport::TransportNetwork,
+};
+
+const GLB_MAGIC: u32 = 0x4654_6c67;
+const GLB_CHUNK_JSON: u32 = 0x4e4f_534a;
+const GLB_CHUNK_BIN: u32 = 0x004e_4942;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const COMPONENT_FLOAT: u32 = 5126;
+const COMPONENT_UNSIGNED_INT: u32 = 5125;
+
+/// The glTF document being assembled with its binary buffer.
+struct GltfBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    meshes: Vec<Value>,
+    materials: Vec<Value>,
+    nodes: Vec<Value>,
+}
+
+impl GltfBuilder {
+    fn push_view(&mut self, bytes: &[u8], target: u32) -> usize {
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.buffer.extend_from_slice(bytes);
+        self.buffer_views.len() - 1
+    }
+
+    fn push_vec3(&mut self, values: &[f32]) -> usize {
+        let bytes = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<_>>();
+        let view = self.push_view(&bytes, TARGET_ARRAY_BUFFER);
+        // the bounds are required for the positions
+        let (mut min, mut max) = ([f32::MAX; 3], [f32::MIN; 3]);
+        values.chunks(3).for_each(|vector| {
+            (0..3).for_each(|k| {
+                min[k] = min[k].min(vector[k]);
+                max[k] = max[k].max(vector[k]);
+            })
+        });
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": COMPONENT_FLOAT,
+            "count": values.len() / 3,
+            "type": "VEC3",
+            "min": min,
+            "max": max,
+        }));
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices: &[u32]) -> usize {
+        let bytes = indices
+            .iter()
+            .flat_map(|index| index.to_le_bytes())
+            .collect::<Vec<_>>();
+        let view = self.push_view(&bytes, TARGET_ELEMENT_ARRAY_BUFFER);
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": COMPONENT_UNSIGNED_INT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        self.accessors.len() - 1
+    }
+
+    /// Add the mesh as a node of the scene. Empty meshes are left out, as glTF does not allow them.
+    fn push_mesh(&mut self, name: &str, mesh: &Mesh, color: [f32; 4]) {
+        if mesh.indices.is_empty() {
+            return;
+        }
+        let positions = self.push_vec3(&mesh.positions);
+        let normals = self.push_vec3(&mesh.normals);
+        let indices = self.push_indices(&mesh.indices);
+        self.materials.push(json!({
+            "name": name,
+            "pbrMetallicRoughness": {
+                "baseColorFactor": color,
+                "metallicFactor": 0.0,
+                "roughnessFactor": 1.0,
+            },
+        }));
+        self.meshes.push(json!({
+            "name": name,
+            "primitives": [{
+                "attributes": { "POSITION": positions, "NORMAL": normals },
+                "indices": indices,
+                "material": self.materials.len() - 1,
+            }],
+        }));
+        self.nodes.push(json!({
+            "name": name,
+            "mesh": self.meshes.len() - 1,
+        }));
+    }
+
+    fn to_glb(&self) -> Vec<u8> {
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": "transport" },
+            "scene": 0,
+            "scenes": [{ "nodes": (0..self.nodes.len()).collect::<Vec<_>>() }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "materials": self.materials,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+        });
+        if !self.buffer.is_empty() {
+            document["buffers"] = json!([{ "byteLength": self.buffer.len() }]);
+        }
+
+        // the chunks are aligned to 4 bytes, padding the JSON with spaces and the binary with zeros
+        let mut json_chunk = document.to_string().into_bytes();
+        json_chunk.resize(json_chunk.len().div_ceil(4) * 4, b' ');
+        let mut bin_chunk = self.buffer.clone();
+        bin_chunk.resize(bin_chunk.len().div_ceil(4) * 4, 0);
+
+        let mut glb = vec![];
+        let total_length = 12
+            + 8
+            + json_chunk.len()
+            + if bin_chunk.is_empty() {
+                0
+            } else {
+                8 + bin_chunk.len()
+            };
+        glb.extend(GLB_MAGIC.to_le_bytes());
+        glb.extend(2u32.to_le_bytes());
+        glb.extend((total_length as u32).to_le_bytes());
+        glb.extend((json_chunk.len() as u32).to_le_bytes());
+        glb.extend(GLB_CHUNK_JSON.to_le_bytes());
+        glb.extend(json_chunk);
+        if !bin_chunk.is_empty() {
+            glb.extend((bin_chunk.len() as u32).to_le_bytes());
+            glb.extend(GLB_CHUNK_BIN.to_le_bytes());
+            glb.extend(bin_chunk);
+        }
+        glb
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl MeshExporter {
+    /// Export the terrain surface and the roads as a binary glTF (`.glb`) with the nodes `terrain` and `roads`.
+    pub fn export_glb(&self, network: &TransportNetwork, terrain: &Terrain) -> Vec<u8> {
+        let mut builder = GltfBuilder {
+            buffer: vec![],
+            buffer_views: vec![],
+            accessors: vec![],
+            meshes: vec![],
+            materials: vec![],
+            nodes: vec![],
+        };
+        builder.push_mesh(
+            "terrain",
+            &self.terrain_mesh(terrain),
+            [0.45, 0.55, 0.35, 1.0],
+        );
+        builder.push_mesh(
+            "roads",
+            &self.road_mesh(network, terrain),
+            [0.3, 0.3, 0.3, 1.0],
+        );
+        builder.to_glb()
+    }
+}
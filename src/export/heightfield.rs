@@ -0,0 +1,44 @@
+use std::io::Cursor;
+
+use image::{ImageBuffer, ImageOutputFormat, Luma};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::terrain::Terrain;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Terrain {
+    /// Encode the altitudes of `width` x `height` pixels over the bounds as a 16-bit grayscale PNG for the heightmaps of game engines.
+    /// The first row is at the minimum y as in `rasterize`.
+    /// The altitudes from `min_altitude` to `max_altitude` are mapped linearly to 0 to 65535 and clamped,
+    /// and the pixels outside the terrain are 0. Returns an empty vector if the size is zero.
+    pub fn to_heightfield_png16(
+        &self,
+        width: usize,
+        height: usize,
+        min_altitude: f64,
+        max_altitude: f64,
+    ) -> Vec<u8> {
+        if width == 0 || height == 0 {
+            return vec![];
+        }
+        let range = max_altitude - min_altitude;
+        let pixels = self
+            .rasterize(width, height)
+            .iter()
+            .map(|altitude| {
+                if !altitude.is_finite() || range <= 0.0 {
+                    return 0;
+                }
+                (((altitude - min_altitude) / range).clamp(0.0, 1.0) * u16::MAX as f64).round()
+                    as u16
+            })
+            .collect::<Vec<_>>();
+        let image =
+            ImageBuffer::<Luma<u16>, Vec<u16>>::from_raw(width as u32, height as u32, pixels)
+                .unwrap();
+        let mut bytes = Cursor::new(vec![]);
+        image.write_to(&mut bytes, ImageOutputFormat::Png).unwrap();
+        bytes.into_inner()
+    }
+}
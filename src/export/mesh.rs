@@ -0,0 +1,254 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{terrain::Terrain, transport::transport::TransportNetwork};
+
+/// An indexed triangle mesh in the Y-up convention of glTF and three.js:
+/// each position is `[x, altitude, y]` and the triangles are counterclockwise seen from above.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Default)]
+pub struct Mesh {
+    pub(crate) positions: Vec<f32>,
+    pub(crate) normals: Vec<f32>,
+    pub(crate) indices: Vec<u32>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Mesh {
+    pub fn num_vertices(&self) -> usize {
+        self.positions.len() / 3
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Get the positions as `[x0, altitude0, y0, x1, altitude1, y1, ...]`.
+    pub fn get_positions(&self) -> Vec<f32> {
+        self.positions.clone()
+    }
+
+    /// Get the unit normals of the vertices, averaged over the triangles around each vertex.
+    pub fn get_normals(&self) -> Vec<f32> {
+        self.normals.clone()
+    }
+
+    /// Get the indices of the vertices of the triangles.
+    pub fn get_indices(&self) -> Vec<u32> {
+        self.indices.clone()
+    }
+}
+
+impl Mesh {
+    fn push_vertex(&mut self, x: f64, altitude: f64, y: f64) -> u32 {
+        self.positions.extend([x as f32, altitude as f32, y as f32]);
+        (self.positions.len() / 3 - 1) as u32
+    }
+
+    /// Compute the normals of the vertices from the triangles.
+    fn compute_normals(&mut self) {
+        let mut normals = vec![0.0f32; self.positions.len()];
+        let position = |index: u32| {
+            let i = index as usize * 3;
+            [
+                self.positions[i],
+                self.positions[i + 1],
+                self.positions[i + 2],
+            ]
+        };
+        self.indices.chunks(3).for_each(|triangle| {
+            let (a, b, c) = (
+                position(triangle[0]),
+                position(triangle[1]),
+                position(triangle[2]),
+            );
+            let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            // weighted by the area of the triangle
+            let normal = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            triangle.iter().for_each(|index| {
+                let i = *index as usize * 3;
+                (0..3).for_each(|k| normals[i + k] += normal[k]);
+            });
+        });
+        normals.chunks_mut(3).for_each(|normal| {
+            let length =
+                (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+            if length > 0.0 {
+                normal.iter_mut().for_each(|value| *value /= length);
+            } else {
+                normal.copy_from_slice(&[0.0, 1.0, 0.0]);
+            }
+        });
+        self.normals = normals;
+    }
+}
+
+/// Builds triangle meshes of the terrain surface and of the roads draped on it as ribbons,
+/// and exports them as glTF.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct MeshExporter {
+    terrain_resolution: (usize, usize),
+    altitude_scale: f64,
+    highway_width: f64,
+    normal_width: f64,
+    road_lift: f64,
+    sample_spacing: f64,
+}
+
+impl Default for MeshExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl MeshExporter {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            terrain_resolution: (256, 256),
+            altitude_scale: 1.0,
+            highway_width: 0.2,
+            normal_width: 0.1,
+            road_lift: 0.01,
+            sample_spacing: 0.25,
+        }
+    }
+
+    /// Set the number of the vertices of the grid of the terrain surface along the x and the y axes.
+    pub fn set_terrain_resolution(self, width: usize, height: usize) -> Self {
+        Self {
+            terrain_resolution: (width, height),
+            ..self
+        }
+    }
+
+    /// Set the factor multiplied to the altitudes, which exaggerates the relief if larger than 1.
+    pub fn set_altitude_scale(self, altitude_scale: f64) -> Self {
+        Self {
+            altitude_scale,
+            ..self
+        }
+    }
+
+    /// Set the widths of the ribbons of the highways and the other roads.
+    pub fn set_road_widths(self, highway_width: f64, normal_width: f64) -> Self {
+        Self {
+            highway_width,
+            normal_width,
+            ..self
+        }
+    }
+
+    /// Set the height of the ribbons of the roads above the terrain, which keeps them from sinking into the surface.
+    pub fn set_road_lift(self, road_lift: f64) -> Self {
+        Self { road_lift, ..self }
+    }
+
+    /// Set the maximum distance between the vertices along the roads. See `TransportNetwork::polylines_3d`.
+    pub fn set_sample_spacing(self, sample_spacing: f64) -> Self {
+        Self {
+            sample_spacing,
+            ..self
+        }
+    }
+
+    /// Build the mesh of the terrain surface on a grid over its bounds.
+    /// The cells outside the terrain are left out.
+    pub fn terrain_mesh(&self, terrain: &Terrain) -> Mesh {
+        let mut mesh = Mesh::default();
+        let (width, height) = (
+            self.terrain_resolution.0.max(2),
+            self.terrain_resolution.1.max(2),
+        );
+        let (bound_min, bound_max) = (terrain.get_bound_min(), terrain.get_bound_max());
+        let mut is_inside = vec![false; width * height];
+        (0..height).for_each(|iy| {
+            (0..width).for_each(|ix| {
+                let x = bound_min.x + (bound_max.x - bound_min.x) * ix as f64 / (width - 1) as f64;
+                let y = bound_min.y + (bound_max.y - bound_min.y) * iy as f64 / (height - 1) as f64;
+                let altitude = terrain.get_altitude(x, y);
+                is_inside[iy * width + ix] = altitude.is_some();
+                mesh.push_vertex(x, altitude.unwrap_or(0.0) * self.altitude_scale, y);
+            });
+        });
+        (0..height - 1).for_each(|iy| {
+            (0..width - 1).for_each(|ix| {
+                let corners = [
+                    iy * width + ix,
+                    (iy + 1) * width + ix,
+                    iy * width + ix + 1,
+                    (iy + 1) * width + ix + 1,
+                ];
+                if corners.iter().all(|corner| is_inside[*corner]) {
+                    let [a, b, c, d] = corners.map(|corner| corner as u32);
+                    mesh.indices.extend([a, b, c, c, b, d]);
+                }
+            });
+        });
+        mesh.compute_normals();
+        mesh
+    }
+
+    /// Build the mesh of the roads as ribbons following the polylines of `TransportNetwork::polylines_3d`.
+    pub fn road_mesh(&self, network: &TransportNetwork, terrain: &Terrain) -> Mesh {
+        let mut mesh = Mesh::default();
+        let polylines = network.polylines_3d(terrain, self.sample_spacing);
+        let vertices = polylines.get_vertices();
+        let offsets = polylines.get_offsets();
+        (0..polylines.num_polylines()).for_each(|index| {
+            let half_width = if polylines.is_highway(index) {
+                self.highway_width
+            } else {
+                self.normal_width
+            } * 0.5;
+            let points = (offsets[index] as usize..offsets[index + 1] as usize)
+                .map(|i| {
+                    (
+                        vertices[i * 3] as f64,
+                        vertices[i * 3 + 1] as f64,
+                        vertices[i * 3 + 2] as f64,
+                    )
+                })
+                .collect::<Vec<_>>();
+            if points.len() < 2 {
+                return;
+            }
+            let mut previous: Option<(u32, u32)> = None;
+            (0..points.len()).for_each(|i| {
+                let before = points[i.saturating_sub(1)];
+                let after = points[(i + 1).min(points.len() - 1)];
+                let (dx, dy) = (after.0 - before.0, after.1 - before.1);
+                let length = (dx * dx + dy * dy).sqrt();
+                if length == 0.0 {
+                    return;
+                }
+                // the left side of the direction of the polyline
+                let (nx, ny) = (-dy / length * half_width, dx / length * half_width);
+                let (x, y, altitude) = points[i];
+                let altitude = altitude * self.altitude_scale + self.road_lift;
+                let left = mesh.push_vertex(x + nx, altitude, y + ny);
+                let right = mesh.push_vertex(x - nx, altitude, y - ny);
+                if let Some((previous_left, previous_right)) = previous {
+                    mesh.indices.extend([
+                        previous_left,
+                        left,
+                        previous_right,
+                        previous_right,
+                        left,
+                        right,
+                    ]);
+                }
+                previous = Some((left, right));
+            });
+        });
+        mesh.compute_normals();
+        mesh
+    }
+}
@@ -0,0 +1,7 @@
+pub mod dot;
+pub mod gltf;
+pub mod heightfield;
+pub mod mesh;
+pub mod obj;
+pub mod osm;
+pub mod svg;
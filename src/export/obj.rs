@@ -0,0 +1,73 @@
+use std::fmt::Write;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    export::mesh::Mesh,
+    transport::{road_class::ROAD_CLASSES, transport::TransportNetwork},
+};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Export the roads as line elements of a Wavefront OBJ document, grouped by the road class.
+    /// The vertices are `x 0 y` in the Y-up convention of `Mesh`. The first vertices are the nodes in the order of their indices,
+    /// followed by the intermediate vertices of the curved edges.
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::from("# transport network\n");
+        self.nodes.iter().for_each(|site| {
+            let _ = writeln!(obj, "v {} 0 {}", site.x, site.y);
+        });
+        let mut num_vertices = self.nodes.len();
+        let mut lines = String::new();
+        ROAD_CLASSES.iter().for_each(|road_class| {
+            let edges = self
+                .iter_edges()
+                .filter(|(_, _, attr)| attr.path_attr.road_class == *road_class)
+                .collect::<Vec<_>>();
+            if edges.is_empty() {
+                return;
+            }
+            let _ = writeln!(lines, "g {}", road_class.name());
+            edges.iter().for_each(|(start, end, _)| {
+                let mut indices = vec![*start + 1];
+                self.geometries.get(*start, *end).iter().for_each(|site| {
+                    let _ = writeln!(obj, "v {} 0 {}", site.x, site.y);
+                    num_vertices += 1;
+                    indices.push(num_vertices);
+                });
+                indices.push(*end + 1);
+                let _ = writeln!(
+                    lines,
+                    "l {}",
+                    indices
+                        .iter()
+                        .map(|index| index.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+            });
+        });
+        obj.push_str(&lines);
+        obj
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Mesh {
+    /// Export the mesh as a Wavefront OBJ document with the normals of the vertices.
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::from("# transport mesh\n");
+        self.positions.chunks(3).for_each(|position| {
+            let _ = writeln!(obj, "v {} {} {}", position[0], position[1], position[2]);
+        });
+        self.normals.chunks(3).for_each(|normal| {
+            let _ = writeln!(obj, "vn {} {} {}", normal[0], normal[1], normal[2]);
+        });
+        self.indices.chunks(3).for_each(|triangle| {
+            let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+            let _ = writeln!(obj, "f {a}//{a} {b}//{b} {c}//{c}");
+        });
+        obj
+    }
+}
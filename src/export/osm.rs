@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{road_class::RoadClass, transport::TransportNetwork},
+    Site2D,
+};
+
+/// The meters of a degree of latitude.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+fn highway_tag(road_class: RoadClass) -> &'static str {
+    match road_class {
+        RoadClass::Motorway => "motorway",
+        RoadClass::Arterial => "primary",
+        RoadClass::Collector => "secondary",
+        RoadClass::Local => "residential",
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Export the roads as an OpenStreetMap XML document, with a way for each edge tagged by `highway`,
+    /// and `oneway` and `bridge` where they apply. The way of a one-way street runs in its direction of travel.
+    ///
+    /// The coordinates are placed around (`origin_lat`, `origin_lon`) with `x` to the east and `y` to the north,
+    /// `meters_per_unit` meters per unit of the coordinates.
+    /// The IDs are synthetic: the node of index `i` has ID `i + 1`, followed by the intermediate vertices of the curved edges.
+    pub fn to_osm_xml(&self, origin_lat: f64, origin_lon: f64, meters_per_unit: f64) -> String {
+        let meters_per_degree_lon = METERS_PER_DEGREE * origin_lat.to_radians().cos();
+        let to_lat_lon = |site: &Site2D| {
+            (
+                origin_lat + site.y * meters_per_unit / METERS_PER_DEGREE,
+                origin_lon + site.x * meters_per_unit / meters_per_degree_lon,
+            )
+        };
+
+        let mut nodes = self.nodes.clone();
+        let ways = self
+            .edges()
+            .map(|edge| {
+                let mut node_ids = vec![edge.start_index + 1];
+                self.geometries
+                    .get(edge.start_index, edge.end_index)
+                    .into_iter()
+                    .for_each(|site| {
+                        nodes.push(site);
+                        node_ids.push(nodes.len());
+                    });
+                node_ids.push(edge.end_index + 1);
+                (edge, node_ids)
+            })
+            .collect::<Vec<_>>();
+
+        let coordinates = nodes.iter().map(to_lat_lon).collect::<Vec<_>>();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str("<osm version=\"0.6\" generator=\"transport\">\n");
+        if !coordinates.is_empty() {
+            let (min_lat, max_lat, min_lon, max_lon) = coordinates.iter().fold(
+                (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+                |(min_lat, max_lat, min_lon, max_lon), (lat, lon)| {
+                    (
+                        min_lat.min(*lat),
+                        max_lat.max(*lat),
+                        min_lon.min(*lon),
+                        max_lon.max(*lon),
+                    )
+                },
+            );
+            let _ = writeln!(
+                xml,
+                "  <bounds minlat=\"{}\" minlon=\"{}\" maxlat=\"{}\" maxlon=\"{}\"/>",
+                min_lat, min_lon, max_lat, max_lon
+            );
+        }
+        coordinates
+            .iter()
+            .enumerate()
+            .for_each(|(index, (lat, lon))| {
+                let _ = writeln!(
+                    xml,
+                    "  <node id=\"{}\" version=\"1\" lat=\"{}\" lon=\"{}\"/>",
+                    index + 1,
+                    lat,
+                    lon
+                );
+            });
+        ways.iter()
+            .enumerate()
+            .for_each(|(index, (edge, node_ids))| {
+                let _ = writeln!(xml, "  <way id=\"{}\" version=\"1\">", index + 1);
+                node_ids.iter().for_each(|node_id| {
+                    let _ = writeln!(xml, "    <nd ref=\"{}\"/>", node_id);
+                });
+                let _ = writeln!(
+                    xml,
+                    "    <tag k=\"highway\" v=\"{}\"/>",
+                    highway_tag(edge.road_class)
+                );
+                if edge.is_one_way {
+                    xml.push_str("    <tag k=\"oneway\" v=\"yes\"/>\n");
+                }
+                if edge.is_bridge {
+                    xml.push_str("    <tag k=\"bridge\" v=\"yes\"/>\n");
+                    xml.push_str("    <tag k=\"layer\" v=\"1\"/>\n");
+                }
+                xml.push_str("  </way>\n");
+            });
+        xml.push_str("</osm>\n");
+        xml
+    }
+}
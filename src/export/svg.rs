@@ -0,0 +1,276 @@
+use std::fmt::Write;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    terrain::Terrain,
+    transport::{style::RoadStyle, transport::TransportNetwork},
+    Site2D,
+};
+
+/// Renders the network and the contour lines of the terrain into an SVG document.
+/// The roads are drawn with the classes of `RoadStyle::to_svg_css`, and the contours with the class `contour`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct SvgExporter {
+    style: RoadStyle,
+    scale: f64,
+    contour_interval: f64,
+    contour_resolution: usize,
+    contour_color: String,
+    contour_width: f64,
+}
+
+impl Default for SvgExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SvgExporter {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            style: RoadStyle::new(),
+            scale: 1.0,
+            contour_interval: 0.0,
+            contour_resolution: 256,
+            contour_color: "rgba(120, 90, 60, 0.5)".to_string(),
+            contour_width: 0.5,
+        }
+    }
+
+    pub fn set_style(self, style: RoadStyle) -> Self {
+        Self { style, ..self }
+    }
+
+    /// Set the size of a unit of the coordinates in the document.
+    /// The widths of the roads and the contours are not scaled.
+    pub fn set_scale(self, scale: f64) -> Self {
+        Self { scale, ..self }
+    }
+
+    /// Draw a contour line at every multiple of `contour_interval` of the altitude.
+    /// Contours are not drawn if the interval is not positive, which is the default.
+    pub fn set_contour_interval(self, contour_interval: f64) -> Self {
+        Self {
+            contour_interval,
+            ..self
+        }
+    }
+
+    /// Set the number of the cells along the longer side of the terrain in which the contours are traced.
+    pub fn set_contour_resolution(self, contour_resolution: usize) -> Self {
+        Self {
+            contour_resolution,
+            ..self
+        }
+    }
+
+    pub fn set_contour_style(self, contour_color: String, contour_width: f64) -> Self {
+        Self {
+            contour_color,
+            contour_width,
+            ..self
+        }
+    }
+
+    /// Render the network over the bounds of the terrain, with the contours of the terrain.
+    pub fn export(&self, network: &TransportNetwork, terrain: &Terrain) -> String {
+        let contours = if self.contour_interval > 0.0 {
+            self.trace_contours(terrain)
+        } else {
+            vec![]
+        };
+        self.render(
+            network,
+            terrain.get_bound_min(),
+            terrain.get_bound_max(),
+            &contours,
+        )
+    }
+
+    /// Render only the network over the bounds of its nodes.
+    pub fn export_network(&self, network: &TransportNetwork) -> String {
+        let nodes = network.nodes();
+        let Some(first) = nodes.first() else {
+            let origin = Site2D { x: 0.0, y: 0.0 };
+            return self.render(network, origin, origin, &[]);
+        };
+        let (min, max) = nodes.iter().fold((*first, *first), |(min, max), site| {
+            (
+                Site2D {
+                    x: min.x.min(site.x),
+                    y: min.y.min(site.y),
+                },
+                Site2D {
+                    x: max.x.max(site.x),
+                    y: max.y.max(site.y),
+                },
+            )
+        });
+        self.render(network, min, max, &[])
+    }
+}
+
+impl SvgExporter {
+    fn render(
+        &self,
+        network: &TransportNetwork,
+        bound_min: Site2D,
+        bound_max: Site2D,
+        contours: &[Vec<(Site2D, Site2D)>],
+    ) -> String {
+        let to_point = |site: Site2D| {
+            (
+                (site.x - bound_min.x) * self.scale,
+                (site.y - bound_min.y) * self.scale,
+            )
+        };
+        let (width, height) = to_point(bound_max);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.3}\" height=\"{:.3}\" viewBox=\"0 0 {:.3} {:.3}\">",
+            width, height, width, height
+        );
+        let _ = writeln!(
+            svg,
+            "<style>\n.contour {{ fill: none; stroke: {}; stroke-width: {}; }}\n{}\n</style>",
+            self.contour_color,
+            self.contour_width,
+            self.style.to_svg_css()
+        );
+
+        contours.iter().for_each(|segments| {
+            let mut d = String::new();
+            segments.iter().for_each(|(start, end)| {
+                let (start, end) = (to_point(*start), to_point(*end));
+                let _ = write!(
+                    d,
+                    "M{:.3} {:.3}L{:.3} {:.3}",
+                    start.0, start.1, end.0, end.1
+                );
+            });
+            let _ = writeln!(svg, "<path class=\"contour\" d=\"{}\"/>", d);
+        });
+
+        // the path data of the roads in each class, drawn casings first and in the z-order
+        let classes = self.style.classes_by_z_order();
+        let paths = classes
+            .iter()
+            .map(|class| {
+                let mut d = String::new();
+                network
+                    .edges()
+                    .filter(|edge| RoadStyle::get_class(edge.is_highway, edge.is_bridge) == *class)
+                    .for_each(|edge| {
+                        network
+                            .get_polyline(edge.start_index, edge.end_index)
+                            .iter()
+                            .enumerate()
+                            .for_each(|(i, site)| {
+                                let (x, y) = to_point(*site);
+                                let command = if i == 0 { 'M' } else { 'L' };
+                                let _ = write!(d, "{}{:.3} {:.3}", command, x, y);
+                            });
+                    });
+                d
+            })
+            .collect::<Vec<_>>();
+        classes
+            .iter()
+            .zip(paths.iter())
+            .filter(|(class, d)| self.style.get_casing_width(**class) > 0.0 && !d.is_empty())
+            .for_each(|(class, d)| {
+                let _ = writeln!(
+                    svg,
+                    "<path class=\"road-{}-casing\" d=\"{}\"/>",
+                    class.name(),
+                    d
+                );
+            });
+        classes
+            .iter()
+            .zip(paths.iter())
+            .filter(|(_, d)| !d.is_empty())
+            .for_each(|(class, d)| {
+                let _ = writeln!(svg, "<path class=\"road-{}\" d=\"{}\"/>", class.name(), d);
+            });
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Trace the contours of the terrain by marching squares over its rasterized altitudes.
+    /// Returns the segments of the contour at each level, from the lowest.
+    fn trace_contours(&self, terrain: &Terrain) -> Vec<Vec<(Site2D, Site2D)>> {
+        let (bound_min, bound_max) = (terrain.get_bound_min(), terrain.get_bound_max());
+        let (extent_x, extent_y) = (bound_max.x - bound_min.x, bound_max.y - bound_min.y);
+        if extent_x <= 0.0 || extent_y <= 0.0 || self.contour_resolution == 0 {
+            return vec![];
+        }
+        let cell_size = extent_x.max(extent_y) / self.contour_resolution as f64;
+        let width = ((extent_x / cell_size).ceil() as usize).max(1);
+        let height = ((extent_y / cell_size).ceil() as usize).max(1);
+        let altitudes = terrain.rasterize(width, height);
+        let (pixel_width, pixel_height) = (extent_x / width as f64, extent_y / height as f64);
+        let site_of = |ix: f64, iy: f64| Site2D {
+            x: bound_min.x + (ix + 0.5) * pixel_width,
+            y: bound_min.y + (iy + 0.5) * pixel_height,
+        };
+
+        let (min_altitude, max_altitude) = altitudes
+            .iter()
+            .filter(|altitude| altitude.is_finite())
+            .fold((f64::MAX, f64::MIN), |(min, max), altitude| {
+                (min.min(*altitude), max.max(*altitude))
+            });
+        if min_altitude > max_altitude {
+            return vec![];
+        }
+        let first_level = (min_altitude / self.contour_interval).ceil() as i64;
+        let last_level = (max_altitude / self.contour_interval).floor() as i64;
+
+        (first_level..=last_level)
+            .map(|level| {
+                let level = level as f64 * self.contour_interval;
+                let mut segments = vec![];
+                (0..height.saturating_sub(1)).for_each(|iy| {
+                    (0..width.saturating_sub(1)).for_each(|ix| {
+                        // the corners of the cell clockwise from the top-left
+                        let corners = [(ix, iy), (ix + 1, iy), (ix + 1, iy + 1), (ix, iy + 1)];
+                        let values = corners.map(|(cx, cy)| altitudes[cy * width + cx]);
+                        if values.iter().any(|value| !value.is_finite()) {
+                            return;
+                        }
+                        // the crossings of the level on the sides of the cell
+                        let crossings = (0..4)
+                            .filter_map(|side| {
+                                let (a, b) = (side, (side + 1) % 4);
+                                if (values[a] < level) == (values[b] < level) {
+                                    return None;
+                                }
+                                let t = (level - values[a]) / (values[b] - values[a]);
+                                let (ax, ay) = corners[a];
+                                let (bx, by) = corners[b];
+                                Some(site_of(
+                                    ax as f64 + (bx as f64 - ax as f64) * t,
+                                    ay as f64 + (by as f64 - ay as f64) * t,
+                                ))
+                            })
+                            .collect::<Vec<_>>();
+                        // a saddle has four crossings, which are paired along the sides
+                        crossings.chunks_exact(2).for_each(|pair| {
+                            segments.push((pair[0], pair[1]));
+                        });
+                    });
+                });
+                segments
+            })
+            .collect::<Vec<_>>()
+    }
+}
@@ -0,0 +1,199 @@
+use noise::{
+    core::worley::{distance_functions::euclidean, worley_2d, ReturnType},
+    permutationtable::PermutationTable,
+    NoiseFn, OpenSimplex, Perlin,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// The kinds of the noise from which the landform is generated.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum NoiseKind {
+    #[default]
+    Perlin,
+    OpenSimplex,
+    /// Perlin noise folded at zero in each octave, which forms sharp ridgelines.
+    RidgedMultifractal,
+    /// The distance to the nearest of scattered points, which forms basins between ranges.
+    Worley,
+}
+
+/// A noise function of a kind with a seed.
+pub(crate) enum NoiseSource {
+    Perlin(Perlin),
+    OpenSimplex(OpenSimplex),
+    RidgedMultifractal(Perlin),
+    Worley(PermutationTable),
+}
+
+impl NoiseSource {
+    pub(crate) fn new(kind: NoiseKind, seed: u32) -> Self {
+        match kind {
+            NoiseKind::Perlin => NoiseSource::Perlin(Perlin::new(seed)),
+            NoiseKind::OpenSimplex => NoiseSource::OpenSimplex(OpenSimplex::new(seed)),
+            NoiseKind::RidgedMultifractal => NoiseSource::RidgedMultifractal(Perlin::new(seed)),
+            NoiseKind::Worley => NoiseSource::Worley(PermutationTable::new(seed)),
+        }
+    }
+
+    /// Get the value of an octave at the point, between -1 and 1.
+    fn get(&self, x: f64, y: f64) -> f64 {
+        match self {
+            NoiseSource::Perlin(perlin) => perlin.get([x, y]),
+            NoiseSource::OpenSimplex(open_simplex) => open_simplex.get([x, y]),
+            NoiseSource::RidgedMultifractal(perlin) => {
+                let ridge = 1.0 - perlin.get([x, y]).abs();
+                ridge * ridge * 2.0 - 1.0
+            }
+            NoiseSource::Worley(hasher) => {
+                worley_2d(hasher, euclidean, ReturnType::Distance, [x, y]).clamp(-1.0, 1.0)
+            }
+        }
+    }
+}
+
+pub(crate) fn octaved_noise(
+    source: &NoiseSource,
+    x: f64,
+    y: f64,
+    octaves: usize,
+    persistence: f64,
+) -> f64 {
+    let mut value = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max_value = 0.0;
+
+    for _ in 0..octaves {
+        value += source.get(x * frequency, y * frequency) * amplitude;
+        max_value += amplitude;
+        amplitude *= persistence;
+        frequency *= 2.0;
+    }
+
+    value / max_value
+}
+
+/// The styles of the landform, which configure the noise of the erodibility and the outlets.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum TerrainPreset {
+    /// A continent rising toward the center.
+    #[default]
+    Continent,
+    /// High and rugged ranges with little sea.
+    Mountainous,
+    /// Islands scattered over the sea.
+    Archipelago,
+    /// Low and gentle land.
+    Plains,
+    /// Highlands dissected by deep valleys.
+    Canyon,
+}
+
+/// The noise from which the erodibility and the outlets of the sites are derived.
+///
+/// The erodibility is `amplitude * noise ^ 2 + center_weight * (1 - d) ^ 2`,
+/// where `d` is the distance from the center of the bounds relative to their size.
+/// A site is an outlet where the noise, blended toward 0.5 at the center by `outlet_falloff`, exceeds `outlet_threshold`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct NoiseParameters {
+    pub(crate) octaves: usize,
+    pub(crate) erodibility_frequency: f64,
+    pub(crate) erodibility_persistence: f64,
+    pub(crate) erodibility_amplitude: f64,
+    pub(crate) center_weight: f64,
+    pub(crate) outlet_frequency: f64,
+    pub(crate) outlet_persistence: f64,
+    pub(crate) outlet_threshold: f64,
+    /// How the outlets concentrate on the outside. Zero places them evenly.
+    pub(crate) outlet_falloff: f64,
+}
+
+impl Default for NoiseParameters {
+    fn default() -> Self {
+        Self::from_preset(TerrainPreset::Continent)
+    }
+}
+
+impl NoiseParameters {
+    pub(crate) fn from_preset(preset: TerrainPreset) -> Self {
+        let continent = Self {
+            octaves: 8,
+            erodibility_frequency: 0.5,
+            erodibility_persistence: 0.55,
+            erodibility_amplitude: 1.0,
+            center_weight: 3.0,
+            outlet_frequency: 1.0,
+            outlet_persistence: 0.5,
+            outlet_threshold: 0.55,
+            outlet_falloff: 1.0,
+        };
+        match preset {
+            TerrainPreset::Continent => continent,
+            TerrainPreset::Mountainous => Self {
+                erodibility_frequency: 1.0,
+                center_weight: 0.5,
+                outlet_threshold: 0.6,
+                ..continent
+            },
+            TerrainPreset::Archipelago => Self {
+                erodibility_amplitude: 2.0,
+                center_weight: 1.0,
+                outlet_frequency: 3.0,
+                outlet_threshold: 0.5,
+                outlet_falloff: 0.0,
+                ..continent
+            },
+            TerrainPreset::Plains => Self {
+                erodibility_amplitude: 2.0,
+                center_weight: 8.0,
+                ..continent
+            },
+            TerrainPreset::Canyon => Self {
+                erodibility_frequency: 2.0,
+                erodibility_persistence: 0.7,
+                erodibility_amplitude: 8.0,
+                center_weight: 0.5,
+                ..continent
+            },
+        }
+    }
+
+    /// Get the erodibility and whether the site is an outlet at `(x, y)` in the coordinates relative to the bounds,
+    /// `dist_from_center` away from the center.
+    pub(crate) fn sample(
+        &self,
+        source: &NoiseSource,
+        x: f64,
+        y: f64,
+        dist_from_center: f64,
+    ) -> (f64, bool) {
+        let noise_erodibility = octaved_noise(
+            source,
+            x * self.erodibility_frequency,
+            y * self.erodibility_frequency,
+            self.octaves,
+            self.erodibility_persistence,
+        )
+        .abs()
+        .powi(2)
+            * self.erodibility_amplitude
+            + (1.0 - dist_from_center).powi(2) * self.center_weight;
+        let blend = dist_from_center.powf(self.outlet_falloff);
+        let noise_is_outlet = (octaved_noise(
+            source,
+            x * self.outlet_frequency,
+            y * self.outlet_frequency,
+            self.octaves,
+            self.outlet_persistence,
+        ) * 0.5
+            + 0.5)
+            * blend
+            + (1.0 - blend) * 0.5;
+        (noise_erodibility, noise_is_outlet > self.outlet_threshold)
+    }
+}
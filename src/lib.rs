@@ -1,20 +1,32 @@
+pub mod biome;
+pub mod config;
+pub mod density;
+pub mod export;
+pub mod landform;
+pub mod river;
 pub mod terrain;
 pub mod transport;
+pub mod world;
 
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
-#[derive(Clone, Copy)]
+#[cfg(all(feature = "parallel", feature = "wasm", target_arch = "wasm32"))]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Site2D {
     pub x: f64,
     pub y: f64,
 }
 
-impl Into<fastlem::models::surface::sites::Site2D> for Site2D {
-    fn into(self) -> fastlem::models::surface::sites::Site2D {
-        fastlem::models::surface::sites::Site2D {
-            x: self.x,
-            y: self.y,
+impl From<Site2D> for fastlem::models::surface::sites::Site2D {
+    fn from(site: Site2D) -> Self {
+        Self {
+            x: site.x,
+            y: site.y,
         }
     }
 }
@@ -0,0 +1,110 @@
+use fastlem::core::traits::Model;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    terrain::{build_model, Terrain, SEA_LEVEL},
+    Site2D,
+};
+
+/// River polylines traced down the drainage of a terrain.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Rivers {
+    polylines: Vec<Vec<Site2D>>,
+    flows: Vec<Vec<f64>>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Rivers {
+    pub fn num_rivers(&self) -> usize {
+        self.polylines.len()
+    }
+
+    /// Get the polyline of the river as flattened `[x0, y0, x1, y1, ...]` from upstream to downstream.
+    pub fn get_river_polyline(&self, index: usize) -> Vec<f64> {
+        self.polylines[index]
+            .iter()
+            .flat_map(|site| [site.x, site.y])
+            .collect::<Vec<_>>()
+    }
+
+    /// Get the drainage area at each vertex of the river polyline.
+    pub fn get_river_flows(&self, index: usize) -> Vec<f64> {
+        self.flows[index].clone()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Terrain {
+    /// Extract the rivers whose drainage area is larger than `threshold`.
+    /// Each site drains to its lowest neighbor, and a river ends at the sea or where it joins another river.
+    pub fn get_rivers(&self, threshold: f64) -> Rivers {
+        let sites = self.sites().collect::<Vec<_>>();
+        let altitudes = self.altitudes();
+        let model = build_model(&sites, self.get_bound_min(), self.get_bound_max()).unwrap();
+
+        let receivers = (0..sites.len())
+            .map(|index| {
+                if altitudes[index] < SEA_LEVEL {
+                    return None;
+                }
+                model
+                    .graph()
+                    .neighbors_of(index)
+                    .iter()
+                    .map(|n| n.0)
+                    .filter(|&neighbor| altitudes[neighbor] < altitudes[index])
+                    .min_by(|&a, &b| altitudes[a].partial_cmp(&altitudes[b]).unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        let mut order = (0..sites.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| altitudes[b].partial_cmp(&altitudes[a]).unwrap());
+        let mut flows = model.areas().to_vec();
+        order.iter().for_each(|&index| {
+            if let Some(receiver) = receivers[index] {
+                flows[receiver] += flows[index];
+            }
+        });
+
+        let is_river = |index: usize| flows[index] > threshold && altitudes[index] >= SEA_LEVEL;
+        let mut has_upstream = vec![false; sites.len()];
+        (0..sites.len()).for_each(|index| {
+            if let Some(receiver) = receivers[index] {
+                if is_river(index) {
+                    has_upstream[receiver] = true;
+                }
+            }
+        });
+
+        let mut visited = vec![false; sites.len()];
+        let mut rivers = Rivers {
+            polylines: vec![],
+            flows: vec![],
+        };
+        // trace from the sources, so that the longest rivers are traced first
+        order
+            .iter()
+            .filter(|&&index| is_river(index) && !has_upstream[index])
+            .for_each(|&source| {
+                let mut polyline = vec![sites[source]];
+                let mut river_flows = vec![flows[source]];
+                visited[source] = true;
+                let mut current = source;
+                while let Some(receiver) = receivers[current] {
+                    polyline.push(sites[receiver]);
+                    river_flows.push(flows[receiver]);
+                    if visited[receiver] || !is_river(receiver) {
+                        break;
+                    }
+                    visited[receiver] = true;
+                    current = receiver;
+                }
+                if polyline.len() >= 2 {
+                    rivers.polylines.push(polyline);
+                    rivers.flows.push(river_flows);
+                }
+            });
+        rivers
+    }
+}
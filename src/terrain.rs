@@ -1,51 +1,141 @@
 use fastlem::core::{parameters::TopographicalParameters, traits::Model};
 use fastlem::lem::generator::TerrainGenerator;
 use fastlem::models::surface::builder::TerrainModel2DBulider;
+use fastlem::models::surface::model::TerrainModel2D;
 use fastlem::models::surface::terrain::Terrain2D;
-use noise::{NoiseFn, Perlin};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize, Serializer};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-use crate::Site2D;
+use crate::{
+    density::DensityMap,
+    landform::{NoiseKind, NoiseParameters, NoiseSource, TerrainPreset},
+    Site2D,
+};
 
-fn octaved_perlin(perlin: &Perlin, x: f64, y: f64, octaves: usize, persistence: f64) -> f64 {
-    let mut value = 0.0;
-    let mut amplitude = 1.0;
-    let mut frequency = 1.0;
-    let mut max_value = 0.0;
+pub(crate) static SEA_LEVEL: f64 = 1e-3;
+/// The distance between the samples of the gradient relative to the shorter side of the bounds.
+const GRADIENT_DELTA_RATIO: f64 = 1e-4;
 
-    for _ in 0..octaves {
-        value += perlin.get([x * frequency, y * frequency]) * amplitude;
-        max_value += amplitude;
-        amplitude *= persistence;
-        frequency *= 2.0;
+/// Apply `f` to every site, in parallel with the `parallel` feature.
+fn map_sites<T, F>(sites: &[Site2D], f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(Site2D) -> T + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        sites.par_iter().map(|site| f(*site)).collect::<Vec<_>>()
     }
+    #[cfg(not(feature = "parallel"))]
+    {
+        sites.iter().map(|site| f(*site)).collect::<Vec<_>>()
+    }
+}
 
-    value / max_value
+/// Run `f` on a thread pool with `threads` threads, or on the global thread pool if it is zero.
+/// Without the `parallel` feature or on wasm, `f` just runs on the current thread.
+#[cfg_attr(
+    any(not(feature = "parallel"), target_arch = "wasm32"),
+    allow(unused_variables)
+)]
+fn in_thread_pool<R, F>(threads: usize, f: F) -> R
+where
+    R: Send,
+    F: FnOnce() -> R + Send,
+{
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    if threads > 0 {
+        if let Ok(pool) = rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+            return pool.install(f);
+        }
+    }
+    f()
+}
+
+/// Rebuild the model of the generated sites, which is consumed during the generation.
+pub(crate) fn build_model(
+    sites: &[Site2D],
+    bound_min: Site2D,
+    bound_max: Site2D,
+) -> Option<TerrainModel2D> {
+    TerrainModel2DBulider::default()
+        .set_sites(sites.iter().map(|site| (*site).into()).collect::<Vec<_>>())
+        .set_bounding_box(Some(bound_min.into()), Some(bound_max.into()))
+        .build()
+        .ok()
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TerrainBuilder {
     bound_min: Site2D,
     bound_max: Site2D,
     node_num: usize,
+    threads: usize,
+    noise: NoiseParameters,
+    noise_kind: NoiseKind,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Deserialize)]
+#[serde(try_from = "SerializedTerrain")]
 pub struct Terrain {
     terrain: Terrain2D,
+    bound_min: Site2D,
+    bound_max: Site2D,
 }
 
-#[wasm_bindgen]
+/// The steepness of the terrain at a site.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug)]
+pub struct Gradient {
+    /// The rise of the altitude per unit of horizontal distance along the steepest direction.
+    pub slope: f64,
+    /// The angle of the steepest downhill direction in radians, which is zero on flat ground.
+    pub direction: f64,
+}
+
+impl Default for TerrainBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl TerrainBuilder {
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new() -> Self {
         Self {
             bound_min: Site2D { x: 0.0, y: 0.0 },
             bound_max: Site2D { x: 0.0, y: 0.0 },
             node_num: 0,
+            threads: 0,
+            noise: NoiseParameters::default(),
+            noise_kind: NoiseKind::Perlin,
         }
     }
 
+    /// Export the parameters as JSON, which can be loaded by `from_json`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Load the parameters written by `to_json`. The missing parameters take the values of `new`.
+    /// Returns `None` if the JSON is broken or has a parameter of a wrong type.
+    pub fn from_json(json: &str) -> Option<TerrainBuilder> {
+        serde_json::from_str(json).ok()
+    }
+
+    /// Create a builder of a terrain over `(0, 0)` to `(200, 100)` with 20000 nodes,
+    /// on which `TransportNetworkBuilder::default_city` grows a city.
+    pub fn default_terrain() -> Self {
+        Self::new().set_bound_max(200.0, 100.0).set_node_num(20000)
+    }
+
     pub fn set_bound_min(self, bound_min_x: f64, bound_min_y: f64) -> Self {
         Self {
             bound_min: Site2D {
@@ -70,61 +160,634 @@ impl TerrainBuilder {
         Self { node_num, ..self }
     }
 
+    /// Set the style of the landform generated by `build`.
+    pub fn set_preset(self, preset: TerrainPreset) -> Self {
+        Self {
+            noise: NoiseParameters::from_preset(preset),
+            ..self
+        }
+    }
+
+    /// Set the kind of the noise of the erodibility and the outlets, which is kept by `set_preset`.
+    pub fn set_noise_kind(self, noise_kind: NoiseKind) -> Self {
+        Self { noise_kind, ..self }
+    }
+
+    /// Set the number of the octaves of the noise, at least one. More octaves add finer roughness.
+    /// This and the other settings of the noise override those of the preset until the next `set_preset`.
+    pub fn set_octaves(self, octaves: usize) -> Self {
+        Self {
+            noise: NoiseParameters {
+                octaves: octaves.max(1),
+                ..self.noise
+            },
+            ..self
+        }
+    }
+
+    /// Set the ratio of the amplitude of each octave to the previous one, of the erodibility and the outlet noise.
+    pub fn set_persistence(self, erodibility_persistence: f64, outlet_persistence: f64) -> Self {
+        Self {
+            noise: NoiseParameters {
+                erodibility_persistence,
+                outlet_persistence,
+                ..self.noise
+            },
+            ..self
+        }
+    }
+
+    /// Set the frequency of the first octave per the size of the bounds, of the erodibility and the outlet noise.
+    pub fn set_base_frequency(self, erodibility_frequency: f64, outlet_frequency: f64) -> Self {
+        Self {
+            noise: NoiseParameters {
+                erodibility_frequency,
+                outlet_frequency,
+                ..self.noise
+            },
+            ..self
+        }
+    }
+
+    /// Set the weight of the noise in the erodibility. Larger amplitudes vary the landform more.
+    pub fn set_amplitude(self, erodibility_amplitude: f64) -> Self {
+        Self {
+            noise: NoiseParameters {
+                erodibility_amplitude,
+                ..self.noise
+            },
+            ..self
+        }
+    }
+
+    /// Set the number of threads to build terrains with the `parallel` feature on native targets.
+    /// Zero uses the global thread pool of rayon, which is also used on wasm.
+    pub fn set_threads(self, threads: usize) -> Self {
+        Self { threads, ..self }
+    }
+
     pub fn build(self, seed: u32) -> Terrain {
-        let model = TerrainModel2DBulider::from_random_sites(
-            self.node_num,
-            self.bound_min.into(),
-            self.bound_max.into(),
-        )
-        .relaxate_sites(1)
+        self.build_u64(seed as u64)
+    }
+
+    /// Same as `build` with a 64-bit seed.
+    /// The seeds below 2^32 build the same terrain as `build`.
+    pub fn build_u64(self, seed: u64) -> Terrain {
+        in_thread_pool(self.threads, move || self.generate(seed))
+    }
+
+    /// Build a terrain with the erodibility and whether the site is an outlet given by the JS function
+    /// `parameters(x, y)`, which returns `[erodibility, isOutlet]`.
+    /// The sites of the terrain are placed by `seed`, so the same seed and parameters build the same terrain.
+    /// Returns `None` if the terrain cannot be generated from the parameters.
+    #[cfg(feature = "wasm")]
+    pub fn build_with_function(self, seed: u64, parameters: &js_sys::Function) -> Option<Terrain> {
+        self.build_with(seed, |sites| {
+            sites
+                .iter()
+                .map(|site| {
+                    let value = parameters
+                        .call2(
+                            &JsValue::NULL,
+                            &JsValue::from_f64(site.x),
+                            &JsValue::from_f64(site.y),
+                        )
+                        .ok()
+                        .map(|value| js_sys::Array::from(&value))
+                        .unwrap_or_default();
+                    (
+                        value.get(0).as_f64().unwrap_or(1.0),
+                        value.get(1).is_truthy(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// Build a terrain with the erodibility sampled from `erodibility`.
+    /// The sites where `outlet` exceeds 0.5 are the outlets, and the sites are placed by `seed`.
+    /// Returns `None` if the terrain cannot be generated from the parameters.
+    pub fn build_with_maps(
+        self,
+        seed: u64,
+        erodibility: &DensityMap,
+        outlet: &DensityMap,
+    ) -> Option<Terrain> {
+        in_thread_pool(self.threads, move || {
+            self.build_with(seed, |sites| {
+                map_sites(sites, |site| {
+                    (
+                        erodibility.sample(site.x, site.y),
+                        outlet.sample(site.x, site.y) > 0.5,
+                    )
+                })
+            })
+        })
+    }
+
+    /// Build terrains for each seed.
+    /// With the `parallel` feature the terrains are generated on the rayon thread pool.
+    pub fn build_seeds(&self, seeds: Vec<u32>) -> Vec<Terrain> {
+        in_thread_pool(self.threads, || {
+            #[cfg(feature = "parallel")]
+            {
+                use rayon::prelude::*;
+                seeds
+                    .par_iter()
+                    .map(|&seed| self.clone().generate(seed as u64))
+                    .collect::<Vec<_>>()
+            }
+            #[cfg(not(feature = "parallel"))]
+            {
+                seeds
+                    .iter()
+                    .map(|&seed| self.clone().generate(seed as u64))
+                    .collect::<Vec<_>>()
+            }
+        })
+    }
+}
+
+impl TerrainBuilder {
+    /// Generate the terrain of the seed on the current thread pool.
+    /// The noise takes the lower 32 bits of the seed, and the upper bits shift the domain of the noise.
+    fn generate(self, seed: u64) -> Terrain {
+        let source = NoiseSource::new(self.noise_kind, seed as u32);
+        let (offset_x, offset_y) = {
+            // the noise repeats every 256 units
+            let upper = seed >> 32;
+            (
+                (upper & 0xffff) as f64 / 65536.0 * 256.0,
+                (upper >> 16) as f64 / 65536.0 * 256.0,
+            )
+        };
+        let (bound_min, bound_max, noise) = (self.bound_min, self.bound_max, self.noise);
+
+        self.build_with(seed, |sites| {
+            map_sites(sites, |site| {
+                let x = site.x / (bound_max.x - bound_min.x);
+                let y = site.y / (bound_max.y - bound_min.y);
+                let dist_from_center = ((x - 0.5).powi(2) + (y - 0.5).powi(2)).sqrt();
+                let (x, y) = (x + offset_x, y + offset_y);
+                noise.sample(&source, x, y, dist_from_center)
+            })
+        })
         .unwrap()
-        .build()
-        .unwrap();
+    }
 
-        let sites = model.sites().to_vec();
+    /// Build a terrain on the sites placed by `sites_seed`, with `parameters(sites)` returning
+    /// the erodibility and whether the site is an outlet for each site.
+    pub(crate) fn build_with<F>(self, sites_seed: u64, parameters: F) -> Option<Terrain>
+    where
+        F: FnOnce(&[Site2D]) -> Vec<(f64, bool)>,
+    {
+        // `from_random_sites` of fastlem takes no seed, so the sites are placed by the seed here
+        let mut rng = StdRng::seed_from_u64(sites_seed);
+        let (bound_min, bound_max) = (self.bound_min, self.bound_max);
+        let sites = (0..self.node_num)
+            .map(|_| {
+                Site2D {
+                    x: bound_min.x + rng.gen::<f64>() * (bound_max.x - bound_min.x),
+                    y: bound_min.y + rng.gen::<f64>() * (bound_max.y - bound_min.y),
+                }
+                .into()
+            })
+            .collect::<Vec<_>>();
+        let model = TerrainModel2DBulider::default()
+            .set_sites(sites)
+            .set_bounding_box(Some(bound_min.into()), Some(bound_max.into()))
+            .relaxate_sites(1)
+            .ok()?
+            .build()
+            .ok()?;
 
-        let perlin = Perlin::new(seed);
+        let sites = model
+            .sites()
+            .iter()
+            .map(|site| Site2D {
+                x: site.x,
+                y: site.y,
+            })
+            .collect::<Vec<_>>();
+        let parameters = parameters(&sites)
+            .into_iter()
+            .map(|(erodibility, is_outlet)| {
+                TopographicalParameters::default()
+                    .set_erodibility(erodibility)
+                    .set_is_outlet(is_outlet)
+            })
+            .collect::<_>();
 
         let terrain = TerrainGenerator::default()
             .set_model(model)
-            .set_parameters(
-                (0..sites.len())
-                    .map(|i| {
-                        let site = sites[i];
-                        let octaves = 8;
-                        let x = site.x / (self.bound_max.x - self.bound_min.x);
-                        let y = site.y / (self.bound_max.y - self.bound_min.y);
-                        let dist_from_center = ((x - 0.5).powi(2) + (y - 0.5).powi(2)).sqrt();
-                        let noise_erodibility =
-                            octaved_perlin(&perlin, x * 0.5, y * 0.5, octaves, 0.55)
-                                .abs()
-                                .powi(2)
-                                * 1.0
-                                + (1.0 - dist_from_center).powi(2) * 3.0;
-                        let noise_is_outlet = (octaved_perlin(&perlin, x, y, octaves, 0.5) * 0.5
-                            + 0.5)
-                            * dist_from_center
-                            + (1.0 - dist_from_center) * 0.5;
-                        TopographicalParameters::default()
-                            .set_erodibility(noise_erodibility)
-                            .set_is_outlet(noise_is_outlet > 0.55)
-                    })
-                    .collect::<_>(),
-            )
+            .set_parameters(parameters)
             .generate()
-            .unwrap();
+            .ok()?;
+
+        Some(Terrain {
+            terrain,
+            bound_min: self.bound_min,
+            bound_max: self.bound_max,
+        })
+    }
+}
+
+impl Terrain {
+    pub(crate) fn from_parts(
+        sites: Vec<Site2D>,
+        altitudes: Vec<f64>,
+        bound_min: Site2D,
+        bound_max: Site2D,
+    ) -> Option<Self> {
+        if sites.len() != altitudes.len() {
+            return None;
+        }
+        let model = build_model(&sites, bound_min, bound_max)?;
+        if model.sites().len() != altitudes.len() {
+            return None;
+        }
+        Some(Self {
+            terrain: model.create_terrain_from_result(&altitudes),
+            bound_min,
+            bound_max,
+        })
+    }
+
+    pub(crate) fn sites(&self) -> impl Iterator<Item = Site2D> + '_ {
+        self.terrain.sites().iter().map(|site| Site2D {
+            x: site.x,
+            y: site.y,
+        })
+    }
+
+    pub(crate) fn altitudes(&self) -> &[f64] {
+        self.terrain.altitudes()
+    }
+}
+
+/// The serialized form of a terrain.
+/// The interpolation is rebuilt from the sites on deserialization.
+#[derive(Serialize, Deserialize)]
+struct SerializedTerrain {
+    bound_min: Site2D,
+    bound_max: Site2D,
+    sites: Vec<Site2D>,
+    altitudes: Vec<f64>,
+}
+
+impl TryFrom<SerializedTerrain> for Terrain {
+    type Error = String;
+
+    fn try_from(serialized: SerializedTerrain) -> Result<Self, Self::Error> {
+        Self::from_parts(
+            serialized.sites,
+            serialized.altitudes,
+            serialized.bound_min,
+            serialized.bound_max,
+        )
+        .ok_or_else(|| "the sites of the terrain are broken".to_string())
+    }
+}
+
+impl Serialize for Terrain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedTerrain {
+            bound_min: self.bound_min,
+            bound_max: self.bound_max,
+            sites: self.sites().collect::<Vec<_>>(),
+            altitudes: self.altitudes().to_vec(),
+        }
+        .serialize(serializer)
+    }
+}
 
-        Terrain { terrain }
+impl Clone for Terrain {
+    fn clone(&self) -> Self {
+        Self::from_parts(
+            self.sites().collect::<Vec<_>>(),
+            self.altitudes().to_vec(),
+            self.bound_min,
+            self.bound_max,
+        )
+        .unwrap()
     }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl Terrain {
     pub fn get_altitude(&self, site_x: f64, site_y: f64) -> Option<f64> {
         let site = Site2D {
             x: site_x,
             y: site_y,
         };
-        self.terrain.get_altitude(&site.into())
+        // the interpolation can be degenerate on the boundary of the sites
+        self.terrain
+            .get_altitude(&site.into())
+            .filter(|altitude| altitude.is_finite())
+    }
+
+    /// Get the altitudes at the sites (`xs[i]`, `ys[i]`) in one call, `NaN` outside the terrain.
+    /// The extra coordinates of the longer slice are ignored.
+    pub fn get_altitudes(&self, xs: &[f64], ys: &[f64]) -> Vec<f64> {
+        let sites = xs
+            .iter()
+            .zip(ys)
+            .map(|(&x, &y)| Site2D { x, y })
+            .collect::<Vec<_>>();
+        map_sites(&sites, |site| {
+            self.get_altitude(site.x, site.y).unwrap_or(f64::NAN)
+        })
+    }
+
+    /// Create a terrain from an elevation grid such as a DEM tile.
+    /// `data` is the row-major altitudes of a `width` x `height` grid whose corner points are placed on
+    /// `bound_min` and `bound_max`, and the rows go from `bound_min_y` to `bound_max_y`.
+    /// The altitudes below the sea level are the sea.
+    /// Returns `None` if the size of `data` does not match or it contains non-finite values.
+    pub fn from_heightmap(
+        width: usize,
+        height: usize,
+        data: Vec<f64>,
+        bound_min_x: f64,
+        bound_min_y: f64,
+        bound_max_x: f64,
+        bound_max_y: f64,
+    ) -> Option<Terrain> {
+        if width < 2 || height < 2 || data.len() != width * height {
+            return None;
+        }
+        if data.iter().any(|altitude| !altitude.is_finite()) {
+            return None;
+        }
+        let bound_min = Site2D {
+            x: bound_min_x,
+            y: bound_min_y,
+        };
+        let bound_max = Site2D {
+            x: bound_max_x,
+            y: bound_max_y,
+        };
+        let sites = (0..height)
+            .flat_map(|iy| {
+                (0..width).map(move |ix| Site2D {
+                    x: bound_min.x + (bound_max.x - bound_min.x) * ix as f64 / (width - 1) as f64,
+                    y: bound_min.y + (bound_max.y - bound_min.y) * iy as f64 / (height - 1) as f64,
+                })
+            })
+            .collect::<Vec<_>>();
+        Self::from_parts(sites, data, bound_min, bound_max)
+    }
+
+    pub fn get_bound_min(&self) -> Site2D {
+        self.bound_min
+    }
+
+    pub fn get_bound_max(&self) -> Site2D {
+        self.bound_max
+    }
+
+    /// Get the gradient of the altitude at the site by the differences around it.
+    /// Returns `None` if the site is outside the terrain.
+    pub fn get_gradient(&self, site_x: f64, site_y: f64) -> Option<Gradient> {
+        let altitude = self.get_altitude(site_x, site_y)?;
+        let delta = (self.bound_max.x - self.bound_min.x).min(self.bound_max.y - self.bound_min.y)
+            * GRADIENT_DELTA_RATIO;
+        // one-sided near the boundary of the terrain
+        let derivative = |minus: Option<f64>, plus: Option<f64>| match (minus, plus) {
+            (Some(minus), Some(plus)) => (plus - minus) / (2.0 * delta),
+            (None, Some(plus)) => (plus - altitude) / delta,
+            (Some(minus), None) => (altitude - minus) / delta,
+            (None, None) => 0.0,
+        };
+        let gradient_x = derivative(
+            self.get_altitude(site_x - delta, site_y),
+            self.get_altitude(site_x + delta, site_y),
+        );
+        let gradient_y = derivative(
+            self.get_altitude(site_x, site_y - delta),
+            self.get_altitude(site_x, site_y + delta),
+        );
+        Some(Gradient {
+            slope: (gradient_x * gradient_x + gradient_y * gradient_y).sqrt(),
+            direction: libm::atan2(-gradient_y, -gradient_x),
+        })
+    }
+
+    /// Sample the altitudes at the centers of `width` x `height` pixels over the bounds, row by row.
+    /// Pixels outside the terrain are `NaN`.
+    pub fn rasterize(&self, width: usize, height: usize) -> Vec<f64> {
+        let pixel_width = (self.bound_max.x - self.bound_min.x) / width as f64;
+        let pixel_height = (self.bound_max.y - self.bound_min.y) / height as f64;
+        (0..height)
+            .flat_map(|iy| {
+                (0..width).map(move |ix| {
+                    let x = self.bound_min.x + (ix as f64 + 0.5) * pixel_width;
+                    let y = self.bound_min.y + (iy as f64 + 0.5) * pixel_height;
+                    self.get_altitude(x, y).unwrap_or(f64::NAN)
+                })
+            })
+            .collect::<Vec<_>>()
+    }
+
+    /// Shade `width` x `height` pixels over the bounds, row by row, as lit from the direction `azimuth` in radians
+    /// from the x axis toward the y axis, at `altitude_angle` in radians above the horizon.
+    /// The intensity is from 0 for the slopes facing away from the light to 255 for those facing it, and 0 outside the terrain.
+    pub fn hillshade(
+        &self,
+        width: usize,
+        height: usize,
+        azimuth: f64,
+        altitude_angle: f64,
+    ) -> Vec<u8> {
+        let altitudes = self.rasterize(width, height);
+        let pixel_width = (self.bound_max.x - self.bound_min.x) / width as f64;
+        let pixel_height = (self.bound_max.y - self.bound_min.y) / height as f64;
+        let light = [
+            libm::cos(altitude_angle) * libm::cos(azimuth),
+            libm::cos(altitude_angle) * libm::sin(azimuth),
+            libm::sin(altitude_angle),
+        ];
+        // the neighboring pixel, or the pixel itself at the boundary of the terrain
+        let altitude_at = |ix: usize, iy: usize, fallback: f64| {
+            let altitude = altitudes[iy * width + ix];
+            if altitude.is_finite() {
+                altitude
+            } else {
+                fallback
+            }
+        };
+        (0..height)
+            .flat_map(|iy| {
+                let altitudes = &altitudes;
+                (0..width).map(move |ix| {
+                    let altitude = altitudes[iy * width + ix];
+                    if !altitude.is_finite() {
+                        return 0;
+                    }
+                    let gradient_x = (altitude_at((ix + 1).min(width - 1), iy, altitude)
+                        - altitude_at(ix.saturating_sub(1), iy, altitude))
+                        / (2.0 * pixel_width);
+                    let gradient_y = (altitude_at(ix, (iy + 1).min(height - 1), altitude)
+                        - altitude_at(ix, iy.saturating_sub(1), altitude))
+                        / (2.0 * pixel_height);
+                    let normal_length =
+                        (gradient_x * gradient_x + gradient_y * gradient_y + 1.0).sqrt();
+                    let intensity =
+                        (-gradient_x * light[0] - gradient_y * light[1] + light[2]) / normal_length;
+                    (intensity.clamp(0.0, 1.0) * 255.0).round() as u8
+                })
+            })
+            .collect::<Vec<_>>()
+    }
+
+    /// Compute the distance to the nearest sea on a `width` x `height` grid whose corner points are placed on the bounds.
+    /// The distance is 0 on the sea, and the points outside the terrain are not regarded as the sea.
+    /// Returns `None` if there is no sea on the grid.
+    pub fn get_water_distance_map(&self, width: usize, height: usize) -> Option<DensityMap> {
+        let site_of = |index: usize| {
+            let (ix, iy) = (index % width, index / width);
+            Site2D {
+                x: self.bound_min.x
+                    + (self.bound_max.x - self.bound_min.x) * ix as f64
+                        / width.saturating_sub(1).max(1) as f64,
+                y: self.bound_min.y
+                    + (self.bound_max.y - self.bound_min.y) * iy as f64
+                        / height.saturating_sub(1).max(1) as f64,
+            }
+        };
+
+        // propagate the nearest sea point forth and back over the grid
+        let mut nearest = (0..width * height)
+            .map(|index| {
+                let site = site_of(index);
+                self.get_altitude(site.x, site.y)
+                    .filter(|altitude| *altitude < SEA_LEVEL)
+                    .map(|_| site)
+            })
+            .collect::<Vec<_>>();
+        if nearest.iter().all(|site| site.is_none()) {
+            return None;
+        }
+        let distance_of = |index: usize, site: Option<Site2D>| {
+            site.map_or(f64::MAX, |site| {
+                let point = site_of(index);
+                ((point.x - site.x).powi(2) + (point.y - site.y).powi(2)).sqrt()
+            })
+        };
+        let forward = [(-1, 0), (-1, -1), (0, -1), (1, -1)];
+        let backward = [(1, 0), (1, 1), (0, 1), (-1, 1)];
+        let mut relax = |ix: usize, iy: usize, offsets: &[(i64, i64)]| {
+            let index = iy * width + ix;
+            offsets.iter().for_each(|(dx, dy)| {
+                let (nx, ny) = (ix as i64 + dx, iy as i64 + dy);
+                if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                    return;
+                }
+                let candidate = nearest[ny as usize * width + nx as usize];
+                if distance_of(index, candidate) < distance_of(index, nearest[index]) {
+                    nearest[index] = candidate;
+                }
+            });
+        };
+        (0..height).for_each(|iy| {
+            (0..width).for_each(|ix| relax(ix, iy, &forward));
+            (0..width).rev().for_each(|ix| relax(ix, iy, &[(1, 0)]));
+        });
+        (0..height).rev().for_each(|iy| {
+            (0..width).rev().for_each(|ix| relax(ix, iy, &backward));
+            (0..width).for_each(|ix| relax(ix, iy, &[(-1, 0)]));
+        });
+
+        Some(DensityMap::new(
+            width,
+            height,
+            self.bound_min.x,
+            self.bound_min.y,
+            self.bound_max.x,
+            self.bound_max.y,
+            (0..width * height)
+                .map(|index| distance_of(index, nearest[index]))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    /// Suggest up to `num` sites suitable for cities, separated by at least `min_distance`.
+    /// Flat lowlands above the sea level are preferred.
+    pub fn suggest_sites(&self, num: usize, min_distance: f64, seed: u32) -> Vec<Site2D> {
+        self.suggest_sites_u64(num, min_distance, seed as u64)
+    }
+
+    /// Same as `suggest_sites` with a 64-bit seed.
+    /// The seeds below 2^32 suggest the same sites as `suggest_sites`.
+    pub fn suggest_sites_u64(&self, num: usize, min_distance: f64, seed: u64) -> Vec<Site2D> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let width = self.bound_max.x - self.bound_min.x;
+        let height = self.bound_max.y - self.bound_min.y;
+        if num == 0 || width <= 0.0 || height <= 0.0 {
+            return vec![];
+        }
+        let delta = width.min(height) * 0.01;
+
+        let mut candidates = (0..num * 64)
+            .filter_map(|_| {
+                let site = Site2D {
+                    x: rng.gen_range(self.bound_min.x..self.bound_max.x),
+                    y: rng.gen_range(self.bound_min.y..self.bound_max.y),
+                };
+                let altitude = self.get_altitude(site.x, site.y)?;
+                if altitude < SEA_LEVEL {
+                    return None;
+                }
+                let altitude_x = self.get_altitude(site.x + delta, site.y)?;
+                let altitude_y = self.get_altitude(site.x, site.y + delta)?;
+                let slope = ((altitude_x - altitude).powi(2) + (altitude_y - altitude).powi(2))
+                    .sqrt()
+                    / delta;
+                let score = 1.0 / (1.0 + slope * 10.0 + altitude);
+                Some((site, score))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+        let mut sites: Vec<Site2D> = vec![];
+        for (site, _) in candidates {
+            if sites.len() >= num {
+                break;
+            }
+            let is_isolated = sites.iter().all(|other| {
+                (other.x - site.x).powi(2) + (other.y - site.y).powi(2) >= min_distance.powi(2)
+            });
+            if is_isolated {
+                sites.push(site);
+            }
+        }
+        sites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get the bits of the coordinates of the sites of the terrain built by `seed`.
+    fn build(seed: u64) -> Vec<(u64, u64)> {
+        let (bound_min, bound_max) = (Site2D { x: 0.0, y: 0.0 }, Site2D { x: 20.0, y: 10.0 });
+        let erodibility = DensityMap::from_fn(8, 8, bound_min, bound_max, |x, _| 1.0 + x / 20.0);
+        // the outlets are the sites within 1 from the left edge
+        let outlet = DensityMap::from_fn(8, 8, bound_min, bound_max, |x, _| 1.0 - x / 2.0);
+        TerrainBuilder::new()
+            .set_bound_max(20.0, 10.0)
+            .set_node_num(500)
+            .build_with_maps(seed, &erodibility, &outlet)
+            .unwrap()
+            .sites()
+            .map(|site| (site.x.to_bits(), site.y.to_bits()))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_places_the_same_sites() {
+        assert_eq!(build(7), build(7));
+        assert_ne!(build(7), build(8));
     }
 }
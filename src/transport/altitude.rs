@@ -0,0 +1,70 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{terrain::Terrain, Site2D};
+
+/// The bits of a grid point whose altitude is not sampled yet, a NaN which the terrain never returns.
+const UNSAMPLED: u64 = 0x7ff8_dead_beef_0001;
+
+/// The altitudes of the terrain on a regular grid over its bounds, sampled at the first use of each grid point,
+/// which are interpolated bilinearly instead of interpolating the sites of the terrain.
+pub(crate) struct AltitudeCache {
+    width: usize,
+    height: usize,
+    bound_min: Site2D,
+    bound_max: Site2D,
+    /// The bits of the altitudes of the grid points row by row, `NaN` outside the terrain.
+    altitudes: Vec<AtomicU64>,
+}
+
+impl AltitudeCache {
+    /// Make a `width` x `height` grid whose corner points are on the bounds of `terrain`.
+    pub(crate) fn new(terrain: &Terrain, width: usize, height: usize) -> Self {
+        let (width, height) = (width.max(2), height.max(2));
+        Self {
+            width,
+            height,
+            bound_min: terrain.get_bound_min(),
+            bound_max: terrain.get_bound_max(),
+            altitudes: (0..width * height)
+                .map(|_| AtomicU64::new(UNSAMPLED))
+                .collect::<Vec<_>>(),
+        }
+    }
+
+    fn grid_altitude(&self, terrain: &Terrain, ix: usize, iy: usize) -> f64 {
+        let cell = &self.altitudes[iy * self.width + ix];
+        let bits = cell.load(Ordering::Relaxed);
+        if bits != UNSAMPLED {
+            return f64::from_bits(bits);
+        }
+        let x = self.bound_min.x
+            + (self.bound_max.x - self.bound_min.x) * ix as f64 / (self.width - 1) as f64;
+        let y = self.bound_min.y
+            + (self.bound_max.y - self.bound_min.y) * iy as f64 / (self.height - 1) as f64;
+        let altitude = terrain.get_altitude(x, y).unwrap_or(f64::NAN);
+        cell.store(altitude.to_bits(), Ordering::Relaxed);
+        altitude
+    }
+
+    /// Get the altitude at `site` from the grid.
+    /// Near the boundary of the terrain, where a grid point around `site` is outside it, `terrain` is queried directly.
+    pub(crate) fn get_altitude(&self, terrain: &Terrain, site: Site2D) -> Option<f64> {
+        let fx = (site.x - self.bound_min.x) / (self.bound_max.x - self.bound_min.x);
+        let fy = (site.y - self.bound_min.y) / (self.bound_max.y - self.bound_min.y);
+        if !(0.0..=1.0).contains(&fx) || !(0.0..=1.0).contains(&fy) {
+            return terrain.get_altitude(site.x, site.y);
+        }
+        let gx = fx * (self.width - 1) as f64;
+        let gy = fy * (self.height - 1) as f64;
+        let (ix, iy) = (gx.floor() as usize, gy.floor() as usize);
+        let (ix1, iy1) = ((ix + 1).min(self.width - 1), (iy + 1).min(self.height - 1));
+        let (tx, ty) = (gx - ix as f64, gy - iy as f64);
+        let altitude = |ix: usize, iy: usize| self.grid_altitude(terrain, ix, iy);
+        let interpolated = (altitude(ix, iy) * (1.0 - tx) + altitude(ix1, iy) * tx) * (1.0 - ty)
+            + (altitude(ix, iy1) * (1.0 - tx) + altitude(ix1, iy1) * tx) * ty;
+        if interpolated.is_nan() {
+            return terrain.get_altitude(site.x, site.y);
+        }
+        Some(interpolated)
+    }
+}
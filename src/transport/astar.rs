@@ -0,0 +1,146 @@
+use std::collections::BinaryHeap;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::{
+    math::get_distance,
+    oneway::OneWay,
+    transport::{Edge, TransportNetwork},
+};
+
+struct Visit {
+    index: usize,
+    estimated_cost: f64,
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.estimated_cost.total_cmp(&self.estimated_cost)
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.estimated_cost == other.estimated_cost
+    }
+}
+
+impl Eq for Visit {}
+
+impl TransportNetwork {
+    /// Find the route of the least cost from `start` to `goal` by A*, following the one-way streets,
+    /// and return its nodes from `start` to `goal`, or `None` if `goal` cannot be reached.
+    ///
+    /// `cost(edge, length)` gives the cost of traveling the edge from `edge.start_index` to `edge.end_index`, whose polyline is `length` long.
+    /// A negative or non-finite cost closes the edge.
+    /// The search is guided by the straight distance to `goal` times `min_cost_per_length`, which must not exceed
+    /// the cost per length of any edge for the route to be the least-cost one. Zero makes the search Dijkstra's algorithm.
+    pub fn find_route_by<F>(
+        &self,
+        start: usize,
+        goal: usize,
+        min_cost_per_length: f64,
+        cost: F,
+    ) -> Option<Vec<usize>>
+    where
+        F: Fn(&Edge, f64) -> f64,
+    {
+        let num_nodes = self.nodes.len();
+        if start >= num_nodes || goal >= num_nodes {
+            return None;
+        }
+        let heuristic =
+            |index: usize| get_distance(self.nodes[index], self.nodes[goal]) * min_cost_per_length;
+
+        let mut costs = vec![f64::MAX; num_nodes];
+        let mut previous = vec![None; num_nodes];
+        let mut is_closed = vec![false; num_nodes];
+        let mut heap = BinaryHeap::new();
+        costs[start] = 0.0;
+        heap.push(Visit {
+            index: start,
+            estimated_cost: heuristic(start),
+        });
+        while let Some(visit) = heap.pop() {
+            if is_closed[visit.index] {
+                continue;
+            }
+            if visit.index == goal {
+                let mut route = vec![goal];
+                while let Some(index) = previous[route[route.len() - 1]] {
+                    route.push(index);
+                }
+                route.reverse();
+                return Some(route);
+            }
+            is_closed[visit.index] = true;
+            self.graph
+                .neighbors_of(visit.index)
+                .iter()
+                .filter(|n| {
+                    !is_closed[n.0]
+                        && n.1
+                            .one_way
+                            .allows(&self.nodes[visit.index], &self.nodes[n.0])
+                })
+                .for_each(|n| {
+                    let edge = Edge {
+                        start_index: visit.index,
+                        end_index: n.0,
+                        road_class: n.1.path_attr.road_class,
+                        is_one_way: n.1.one_way != OneWay::TwoWay,
+                        is_highway: n.1.path_attr.is_highway(),
+                        is_even: n.1.path_attr.is_even,
+                        is_bridge: n.1.path_attr.is_bridge,
+                        cost: n.1.cost,
+                    };
+                    let edge_cost = cost(&edge, self.get_edge_length(visit.index, n.0));
+                    if !edge_cost.is_finite() || edge_cost < 0.0 {
+                        return;
+                    }
+                    let next_cost = costs[visit.index] + edge_cost;
+                    if next_cost < costs[n.0] {
+                        costs[n.0] = next_cost;
+                        previous[n.0] = Some(visit.index);
+                        heap.push(Visit {
+                            index: n.0,
+                            estimated_cost: next_cost + heuristic(n.0),
+                        });
+                    }
+                });
+        }
+        None
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Find the route of the least cost from `start` to `goal` as `find_route_by`,
+    /// with the cost given by `cost(edge, length)` in JavaScript. A call which throws closes the edge.
+    #[cfg(feature = "wasm")]
+    pub fn find_route_with(
+        &self,
+        start: usize,
+        goal: usize,
+        min_cost_per_length: f64,
+        cost: &js_sys::Function,
+    ) -> Option<Vec<usize>> {
+        self.find_route_by(start, goal, min_cost_per_length, |edge, length| {
+            cost.call2(
+                &JsValue::NULL,
+                &JsValue::from(*edge),
+                &JsValue::from_f64(length),
+            )
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or(f64::NAN)
+        })
+    }
+}
@@ -0,0 +1,71 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    transport::{
+        road_class::RoadClass,
+        transport::{PathAttr, SnapPreference},
+        treeobj::{PathTree, PathTreeQuery},
+    },
+    Site2D,
+};
+
+/// A path tree filled with random short paths and the paths to look up in it,
+/// which exposes `PathTree::find` to the benchmarks.
+pub struct PathTreeBench {
+    tree: PathTree,
+    queries: Vec<(Site2D, Site2D)>,
+}
+
+impl PathTreeBench {
+    /// Scatter `num_paths` paths of `path_length` and as many queries in a square
+    /// in which the paths are about `path_length` apart.
+    pub fn new(num_paths: usize, path_length: f64, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let size = path_length * (num_paths as f64).sqrt();
+        let mut random_path = || {
+            let start = Site2D {
+                x: rng.gen_range(0.0..size),
+                y: rng.gen_range(0.0..size),
+            };
+            let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+            let end = Site2D {
+                x: start.x + path_length * angle.cos(),
+                y: start.y + path_length * angle.sin(),
+            };
+            (start, end)
+        };
+        let mut tree = PathTree::new();
+        (0..num_paths).for_each(|i| {
+            let (start, end) = random_path();
+            tree.insert(
+                i * 2,
+                i * 2 + 1,
+                start,
+                end,
+                PathAttr {
+                    road_class: RoadClass::Local,
+                    is_even: false,
+                    is_bridge: false,
+                },
+                path_length,
+            );
+        });
+        let queries = (0..num_paths).map(|_| random_path()).collect::<Vec<_>>();
+        Self { tree, queries }
+    }
+
+    /// Look up all the queries with the look-ahead distance `diameter`,
+    /// and return the number of them which found a path, so that the work is not optimized away.
+    pub fn run(&self, diameter: f64) -> usize {
+        self.queries
+            .iter()
+            .filter(|(start, end)| {
+                !matches!(
+                    self.tree
+                        .find(start, end, diameter, &[], SnapPreference::SitesFirst),
+                    PathTreeQuery::None
+                )
+            })
+            .count()
+    }
+}
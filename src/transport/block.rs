@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{transport::transport::TransportNetwork, Site2D};
+
+/// Closed polygons bounded by roads.
+/// Each polygon is counterclockwise, and its last vertex is not repeated.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Blocks {
+    pub(crate) node_cycles: Vec<Vec<usize>>,
+    pub(crate) polygons: Vec<Vec<Site2D>>,
+}
+
+pub(crate) fn get_signed_area(polygon: &[Site2D]) -> f64 {
+    (0..polygon.len())
+        .map(|i| {
+            let a = polygon[i];
+            let b = polygon[(i + 1) % polygon.len()];
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        * 0.5
+}
+
+/// Remove the dead ends walked forth and back, such as `b` and `c` in `[a, b, c, b, d]`.
+fn remove_spikes(cycle: &[usize]) -> Vec<usize> {
+    let mut cycle = cycle.to_vec();
+    loop {
+        let len = cycle.len();
+        if len < 3 {
+            return vec![];
+        }
+        let spike = (0..len).find(|&i| cycle[i] == cycle[(i + 2) % len]);
+        match spike {
+            Some(i) => {
+                // remove the tip and one of the repeated nodes
+                let (tip, repeated) = ((i + 1) % len, (i + 2) % len);
+                let (first, second) = (tip.max(repeated), tip.min(repeated));
+                cycle.remove(first);
+                cycle.remove(second);
+            }
+            None => return cycle,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Blocks {
+    pub fn num_blocks(&self) -> usize {
+        self.polygons.len()
+    }
+
+    /// Get the polygon of the block as flattened `[x0, y0, x1, y1, ...]`.
+    pub fn get_block_polygon(&self, index: usize) -> Vec<f64> {
+        self.polygons[index]
+            .iter()
+            .flat_map(|site| [site.x, site.y])
+            .collect::<Vec<_>>()
+    }
+
+    /// Get the nodes around the block in counterclockwise order.
+    pub fn get_block_nodes(&self, index: usize) -> Vec<usize> {
+        self.node_cycles[index].clone()
+    }
+
+    pub fn get_block_area(&self, index: usize) -> f64 {
+        get_signed_area(&self.polygons[index])
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Extract the blocks enclosed by roads by walking the faces of the planar embedding.
+    /// The unbounded face outside of each connected component is excluded.
+    pub fn extract_blocks(&self) -> Blocks {
+        // the neighbors of each node in counterclockwise order
+        let sorted_neighbors = (0..self.nodes.len())
+            .map(|index| {
+                let mut neighbors = self
+                    .graph
+                    .neighbors_of(index)
+                    .iter()
+                    .map(|n| {
+                        let site = self.nodes[index];
+                        let next = self.get_polyline(index, n.0)[1];
+                        (n.0, libm::atan2(next.y - site.y, next.x - site.x))
+                    })
+                    .collect::<Vec<_>>();
+                neighbors.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+                neighbors.into_iter().map(|n| n.0).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let positions = sorted_neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(index, neighbors)| {
+                neighbors
+                    .iter()
+                    .enumerate()
+                    .map(move |(position, neighbor)| ((index, *neighbor), position))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut visited = sorted_neighbors
+            .iter()
+            .map(|neighbors| vec![false; neighbors.len()])
+            .collect::<Vec<_>>();
+        let mut blocks = Blocks {
+            node_cycles: vec![],
+            polygons: vec![],
+        };
+
+        (0..self.nodes.len()).for_each(|start| {
+            (0..sorted_neighbors[start].len()).for_each(|start_position| {
+                if visited[start][start_position] {
+                    return;
+                }
+                // keep the face on the left by turning to the next road clockwise
+                let mut cycle = vec![];
+                let (mut from, mut position) = (start, start_position);
+                while !visited[from][position] {
+                    visited[from][position] = true;
+                    cycle.push(from);
+                    let to = sorted_neighbors[from][position];
+                    let degree = sorted_neighbors[to].len();
+                    position = (positions[&(to, from)] + degree - 1) % degree;
+                    from = to;
+                }
+
+                let cycle = remove_spikes(&cycle);
+                if cycle.is_empty() {
+                    return;
+                }
+                let polygon = (0..cycle.len())
+                    .flat_map(|i| {
+                        let mut polyline =
+                            self.get_polyline(cycle[i], cycle[(i + 1) % cycle.len()]);
+                        polyline.pop();
+                        polyline
+                    })
+                    .collect::<Vec<_>>();
+                if get_signed_area(&polygon) > 0.0 {
+                    blocks.node_cycles.push(cycle);
+                    blocks.polygons.push(polygon);
+                }
+            });
+        });
+        blocks
+    }
+}
@@ -0,0 +1,231 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        math::get_distance, parcel::Parcels, road_class::RoadClass, transport::TransportNetwork,
+    },
+    Site2D,
+};
+
+/// The footprints of the buildings on the parcels, with their heights.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Buildings {
+    polygons: Vec<Vec<Site2D>>,
+    heights: Vec<f64>,
+    parcels: Vec<usize>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Buildings {
+    pub fn num_buildings(&self) -> usize {
+        self.polygons.len()
+    }
+
+    /// Get the counterclockwise footprint of the building as flattened `[x0, y0, x1, y1, ...]`.
+    pub fn get_building_polygon(&self, index: usize) -> Vec<f64> {
+        self.polygons[index]
+            .iter()
+            .flat_map(|site| [site.x, site.y])
+            .collect::<Vec<_>>()
+    }
+
+    pub fn get_building_height(&self, index: usize) -> f64 {
+        self.heights[index]
+    }
+
+    /// Get the index of the parcel on which the building stands.
+    pub fn get_building_parcel(&self, index: usize) -> usize {
+        self.parcels[index]
+    }
+}
+
+/// The weight of the road class in the intensity of the buildings facing it.
+fn get_class_weight(road_class: RoadClass) -> f64 {
+    match road_class {
+        RoadClass::Motorway => 1.0,
+        RoadClass::Arterial => 0.8,
+        RoadClass::Collector => 0.6,
+        RoadClass::Local => 0.4,
+    }
+}
+
+/// Places a rectangular or L-shaped building on each parcel.
+///
+/// The intensity of a parcel is the mean of the weight of the class of the road it faces
+/// and its closeness to the center, which falls linearly to zero at the center radius.
+/// The parcels of higher intensity are built more often, with larger and taller buildings.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct BuildingBuilder {
+    center: Site2D,
+    center_radius: f64,
+    setback: f64,
+    min_occupancy: f64,
+    min_height: f64,
+    max_height: f64,
+    l_shape_probability: f64,
+}
+
+impl Default for BuildingBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BuildingBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            center: Site2D { x: 0.0, y: 0.0 },
+            center_radius: 0.0,
+            setback: 0.01,
+            min_occupancy: 0.5,
+            min_height: 0.01,
+            max_height: 0.1,
+            l_shape_probability: 0.2,
+        }
+    }
+
+    /// Set the center of the city, around which the buildings are denser and taller.
+    pub fn set_center(self, center_x: f64, center_y: f64) -> Self {
+        Self {
+            center: Site2D {
+                x: center_x,
+                y: center_y,
+            },
+            ..self
+        }
+    }
+
+    /// Set the distance from the center at which its influence vanishes. Zero disables the influence of the center.
+    pub fn set_center_radius(self, center_radius: f64) -> Self {
+        Self {
+            center_radius,
+            ..self
+        }
+    }
+
+    /// Set the gap between the footprints and the sides of the parcels.
+    pub fn set_setback(self, setback: f64) -> Self {
+        Self { setback, ..self }
+    }
+
+    /// Set the probability that a parcel of zero intensity is built, which rises to one at full intensity.
+    pub fn set_min_occupancy(self, min_occupancy: f64) -> Self {
+        Self {
+            min_occupancy,
+            ..self
+        }
+    }
+
+    /// Set the heights of the buildings at zero and at full intensity.
+    pub fn set_height_range(self, min_height: f64, max_height: f64) -> Self {
+        Self {
+            min_height,
+            max_height,
+            ..self
+        }
+    }
+
+    /// Set the probability that a footprint is L-shaped rather than rectangular.
+    pub fn set_l_shape_probability(self, l_shape_probability: f64) -> Self {
+        Self {
+            l_shape_probability,
+            ..self
+        }
+    }
+
+    pub fn build(&self, seed: u32, network: &TransportNetwork, parcels: &Parcels) -> Buildings {
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let mut buildings = Buildings {
+            polygons: vec![],
+            heights: vec![],
+            parcels: vec![],
+        };
+
+        parcels
+            .polygons
+            .iter()
+            .zip(parcels.edges.iter())
+            .enumerate()
+            .for_each(|(parcel_index, (lot, (start, end)))| {
+                let (_, attr) = network.graph.has_edge(*start, *end);
+                let lot_center = Site2D {
+                    x: (lot[0].x + lot[2].x) * 0.5,
+                    y: (lot[0].y + lot[2].y) * 0.5,
+                };
+                let closeness = if self.center_radius > 0.0 {
+                    (1.0 - get_distance(lot_center, self.center) / self.center_radius).max(0.0)
+                } else {
+                    0.0
+                };
+                let intensity = (get_class_weight(attr.path_attr.road_class) + closeness) * 0.5;
+                let occupancy = self.min_occupancy + (1.0 - self.min_occupancy) * intensity;
+                if !rng.gen_bool(occupancy.clamp(0.0, 1.0)) {
+                    return;
+                }
+
+                // the lot is a rectangle: `u` runs along the road and `v` runs into the block
+                let width = get_distance(lot[0], lot[1]);
+                let depth = get_distance(lot[0], lot[3]);
+                let (u_max, v_max) = (width - self.setback, depth * (0.5 + 0.4 * intensity));
+                let (u_min, v_min) = (self.setback, self.setback);
+                if u_max <= u_min || v_max <= v_min {
+                    return;
+                }
+                let at = |u: f64, v: f64| Site2D {
+                    x: lot[0].x
+                        + (lot[1].x - lot[0].x) * u / width
+                        + (lot[3].x - lot[0].x) * v / depth,
+                    y: lot[0].y
+                        + (lot[1].y - lot[0].y) * u / width
+                        + (lot[3].y - lot[0].y) * v / depth,
+                };
+                let polygon = if rng.gen_bool(self.l_shape_probability.clamp(0.0, 1.0)) {
+                    // cut the back corner on either side
+                    let u_cut = u_min + (u_max - u_min) * rng.gen_range(0.4..0.7);
+                    let v_cut = v_min + (v_max - v_min) * rng.gen_range(0.4..0.7);
+                    if rng.gen_bool(0.5) {
+                        vec![
+                            at(u_min, v_min),
+                            at(u_max, v_min),
+                            at(u_max, v_cut),
+                            at(u_cut, v_cut),
+                            at(u_cut, v_max),
+                            at(u_min, v_max),
+                        ]
+                    } else {
+                        let u_cut = u_min + u_max - u_cut;
+                        vec![
+                            at(u_min, v_min),
+                            at(u_max, v_min),
+                            at(u_max, v_max),
+                            at(u_cut, v_max),
+                            at(u_cut, v_cut),
+                            at(u_min, v_cut),
+                        ]
+                    }
+                } else {
+                    vec![
+                        at(u_min, v_min),
+                        at(u_max, v_min),
+                        at(u_max, v_max),
+                        at(u_min, v_max),
+                    ]
+                };
+                let height = self.min_height
+                    + (self.max_height - self.min_height)
+                        * intensity
+                        * intensity
+                        * rng.gen_range(0.8..1.2);
+
+                buildings.polygons.push(polygon);
+                buildings.heights.push(height);
+                buildings.parcels.push(parcel_index);
+            });
+        buildings
+    }
+}
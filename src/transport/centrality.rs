@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::{shortest::shortest_path_tree, transport::TransportNetwork};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Compute the betweenness centrality of each edge, in the order of `get_edges`:
+    /// the number of the shortest paths between pairs of nodes that pass through the edge,
+    /// following the one-way streets and weighted by the lengths of the edges.
+    ///
+    /// The paths are taken from `sample_size` origins at equal intervals of the node indices
+    /// and the result is scaled to estimate the paths from all the nodes.
+    /// All the nodes are taken as origins if `sample_size` is zero or not less than the number of the nodes.
+    pub fn edge_betweenness(&self, sample_size: usize) -> Vec<f64> {
+        let num_nodes = self.nodes.len();
+        let edge_positions = self
+            .edges()
+            .enumerate()
+            .map(|(position, edge)| {
+                (
+                    (
+                        edge.start_index.min(edge.end_index),
+                        edge.start_index.max(edge.end_index),
+                    ),
+                    position,
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let mut betweenness = vec![0.0; edge_positions.len()];
+
+        let origins = (0..num_nodes)
+            .filter(|index| !self.graph.neighbors_of(*index).is_empty())
+            .collect::<Vec<_>>();
+        let sample_size = if sample_size == 0 {
+            origins.len()
+        } else {
+            sample_size.min(origins.len())
+        };
+        if sample_size == 0 {
+            return betweenness;
+        }
+        let stride = origins.len() as f64 / sample_size as f64;
+        let scale = origins.len() as f64 / sample_size as f64;
+
+        let mut dependencies = vec![0.0; num_nodes];
+        (0..sample_size).for_each(|sample| {
+            let origin = origins[(sample as f64 * stride) as usize];
+            let tree = shortest_path_tree(num_nodes, origin, |index| {
+                self.get_out_neighbors(index)
+                    .iter()
+                    .map(|neighbor| (neighbor.index, self.get_edge_length(index, neighbor.index)))
+                    .collect::<Vec<_>>()
+            });
+            // accumulate the paths to the farther nodes back to the origin
+            tree.order
+                .iter()
+                .for_each(|index| dependencies[*index] = 0.0);
+            tree.order.iter().rev().for_each(|index| {
+                if let Some(from) = tree.previous[*index] {
+                    let dependency = 1.0 + dependencies[*index];
+                    dependencies[from] += dependency;
+                    betweenness[edge_positions[&(from.min(*index), from.max(*index))]] +=
+                        dependency * scale;
+                }
+            });
+        });
+        betweenness
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+
+use terrain_graph::edge_attributed_undirected::EdgeAttributedUndirectedGraph;
+
+fn edge_key(index_a: usize, index_b: usize) -> (usize, usize) {
+    (index_a.min(index_b), index_a.max(index_b))
+}
+
+/// Split the subgraph made of the edges accepted by `filter` into chains of node indices.
+/// Every interior node of a chain has exactly two accepted edges.
+/// Closed loops without any junction are returned with the same node at both ends.
+pub(crate) fn extract_chains<A, F>(
+    graph: &EdgeAttributedUndirectedGraph<A>,
+    filter: F,
+) -> Vec<Vec<usize>>
+where
+    A: Copy + Clone + Default,
+    F: Fn(&A) -> bool,
+{
+    let degrees = (0..graph.order())
+        .map(|index| {
+            graph
+                .neighbors_of(index)
+                .iter()
+                .filter(|n| filter(&n.1))
+                .count()
+        })
+        .collect::<Vec<_>>();
+
+    let mut visited = HashSet::new();
+    let mut chains = vec![];
+
+    let walk = |start: usize, visited: &mut HashSet<(usize, usize)>| {
+        let mut chains_from_start = vec![];
+        graph.neighbors_of(start).iter().for_each(|first| {
+            if !filter(&first.1) || visited.contains(&edge_key(start, first.0)) {
+                return;
+            }
+            visited.insert(edge_key(start, first.0));
+            let mut chain = vec![start, first.0];
+            let mut current = first.0;
+            while degrees[current] == 2 && current != start {
+                let next = graph
+                    .neighbors_of(current)
+                    .iter()
+                    .find(|n| filter(&n.1) && !visited.contains(&edge_key(current, n.0)));
+                if let Some(next) = next {
+                    visited.insert(edge_key(current, next.0));
+                    chain.push(next.0);
+                    current = next.0;
+                } else {
+                    break;
+                }
+            }
+            chains_from_start.push(chain);
+        });
+        chains_from_start
+    };
+
+    // chains between junctions or dead ends
+    (0..graph.order()).for_each(|index| {
+        if degrees[index] != 2 && degrees[index] > 0 {
+            chains.extend(walk(index, &mut visited));
+        }
+    });
+
+    // isolated loops
+    (0..graph.order()).for_each(|index| {
+        if degrees[index] == 2 {
+            chains.extend(walk(index, &mut visited));
+        }
+    });
+
+    chains
+}
@@ -0,0 +1,65 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::transport::TransportNetwork;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NodeKind {
+    /// No road is connected.
+    Isolated,
+    /// Only one road is connected.
+    DeadEnd,
+    /// Two roads are connected without any branch.
+    Through,
+    /// Three roads meet.
+    TIntersection,
+    /// Four roads meet.
+    Crossroad,
+    /// Five or more roads meet.
+    MultiwayIntersection,
+}
+
+/// The metadata of a node for placing traffic lights or roundabouts.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug)]
+pub struct NodeClass {
+    pub degree: usize,
+    pub num_highways: usize,
+    pub kind: NodeKind,
+    /// An intersection where at least one highway meets other roads.
+    pub is_highway_junction: bool,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    pub fn classify_node(&self, index: usize) -> NodeClass {
+        let neighbors = self.graph.neighbors_of(index);
+        let degree = neighbors.len();
+        let num_highways = neighbors
+            .iter()
+            .filter(|n| n.1.path_attr.is_highway())
+            .count();
+        let kind = match degree {
+            0 => NodeKind::Isolated,
+            1 => NodeKind::DeadEnd,
+            2 => NodeKind::Through,
+            3 => NodeKind::TIntersection,
+            4 => NodeKind::Crossroad,
+            _ => NodeKind::MultiwayIntersection,
+        };
+        NodeClass {
+            degree,
+            num_highways,
+            kind,
+            is_highway_junction: degree >= 3 && num_highways > 0,
+        }
+    }
+
+    /// Classify every node in the order of the node indices.
+    pub fn classify_nodes(&self) -> Vec<NodeClass> {
+        (0..self.nodes.len())
+            .map(|index| self.classify_node(index))
+            .collect::<Vec<_>>()
+    }
+}
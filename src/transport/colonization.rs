@@ -0,0 +1,385 @@
+use std::collections::HashSet;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rstar::{primitives::GeomWithData, PointDistance, RTree};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    density::DensityMap,
+    terrain::{Terrain, SEA_LEVEL},
+    transport::{
+        error::TransportError,
+        geometry::EdgeGeometries,
+        math::get_distance,
+        oneway::OneWay,
+        road_class::RoadClass,
+        transport::{EdgeAttr, PathAttr, TransportNetwork},
+        treeobj::PathTree,
+    },
+    Site2D,
+};
+
+type NodeObject = GeomWithData<[f64; 2], usize>;
+
+/// Grows a network by space colonization (Runions et al., 2007), as an alternative to `TransportNetworkBuilder`
+/// for sparse rural networks.
+///
+/// Attraction points are scattered on the land by the density map. At each iteration, every point pulls the node nearest to it
+/// within the influence radius, and every pulled node grows a segment toward the mean direction of its points.
+/// The points reached within the kill distance are removed, so the roads spread until the points run out.
+/// A segment ending near another node is joined to it to close a loop, and the segments crossing the roads are dropped.
+///
+/// The roads carrying the nodes beyond them are arterials once the nodes reach the trunk threshold, and the others are local roads.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct ColonizationBuilder {
+    start: Site2D,
+    iterations: usize,
+    num_attractors: usize,
+    segment_length: f64,
+    influence_radius: f64,
+    kill_distance: f64,
+    snap_distance: f64,
+    max_grade: f64,
+    trunk_threshold: usize,
+    density_map: Option<DensityMap>,
+}
+
+impl Default for ColonizationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ColonizationBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            start: Site2D { x: 0.0, y: 0.0 },
+            iterations: 0,
+            num_attractors: 1000,
+            segment_length: 0.5,
+            influence_radius: 10.0,
+            kill_distance: 1.0,
+            snap_distance: 0.75,
+            max_grade: 0.5,
+            trunk_threshold: 50,
+            density_map: None,
+        }
+    }
+
+    pub fn set_start(self, start_x: f64, start_y: f64) -> Self {
+        Self {
+            start: Site2D {
+                x: start_x,
+                y: start_y,
+            },
+            ..self
+        }
+    }
+
+    /// Set the maximum number of the iterations. The growth stops earlier when no node is pulled any more.
+    pub fn set_iterations(self, iterations: usize) -> Self {
+        Self { iterations, ..self }
+    }
+
+    /// Set the number of the attraction points to scatter.
+    pub fn set_num_attractors(self, num_attractors: usize) -> Self {
+        Self {
+            num_attractors,
+            ..self
+        }
+    }
+
+    /// Set the length of the segments grown at each iteration.
+    pub fn set_segment_length(self, segment_length: f64) -> Self {
+        Self {
+            segment_length,
+            ..self
+        }
+    }
+
+    /// Set the distance within which an attraction point pulls the nearest node.
+    pub fn set_influence_radius(self, influence_radius: f64) -> Self {
+        Self {
+            influence_radius,
+            ..self
+        }
+    }
+
+    /// Set the distance within which an attraction point is removed as reached.
+    /// It should be longer than the segment length, or the nodes may circle around the points.
+    pub fn set_kill_distance(self, kill_distance: f64) -> Self {
+        Self {
+            kill_distance,
+            ..self
+        }
+    }
+
+    /// Set the distance within which a new segment is joined to another node to close a loop. Zero grows trees.
+    pub fn set_snap_distance(self, snap_distance: f64) -> Self {
+        Self {
+            snap_distance,
+            ..self
+        }
+    }
+
+    /// Set the maximum grade (rise over run) of the segments.
+    pub fn set_max_grade(self, max_grade: f64) -> Self {
+        Self { max_grade, ..self }
+    }
+
+    /// Set the number of the nodes beyond a road from which it becomes an arterial.
+    pub fn set_trunk_threshold(self, trunk_threshold: usize) -> Self {
+        Self {
+            trunk_threshold,
+            ..self
+        }
+    }
+
+    /// Set the density by which the attraction points are scattered, taken as the probability of keeping a point from 0 to 1.
+    /// Without it, the points are scattered uniformly on the land.
+    pub fn set_density_map(self, density_map: DensityMap) -> Self {
+        Self {
+            density_map: Some(density_map),
+            ..self
+        }
+    }
+
+    /// Check the parameters and the start.
+    pub fn validate(&self, terrain: &Terrain) -> Result<(), TransportError> {
+        let check = |name: &'static str, is_valid: bool, requirement: &'static str| {
+            if is_valid {
+                Ok(())
+            } else {
+                Err(TransportError::InvalidParameter { name, requirement })
+            }
+        };
+        let positive = |value: f64| value > 0.0 && value.is_finite();
+        const POSITIVE: &str = "positive and finite";
+
+        if self.iterations == 0 {
+            return Err(TransportError::NoIterations);
+        }
+        check("segment_length", positive(self.segment_length), POSITIVE)?;
+        check(
+            "influence_radius",
+            positive(self.influence_radius),
+            POSITIVE,
+        )?;
+        check("kill_distance", positive(self.kill_distance), POSITIVE)?;
+        check(
+            "snap_distance",
+            self.snap_distance >= 0.0 && self.snap_distance.is_finite(),
+            "zero or positive and finite",
+        )?;
+        check("max_grade", self.max_grade >= 0.0, "zero or positive")?;
+        if self.get_land_altitude(terrain, self.start).is_none() {
+            return Err(TransportError::StartOutsideTerrain);
+        }
+        Ok(())
+    }
+
+    pub fn build(&self, seed: u32, terrain: &Terrain) -> Result<TransportNetwork, TransportError> {
+        self.validate(terrain)?;
+        let mut rng = ChaCha12Rng::seed_from_u64(seed as u64);
+        let mut attractors = self.scatter(&mut rng, terrain);
+
+        let mut sites = vec![self.start];
+        let mut parents = vec![None];
+        let mut grown_iterations = vec![0];
+        let mut node_tree = RTree::new();
+        node_tree.insert(NodeObject::new([self.start.x, self.start.y], 0));
+        let mut path_tree = PathTree::new();
+        let mut connected = HashSet::new();
+        let mut loops = vec![];
+
+        for iteration in 0..self.iterations {
+            // the points pull the nearest nodes, or are removed if they are reached
+            let mut pulls = vec![(0.0, 0.0); sites.len()];
+            attractors.retain(|point| {
+                let Some(node) = node_tree.nearest_neighbor(&[point.x, point.y]) else {
+                    return true;
+                };
+                let distance = node.distance_2(&[point.x, point.y]).sqrt();
+                if distance < self.kill_distance {
+                    return false;
+                }
+                if distance < self.influence_radius {
+                    let site = sites[node.data];
+                    let pull = &mut pulls[node.data];
+                    pull.0 += (point.x - site.x) / distance;
+                    pull.1 += (point.y - site.y) / distance;
+                }
+                true
+            });
+
+            let mut has_grown = false;
+            for (index, (pull_x, pull_y)) in pulls.into_iter().enumerate() {
+                let pull_length = (pull_x * pull_x + pull_y * pull_y).sqrt();
+                if pull_length == 0.0 {
+                    continue;
+                }
+                let site = sites[index];
+                let site_next = Site2D {
+                    x: site.x + pull_x / pull_length * self.segment_length,
+                    y: site.y + pull_y / pull_length * self.segment_length,
+                };
+                if !self.is_passable(terrain, site, site_next)
+                    || path_tree
+                        .find_crossing(&site, &site_next, &[index])
+                        .is_some()
+                {
+                    continue;
+                }
+
+                // a node nearby closes a loop, unless it is a neighbor, whose road would be doubled,
+                // or it is too far to be joined but too close for another node
+                let nearby = node_tree
+                    .nearest_neighbor(&[site_next.x, site_next.y])
+                    .map(|node| node.data)
+                    .filter(|&other| {
+                        other != index
+                            && get_distance(site_next, sites[other])
+                                < self.snap_distance.max(self.segment_length * 0.5)
+                    });
+                let snapped = match nearby {
+                    Some(other)
+                        if parents[index] == Some(other)
+                            || parents[other] == Some(index)
+                            || get_distance(site_next, sites[other]) >= self.snap_distance =>
+                    {
+                        continue;
+                    }
+                    nearby => nearby,
+                };
+                let end = if let Some(other) = snapped {
+                    let key = (index.min(other), index.max(other));
+                    if connected.contains(&key)
+                        || !self.is_passable(terrain, site, sites[other])
+                        || path_tree
+                            .find_crossing(&site, &sites[other], &[index, other])
+                            .is_some()
+                    {
+                        continue;
+                    }
+                    connected.insert(key);
+                    loops.push((index, other, iteration));
+                    other
+                } else {
+                    sites.push(site_next);
+                    parents.push(Some(index));
+                    grown_iterations.push(iteration);
+                    let end = sites.len() - 1;
+                    node_tree.insert(NodeObject::new([site_next.x, site_next.y], end));
+                    end
+                };
+                path_tree.insert(
+                    index,
+                    end,
+                    site,
+                    sites[end],
+                    PathAttr::default(),
+                    get_distance(site, sites[end]),
+                );
+                has_grown = true;
+            }
+            if !has_grown {
+                break;
+            }
+        }
+
+        // a node is always added after its parent, so the nodes beyond each road are counted backward
+        let mut num_beyond = vec![1; sites.len()];
+        (1..sites.len()).rev().for_each(|index| {
+            if let Some(parent) = parents[index] {
+                num_beyond[parent] += num_beyond[index];
+            }
+        });
+        let edge_attr =
+            |start: usize, end: usize, road_class: RoadClass, iteration: usize| EdgeAttr {
+                path_attr: PathAttr {
+                    road_class,
+                    is_even: false,
+                    is_bridge: false,
+                },
+                cost: get_distance(sites[start], sites[end]),
+                one_way: OneWay::TwoWay,
+                iteration: Some(iteration),
+            };
+        let edges = (1..sites.len())
+            .filter_map(|index| {
+                let parent = parents[index]?;
+                let road_class = if num_beyond[index] >= self.trunk_threshold {
+                    RoadClass::Arterial
+                } else {
+                    RoadClass::Local
+                };
+                Some((
+                    parent,
+                    index,
+                    edge_attr(parent, index, road_class, grown_iterations[index]),
+                ))
+            })
+            .chain(loops.iter().map(|&(start, end, iteration)| {
+                (
+                    start,
+                    end,
+                    edge_attr(start, end, RoadClass::Local, iteration),
+                )
+            }))
+            .collect::<Vec<_>>();
+        let mut network = TransportNetwork::from_edges(sites, edges, EdgeGeometries::default());
+        network.update_overlays(0.0, 0.0);
+        Ok(network)
+    }
+}
+
+impl ColonizationBuilder {
+    fn get_land_altitude(&self, terrain: &Terrain, site: Site2D) -> Option<f64> {
+        terrain
+            .get_altitude(site.x, site.y)
+            .filter(|altitude| *altitude >= SEA_LEVEL)
+    }
+
+    /// Check whether a segment stays on the land within the maximum grade.
+    fn is_passable(&self, terrain: &Terrain, site_start: Site2D, site_end: Site2D) -> bool {
+        match (
+            self.get_land_altitude(terrain, site_start),
+            self.get_land_altitude(terrain, site_end),
+        ) {
+            (Some(altitude_start), Some(altitude_end)) => {
+                (altitude_end - altitude_start).abs()
+                    <= self.max_grade * get_distance(site_start, site_end)
+            }
+            _ => false,
+        }
+    }
+
+    /// Scatter the attraction points on the land of the terrain by the density map.
+    /// The trials are limited so that a sparse map does not stall the scattering.
+    fn scatter(&self, rng: &mut ChaCha12Rng, terrain: &Terrain) -> Vec<Site2D> {
+        let (bound_min, bound_max) = (terrain.get_bound_min(), terrain.get_bound_max());
+        let mut points = vec![];
+        for _ in 0..self.num_attractors.saturating_mul(100) {
+            if points.len() >= self.num_attractors {
+                break;
+            }
+            let point = Site2D {
+                x: rng.gen_range(bound_min.x..bound_max.x),
+                y: rng.gen_range(bound_min.y..bound_max.y),
+            };
+            let probability = self
+                .density_map
+                .as_ref()
+                .map_or(1.0, |density_map| density_map.sample(point.x, point.y));
+            if rng.gen::<f64>() < probability && self.get_land_altitude(terrain, point).is_some() {
+                points.push(point);
+            }
+        }
+        points
+    }
+}
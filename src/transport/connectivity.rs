@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use rstar::{primitives::GeomWithData, RTree};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    terrain::Terrain,
+    transport::{
+        dedup::UnionFind,
+        math::get_distance,
+        oneway::OneWay,
+        road_class::RoadClass,
+        transport::{EdgeAttr, PathAttr, TransportNetwork, TransportNetworkBuilder},
+    },
+    Site2D,
+};
+
+/// The connected components of the network, not counting the nodes without any road.
+/// The components are ordered by their smallest node indices.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Components {
+    pub(crate) component_nodes: Vec<Vec<usize>>,
+    pub(crate) node_components: Vec<Option<usize>>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Components {
+    pub fn num_components(&self) -> usize {
+        self.component_nodes.len()
+    }
+
+    /// Get the nodes of the component in ascending order.
+    pub fn get_component_nodes(&self, index: usize) -> Vec<usize> {
+        self.component_nodes[index].clone()
+    }
+
+    /// Get the component containing the node, or `None` if the node has no road.
+    pub fn get_component_of(&self, node_index: usize) -> Option<usize> {
+        self.node_components[node_index]
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    pub fn connected_components(&self) -> Components {
+        let mut union_find = UnionFind::new(self.nodes.len());
+        self.iter_edges()
+            .for_each(|(start, end, _)| union_find.union(start, end));
+        let mut component_of_root = HashMap::new();
+        let mut component_nodes = vec![];
+        let node_components = (0..self.nodes.len())
+            .map(|index| {
+                if self.graph.neighbors_of(index).is_empty() {
+                    return None;
+                }
+                let component = *component_of_root
+                    .entry(union_find.find(index))
+                    .or_insert_with(|| {
+                        component_nodes.push(vec![]);
+                        component_nodes.len() - 1
+                    });
+                component_nodes[component].push(index);
+                Some(component)
+            })
+            .collect::<Vec<_>>();
+        Components {
+            component_nodes,
+            node_components,
+        }
+    }
+
+    /// Create a copy of the network in which the connected components are linked by straight roads.
+    ///
+    /// The links are considered between the nodes with roads in different components within `max_link_length`,
+    /// and those crossing the sea or the forbidden areas, leaving the terrain or the bounds, or exceeding the maximum grade of `builder` are discarded.
+    /// The remaining links are added from the cheapest under the cost of `builder`, as long as they join components not joined yet.
+    /// A link takes the lower of the highest classes of the roads leading to its ends.
+    pub fn reconnect_components(
+        &self,
+        builder: &TransportNetworkBuilder,
+        terrain: &Terrain,
+        max_link_length: f64,
+    ) -> TransportNetwork {
+        let components = self.connected_components().node_components;
+        let highest_classes = (0..self.nodes.len())
+            .map(|index| {
+                self.graph
+                    .neighbors_of(index)
+                    .iter()
+                    .map(|n| n.1.path_attr.road_class)
+                    .min()
+                    .unwrap_or(RoadClass::Local)
+            })
+            .collect::<Vec<_>>();
+        let tree = RTree::bulk_load(
+            (0..self.nodes.len())
+                .filter(|index| components[*index].is_some())
+                .map(|index| GeomWithData::new([self.nodes[index].x, self.nodes[index].y], index))
+                .collect::<Vec<_>>(),
+        );
+
+        let mut links = tree
+            .iter()
+            .flat_map(|node_a| {
+                let (site_a, index_a) = (self.nodes[node_a.data], node_a.data);
+                tree.locate_within_distance([site_a.x, site_a.y], max_link_length * max_link_length)
+                    .map(|node| node.data)
+                    .filter(|index_b| {
+                        *index_b > index_a && components[index_a] != components[*index_b]
+                    })
+                    .map(move |index_b| (index_a, index_b))
+                    .collect::<Vec<_>>()
+            })
+            .filter_map(|(index_a, index_b)| {
+                let road_class = highest_classes[index_a].max(highest_classes[index_b]);
+                let attr = PathAttr {
+                    road_class,
+                    is_even: false,
+                    is_bridge: false,
+                };
+                let cost = self.evaluate_link(
+                    builder,
+                    terrain,
+                    self.nodes[index_a],
+                    self.nodes[index_b],
+                    attr,
+                )?;
+                Some((
+                    cost,
+                    get_distance(self.nodes[index_a], self.nodes[index_b]),
+                    index_a,
+                    index_b,
+                    attr,
+                ))
+            })
+            .collect::<Vec<_>>();
+        links.sort_by(|a, b| {
+            a.0.total_cmp(&b.0)
+                .then(a.1.total_cmp(&b.1))
+                .then((a.2, a.3).cmp(&(b.2, b.3)))
+        });
+
+        let mut union_find = UnionFind::new(self.nodes.len());
+        let mut edges = self.iter_edges().collect::<Vec<_>>();
+        links.iter().for_each(|(cost, _, index_a, index_b, attr)| {
+            let (component_a, component_b) =
+                (components[*index_a].unwrap(), components[*index_b].unwrap());
+            if union_find.find(component_a) == union_find.find(component_b) {
+                return;
+            }
+            union_find.union(component_a, component_b);
+            edges.push((
+                *index_a,
+                *index_b,
+                EdgeAttr {
+                    path_attr: *attr,
+                    cost: *cost,
+                    one_way: OneWay::TwoWay,
+                    iteration: None,
+                },
+            ));
+        });
+
+        let mut network =
+            TransportNetwork::from_edges(self.nodes.clone(), edges, self.geometries.clone());
+        network.update_overlays(
+            self.highway_overlay.tolerance,
+            self.normal_overlay.tolerance,
+        );
+        network
+    }
+}
+
+impl TransportNetwork {
+    /// Evaluate the cost of a straight road from `site_from` to `site_to`, divided into steps of the branch length of `builder`.
+    /// Returns `None` if any step cannot be built.
+    fn evaluate_link(
+        &self,
+        builder: &TransportNetworkBuilder,
+        terrain: &Terrain,
+        site_from: Site2D,
+        site_to: Site2D,
+        attr: PathAttr,
+    ) -> Option<f64> {
+        let length = get_distance(site_from, site_to);
+        let num_steps = if builder.branch_length > 0.0 {
+            ((length / builder.branch_length).ceil() as usize).max(1)
+        } else {
+            1
+        };
+        let vertices = (0..=num_steps)
+            .map(|step| {
+                let t = step as f64 / num_steps as f64;
+                let site = Site2D {
+                    x: site_from.x + (site_to.x - site_from.x) * t,
+                    y: site_from.y + (site_to.y - site_from.y) * t,
+                };
+                builder
+                    .get_altitude(terrain, site)
+                    .map(|altitude| (site, altitude))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        vertices
+            .windows(2)
+            .map(|pair| {
+                if builder.crosses_forbidden_area(pair[0].0, pair[1].0) {
+                    return None;
+                }
+                builder
+                    .evaluate_cost(pair[0].0, pair[0].1, pair[1].0, pair[1].1, attr)
+                    .ok()
+            })
+            .sum::<Option<f64>>()
+    }
+}
@@ -0,0 +1,275 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    config::derive_seed,
+    terrain::Terrain,
+    transport::{
+        error::TransportError,
+        oneway::OneWay,
+        road_class::RoadClass,
+        route::{find_terrain_route, TerrainRoute},
+        transport::{EdgeAttr, PathAttr, TransportNetwork, TransportNetworkBuilder},
+    },
+    Site2D,
+};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct ContinentBuilder {
+    num_cities: usize,
+    city_min_distance: f64,
+    city_builder: TransportNetworkBuilder,
+    route_resolution: f64,
+    route_slope_weight: f64,
+}
+
+/// A continent generation in progress.
+/// Each call of `step` performs one stage (site selection, one city or one intercity highway)
+/// so that the generation can be spread over several frames.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct ContinentGeneration {
+    builder: ContinentBuilder,
+    seed: u64,
+    cities: Option<Vec<Site2D>>,
+    links: Vec<(usize, usize)>,
+    networks: Vec<TransportNetwork>,
+    routes: Vec<Option<TerrainRoute>>,
+}
+
+impl Default for ContinentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ContinentBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            num_cities: 0,
+            city_min_distance: 0.0,
+            city_builder: TransportNetworkBuilder::new(),
+            route_resolution: 0.0,
+            route_slope_weight: 0.0,
+        }
+    }
+
+    pub fn set_num_cities(self, num_cities: usize) -> Self {
+        Self { num_cities, ..self }
+    }
+
+    pub fn set_city_min_distance(self, city_min_distance: f64) -> Self {
+        Self {
+            city_min_distance,
+            ..self
+        }
+    }
+
+    /// Set the builder used for growing each city. The start of the builder is replaced by the city site.
+    pub fn set_city_builder(self, city_builder: TransportNetworkBuilder) -> Self {
+        Self {
+            city_builder,
+            ..self
+        }
+    }
+
+    pub fn set_route_resolution(self, route_resolution: f64) -> Self {
+        Self {
+            route_resolution,
+            ..self
+        }
+    }
+
+    pub fn set_route_slope_weight(self, route_slope_weight: f64) -> Self {
+        Self {
+            route_slope_weight,
+            ..self
+        }
+    }
+
+    pub fn start(self, seed: u32) -> ContinentGeneration {
+        self.start_u64(seed as u64)
+    }
+
+    pub fn build(self, seed: u32, terrain: &Terrain) -> Result<TransportNetwork, TransportError> {
+        self.build_u64(seed as u64, terrain)
+    }
+
+    /// Same as `start` with a 64-bit seed.
+    /// The sites of the cities and the growth of each city take independent seeds derived from `seed`.
+    pub fn start_u64(self, seed: u64) -> ContinentGeneration {
+        ContinentGeneration {
+            builder: self,
+            seed,
+            cities: None,
+            links: vec![],
+            networks: vec![],
+            routes: vec![],
+        }
+    }
+
+    /// Same as `build` with a 64-bit seed.
+    pub fn build_u64(
+        self,
+        seed: u64,
+        terrain: &Terrain,
+    ) -> Result<TransportNetwork, TransportError> {
+        let mut generation = self.start_u64(seed);
+        while generation.step(terrain)? {}
+        Ok(generation.finish())
+    }
+}
+
+/// Connect the sites with a minimum spanning tree by Prim's algorithm.
+fn spanning_links(sites: &[Site2D]) -> Vec<(usize, usize)> {
+    if sites.is_empty() {
+        return vec![];
+    }
+    let squared_distance = |a: Site2D, b: Site2D| (a.x - b.x).powi(2) + (a.y - b.y).powi(2);
+
+    let mut in_tree = vec![false; sites.len()];
+    let mut nearest = vec![(f64::MAX, 0); sites.len()];
+    let mut links = vec![];
+    let mut current = 0;
+    in_tree[0] = true;
+    for _ in 1..sites.len() {
+        (0..sites.len()).for_each(|i| {
+            let distance = squared_distance(sites[current], sites[i]);
+            if !in_tree[i] && distance < nearest[i].0 {
+                nearest[i] = (distance, current);
+            }
+        });
+        let next = (0..sites.len())
+            .filter(|&i| !in_tree[i])
+            .min_by(|&a, &b| nearest[a].0.total_cmp(&nearest[b].0))
+            .unwrap();
+        in_tree[next] = true;
+        links.push((nearest[next].1, next));
+        current = next;
+    }
+    links
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ContinentGeneration {
+    /// Perform the next stage of the generation. Returns `false` when nothing is left to do.
+    pub fn step(&mut self, terrain: &Terrain) -> Result<bool, TransportError> {
+        let cities = if let Some(cities) = &self.cities {
+            cities
+        } else {
+            let cities = terrain.suggest_sites_u64(
+                self.builder.num_cities,
+                self.builder.city_min_distance,
+                derive_seed(self.seed, 0),
+            );
+            self.links = spanning_links(&cities);
+            self.cities = Some(cities);
+            return Ok(true);
+        };
+
+        if self.networks.len() < cities.len() {
+            let index = self.networks.len();
+            let city = cities[index];
+            let network = self
+                .builder
+                .city_builder
+                .clone()
+                .set_start(city.x, city.y)
+                .build_u64(derive_seed(self.seed, index as u64 + 1), terrain)?;
+            self.networks.push(network);
+            return Ok(true);
+        }
+
+        if self.routes.len() < self.links.len() {
+            let (a, b) = self.links[self.routes.len()];
+            let route = find_terrain_route(
+                terrain,
+                cities[a],
+                cities[b],
+                self.builder.route_resolution,
+                self.builder.route_slope_weight,
+            );
+            self.routes.push(route);
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        if let Some(cities) = &self.cities {
+            self.networks.len() == cities.len() && self.routes.len() == self.links.len()
+        } else {
+            false
+        }
+    }
+
+    pub fn num_cities(&self) -> usize {
+        self.cities.as_ref().map_or(0, |cities| cities.len())
+    }
+
+    pub fn get_city_site(&self, index: usize) -> Option<Site2D> {
+        self.cities.as_ref()?.get(index).copied()
+    }
+
+    /// Merge the city networks and the intercity highways built so far into one network.
+    pub fn finish(self) -> TransportNetwork {
+        let cities = self.cities.unwrap_or_default();
+        let (merged, offsets) = TransportNetwork::merge(&self.networks);
+        let (highway_tolerance, normal_tolerance) = (
+            merged.highway_overlay.tolerance,
+            merged.normal_overlay.tolerance,
+        );
+        let mut edges = merged.iter_edges().collect::<Vec<_>>();
+        let TransportNetwork {
+            mut nodes,
+            mut geometries,
+            ..
+        } = merged;
+
+        // the start of a city is the first node of its network
+        let city_nodes = (0..cities.len())
+            .map(|i| {
+                if i < self.networks.len() && self.networks[i].num_nodes() > 0 {
+                    offsets[i]
+                } else {
+                    nodes.push(cities[i]);
+                    nodes.len() - 1
+                }
+            })
+            .collect::<Vec<_>>();
+
+        self.links
+            .iter()
+            .zip(self.routes.iter())
+            .for_each(|(&(a, b), route)| {
+                if let Some(route) = route {
+                    let (start, end) = (city_nodes[a], city_nodes[b]);
+                    edges.push((
+                        start,
+                        end,
+                        EdgeAttr {
+                            path_attr: PathAttr {
+                                road_class: RoadClass::Motorway,
+                                is_even: false,
+                                is_bridge: false,
+                            },
+                            cost: route.cost,
+                            one_way: OneWay::TwoWay,
+                            iteration: None,
+                        },
+                    ));
+                    let polyline = &route.polyline;
+                    if polyline.len() > 2 {
+                        geometries.insert(start, end, polyline[1..polyline.len() - 1].to_vec());
+                    }
+                }
+            });
+
+        let mut network = TransportNetwork::from_edges(nodes, edges, geometries);
+        network.update_overlays(highway_tolerance, normal_tolerance);
+        network
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{transport::transport::TransportNetwork, Site2D};
+
+const CORNER_CURVE_SEGMENTS: usize = 8;
+
+/// Corners between the roads around a node, ordered counterclockwise.
+/// The corner `i` lies between the neighbors `i` and `i + 1`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct IntersectionGeometry {
+    neighbors: Vec<usize>,
+    corners: Vec<Site2D>,
+    curves: Vec<Vec<Site2D>>,
+    sight_triangles: Vec<[Site2D; 3]>,
+}
+
+fn flatten(sites: &[Site2D]) -> Vec<f64> {
+    sites
+        .iter()
+        .flat_map(|site| [site.x, site.y])
+        .collect::<Vec<_>>()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl IntersectionGeometry {
+    pub fn num_corners(&self) -> usize {
+        self.corners.len()
+    }
+
+    /// Get the neighbors ordered counterclockwise.
+    pub fn get_neighbors(&self) -> Vec<usize> {
+        self.neighbors.clone()
+    }
+
+    /// Get the point where the edges of both roads meet.
+    pub fn get_corner(&self, index: usize) -> Site2D {
+        self.corners[index]
+    }
+
+    /// Get the rounded edge of the corner as flattened `[x0, y0, x1, y1, ...]`.
+    pub fn get_corner_curve(&self, index: usize) -> Vec<f64> {
+        flatten(&self.curves[index])
+    }
+
+    /// Get the polygon cut off from the corner by the rounded edge as flattened `[x0, y0, x1, y1, ...]`.
+    pub fn get_setback_polygon(&self, index: usize) -> Vec<f64> {
+        let mut polygon = vec![self.corners[index]];
+        polygon.extend(self.curves[index].iter().copied());
+        flatten(&polygon)
+    }
+
+    /// Get the sight triangle of the corner as flattened `[x0, y0, x1, y1, x2, y2]`.
+    pub fn get_sight_triangle(&self, index: usize) -> Vec<f64> {
+        flatten(&self.sight_triangles[index])
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Compute the corners around the node from the widths of the roads.
+    /// The corners are rounded with `corner_radius`,
+    /// and the sight triangles extend `sight_distance` along the edges of both roads.
+    /// Nodes with less than two edges have no corners.
+    pub fn get_intersection_geometry(
+        &self,
+        index: usize,
+        highway_width: f64,
+        normal_width: f64,
+        corner_radius: f64,
+        sight_distance: f64,
+    ) -> IntersectionGeometry {
+        let site = self.nodes[index];
+
+        // direction, half width and length of the first segment of each road
+        let mut roads = self
+            .graph
+            .neighbors_of(index)
+            .iter()
+            .map(|n| {
+                let next = self.get_polyline(index, n.0)[1];
+                let (dx, dy) = (next.x - site.x, next.y - site.y);
+                let length = (dx * dx + dy * dy).sqrt();
+                let width = if n.1.path_attr.is_highway() {
+                    highway_width
+                } else {
+                    normal_width
+                };
+                (n.0, dx / length, dy / length, width * 0.5, length)
+            })
+            .filter(|road| road.4 > 0.0)
+            .collect::<Vec<_>>();
+        roads.sort_by(|a, b| a.2.atan2(a.1).partial_cmp(&b.2.atan2(b.1)).unwrap());
+
+        let mut geometry = IntersectionGeometry {
+            neighbors: roads.iter().map(|road| road.0).collect::<Vec<_>>(),
+            corners: vec![],
+            curves: vec![],
+            sight_triangles: vec![],
+        };
+        if roads.len() < 2 {
+            return geometry;
+        }
+
+        (0..roads.len()).for_each(|i| {
+            let (_, ax, ay, a_half_width, a_length) = roads[i];
+            let (_, bx, by, b_half_width, b_length) = roads[(i + 1) % roads.len()];
+
+            let sin = ax * by - ay * bx;
+            let cos = ax * bx + ay * by;
+            let angle = sin.atan2(cos).rem_euclid(std::f64::consts::TAU);
+
+            // the left edge of the road `a` and the right edge of the road `b`
+            let a_edge = Site2D {
+                x: site.x - ay * a_half_width,
+                y: site.y + ax * a_half_width,
+            };
+            let b_edge = Site2D {
+                x: site.x + by * b_half_width,
+                y: site.y - bx * b_half_width,
+            };
+
+            if angle >= std::f64::consts::PI - 1e-6 || sin.abs() < 1e-12 {
+                // the edges never meet inside the corner, so it is closed straight
+                let corner = Site2D {
+                    x: (a_edge.x + b_edge.x) * 0.5,
+                    y: (a_edge.y + b_edge.y) * 0.5,
+                };
+                geometry.corners.push(corner);
+                geometry.curves.push(vec![a_edge, b_edge]);
+                geometry.sight_triangles.push([corner, a_edge, b_edge]);
+                return;
+            }
+
+            // solve a_edge + a * t = b_edge + b * s
+            let (rx, ry) = (b_edge.x - a_edge.x, b_edge.y - a_edge.y);
+            let t = (rx * by - ry * bx) / sin;
+            let corner = Site2D {
+                x: a_edge.x + ax * t,
+                y: a_edge.y + ay * t,
+            };
+
+            let half_angle = angle * 0.5;
+            let tangent_length = (corner_radius.max(0.0) / half_angle.tan())
+                .min(a_length)
+                .min(b_length);
+            let radius = tangent_length * half_angle.tan();
+            let a_tangent = Site2D {
+                x: corner.x + ax * tangent_length,
+                y: corner.y + ay * tangent_length,
+            };
+            let b_tangent = Site2D {
+                x: corner.x + bx * tangent_length,
+                y: corner.y + by * tangent_length,
+            };
+
+            let curve = if radius > 0.0 {
+                let (mx, my) = (ax + bx, ay + by);
+                let m_length = (mx * mx + my * my).sqrt();
+                let center_distance = radius / half_angle.sin();
+                let center = Site2D {
+                    x: corner.x + mx / m_length * center_distance,
+                    y: corner.y + my / m_length * center_distance,
+                };
+                let angle_start = (a_tangent.y - center.y).atan2(a_tangent.x - center.x);
+                let angle_end = (b_tangent.y - center.y).atan2(b_tangent.x - center.x);
+                let sweep = (angle_end - angle_start + std::f64::consts::PI)
+                    .rem_euclid(std::f64::consts::TAU)
+                    - std::f64::consts::PI;
+                (0..=CORNER_CURVE_SEGMENTS)
+                    .map(|k| {
+                        let theta = angle_start + sweep * k as f64 / CORNER_CURVE_SEGMENTS as f64;
+                        Site2D {
+                            x: center.x + radius * theta.cos(),
+                            y: center.y + radius * theta.sin(),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                vec![corner]
+            };
+
+            geometry.corners.push(corner);
+            geometry.curves.push(curve);
+            geometry.sight_triangles.push([
+                corner,
+                Site2D {
+                    x: corner.x + ax * sight_distance,
+                    y: corner.y + ay * sight_distance,
+                },
+                Site2D {
+                    x: corner.x + bx * sight_distance,
+                    y: corner.y + by * sight_distance,
+                },
+            ]);
+        });
+        geometry
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        geometry::EdgeGeometries,
+        math::get_distance,
+        oneway::OneWay,
+        transport::{EdgeAttr, TransportNetwork},
+    },
+    Site2D,
+};
+
+pub(crate) struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            parents: (0..size).collect::<Vec<_>>(),
+        }
+    }
+
+    pub(crate) fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    /// Unite the sets, keeping the smaller index as the representative.
+    pub(crate) fn union(&mut self, index_a: usize, index_b: usize) {
+        let root_a = self.find(index_a);
+        let root_b = self.find(index_b);
+        if root_a != root_b {
+            self.parents[root_a.max(root_b)] = root_a.min(root_b);
+        }
+    }
+}
+
+/// Check whether a site created on `site` during the growth is merged into `other`.
+/// Sites on the same position are merged even if `distance` is zero, negative or NaN.
+pub(crate) fn is_within_merge_distance(site: Site2D, other: Site2D, distance: f64) -> bool {
+    let site_distance = get_distance(site, other);
+    site_distance < distance || site_distance == 0.0
+}
+
+/// Merge the sites closer than `distance` into the one with the smallest index.
+/// Returns the remaining sites and the new index of every original site.
+pub(crate) fn merge_close_sites(sites: &[Site2D], distance: f64) -> (Vec<Site2D>, Vec<usize>) {
+    let mut union_find = UnionFind::new(sites.len());
+    if distance > 0.0 {
+        let cell_of = |site: &Site2D| {
+            (
+                (site.x / distance).floor() as i64,
+                (site.y / distance).floor() as i64,
+            )
+        };
+        let mut cells: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        sites.iter().enumerate().for_each(|(index, site)| {
+            let (cx, cy) = cell_of(site);
+            (cx - 1..=cx + 1).for_each(|x| {
+                (cy - 1..=cy + 1).for_each(|y| {
+                    if let Some(others) = cells.get(&(x, y)) {
+                        others.iter().for_each(|&other| {
+                            if get_distance(*site, sites[other]) < distance {
+                                union_find.union(index, other);
+                            }
+                        });
+                    }
+                });
+            });
+            cells.entry((cx, cy)).or_default().push(index);
+        });
+    }
+
+    let mut merged = vec![];
+    let mut new_indices = vec![0; sites.len()];
+    (0..sites.len()).for_each(|index| {
+        let root = union_find.find(index);
+        if root == index {
+            new_indices[index] = merged.len();
+            merged.push(sites[index]);
+        } else {
+            new_indices[index] = new_indices[root];
+        }
+    });
+    (merged, new_indices)
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Create a copy of the network in which the nodes closer than `epsilon` to each other are merged into one.
+    ///
+    /// The merging is transitive, so `epsilon` should be well below the branch length not to collapse whole roads.
+    /// A merged node takes the position of the node with the smallest index, and the roads of the others are moved onto it.
+    /// The roads which become loops are removed, and of the roads which become parallel only the one of the highest class is kept.
+    /// The node indices are renumbered.
+    pub fn merge_nearby_nodes(&self, epsilon: f64) -> TransportNetwork {
+        let (nodes, new_indices) = merge_close_sites(&self.nodes, epsilon);
+
+        let mut edges = self.iter_edges().collect::<Vec<_>>();
+        edges.sort_by_key(|(_, _, attr)| attr.path_attr.road_class);
+        let mut kept = HashSet::new();
+        let mut geometries = EdgeGeometries::default();
+        let edges = edges
+            .into_iter()
+            .filter_map(|(start, end, attr)| {
+                let (new_start, new_end) = (new_indices[start], new_indices[end]);
+                if new_start == new_end
+                    || !kept.insert((new_start.min(new_end), new_start.max(new_end)))
+                {
+                    return None;
+                }
+                geometries.insert(new_start, new_end, self.geometries.get(start, end));
+                // the direction is relative to the positions of the ends, which may have moved
+                let one_way = match attr.one_way {
+                    OneWay::TwoWay => OneWay::TwoWay,
+                    one_way if one_way.allows(&self.nodes[start], &self.nodes[end]) => {
+                        OneWay::from_travel(&nodes[new_start], &nodes[new_end])
+                    }
+                    _ => OneWay::from_travel(&nodes[new_end], &nodes[new_start]),
+                };
+                Some((new_start, new_end, EdgeAttr { one_way, ..attr }))
+            })
+            .collect::<Vec<_>>();
+
+        let mut network = TransportNetwork::from_edges(nodes, edges, geometries);
+        network.update_overlays(
+            self.highway_overlay.tolerance,
+            self.normal_overlay.tolerance,
+        );
+        network.log = self.log.clone();
+        network.report = self.report.clone();
+        network
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(x: f64, y: f64) -> Site2D {
+        Site2D { x, y }
+    }
+
+    #[test]
+    fn coincident_sites_are_merged_into_the_first() {
+        let sites = [site(1.0, 1.0), site(2.0, 2.0), site(1.0, 1.0)];
+        let (merged, new_indices) = merge_close_sites(&sites, 0.1);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(new_indices, vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn chains_of_close_sites_are_merged_transitively() {
+        // each site is closer than the distance only to its neighbors in the chain
+        let sites = (0..5)
+            .map(|i| site(i as f64 * 0.9, 0.0))
+            .collect::<Vec<_>>();
+        let (merged, new_indices) = merge_close_sites(&sites, 1.0);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].x, 0.0);
+        assert!(new_indices.iter().all(|&index| index == 0));
+    }
+
+    #[test]
+    fn sites_at_the_distance_are_kept() {
+        let sites = [site(0.0, 0.0), site(1.0, 0.0)];
+        let (merged, new_indices) = merge_close_sites(&sites, 1.0);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(new_indices, vec![0, 1]);
+    }
+
+    #[test]
+    fn zero_and_negative_distances_keep_every_site() {
+        let sites = [site(0.0, 0.0), site(0.0, 0.0), site(1e-12, 0.0)];
+        for distance in [0.0, -1.0, f64::NAN] {
+            let (merged, new_indices) = merge_close_sites(&sites, distance);
+            assert_eq!(merged.len(), 3);
+            assert_eq!(new_indices, vec![0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn growth_merges_coincident_sites_at_any_distance() {
+        assert!(is_within_merge_distance(
+            site(1.0, 1.0),
+            site(1.0, 1.0),
+            0.0
+        ));
+        assert!(is_within_merge_distance(
+            site(1.0, 1.0),
+            site(1.0, 1.0),
+            -1.0
+        ));
+        assert!(is_within_merge_distance(
+            site(1.0, 1.0),
+            site(1.0, 1.0),
+            f64::NAN
+        ));
+        assert!(!is_within_merge_distance(
+            site(1.0, 1.0),
+            site(1.0, 1.1),
+            0.0
+        ));
+        assert!(!is_within_merge_distance(
+            site(0.0, 0.0),
+            site(0.5, 0.0),
+            0.5
+        ));
+        assert!(is_within_merge_distance(
+            site(0.0, 0.0),
+            site(0.5, 0.0),
+            0.6
+        ));
+    }
+}
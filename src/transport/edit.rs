@@ -0,0 +1,167 @@
+use terrain_graph::edge_attributed_undirected::EdgeAttributedUndirectedGraph;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        math::{get_distance, get_distance_to_segment},
+        oneway::OneWay,
+        road_class::RoadClass,
+        transport::{EdgeAttr, PathAttr, TransportNetwork},
+    },
+    Site2D,
+};
+
+/// Editing the network by hand.
+/// The node indices are kept by every edit, so the nodes left without roads remain in the network.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Add a node without roads at (`x`, `y`) and return its index.
+    pub fn add_node(&mut self, x: f64, y: f64) -> usize {
+        // the graph has a fixed number of nodes, so it is rebuilt
+        let edges = self.iter_edges().collect::<Vec<_>>();
+        self.nodes.push(Site2D { x, y });
+        self.graph = EdgeAttributedUndirectedGraph::new(self.nodes.len());
+        edges
+            .into_iter()
+            .for_each(|(start, end, attr)| self.graph.add_edge(start, end, attr));
+        self.invalidate_spatial_index();
+        self.nodes.len() - 1
+    }
+
+    /// Add a straight two-way road between the nodes.
+    /// Returns `false` if a node is out of range, the nodes are the same, or they are already connected.
+    pub fn add_edge(
+        &mut self,
+        index_a: usize,
+        index_b: usize,
+        road_class: RoadClass,
+        is_bridge: bool,
+        cost: f64,
+    ) -> bool {
+        if index_a >= self.nodes.len()
+            || index_b >= self.nodes.len()
+            || index_a == index_b
+            || self.graph.has_edge(index_a, index_b).0
+        {
+            return false;
+        }
+        let attr = EdgeAttr {
+            path_attr: PathAttr {
+                road_class,
+                is_even: false,
+                is_bridge,
+            },
+            cost,
+            one_way: OneWay::TwoWay,
+            iteration: None,
+        };
+        self.graph.add_edge(index_a, index_b, attr);
+        self.invalidate_spatial_index();
+        self.update_overlays(
+            self.highway_overlay.tolerance,
+            self.normal_overlay.tolerance,
+        );
+        true
+    }
+
+    /// Remove the road between the nodes. Returns `false` if there is no such road.
+    pub fn remove_edge(&mut self, index_a: usize, index_b: usize) -> bool {
+        if index_a >= self.nodes.len()
+            || index_b >= self.nodes.len()
+            || !self.graph.has_edge(index_a, index_b).0
+        {
+            return false;
+        }
+        self.graph.delete_edge(index_a, index_b);
+        self.geometries.remove(index_a, index_b);
+        self.invalidate_spatial_index();
+        self.update_overlays(
+            self.highway_overlay.tolerance,
+            self.normal_overlay.tolerance,
+        );
+        true
+    }
+
+    /// Split the road between the nodes at a new node at (`x`, `y`) and return the index of the node,
+    /// or `None` if there is no such road.
+    ///
+    /// The halves keep the attributes of the road and share its cost in proportion to their lengths.
+    /// The intermediate vertices of the road are divided at the segment nearest to the new node.
+    pub fn split_edge_at(
+        &mut self,
+        index_a: usize,
+        index_b: usize,
+        x: f64,
+        y: f64,
+    ) -> Option<usize> {
+        if index_a >= self.nodes.len() || index_b >= self.nodes.len() {
+            return None;
+        }
+        let (has_edge, attr) = self.graph.has_edge(index_a, index_b);
+        if !has_edge {
+            return None;
+        }
+        let site = Site2D { x, y };
+        let polyline = self.get_polyline(index_a, index_b);
+        let nearest_segment = (0..polyline.len() - 1)
+            .min_by(|i, j| {
+                get_distance_to_segment(site, polyline[*i], polyline[*i + 1]).total_cmp(
+                    &get_distance_to_segment(site, polyline[*j], polyline[*j + 1]),
+                )
+            })
+            .unwrap_or(0);
+        let intermediate_a = polyline[1..=nearest_segment].to_vec();
+        let intermediate_b = polyline[nearest_segment + 1..polyline.len() - 1].to_vec();
+        let length_of = |start: Site2D, intermediate: &[Site2D], end: Site2D| {
+            std::iter::once(start)
+                .chain(intermediate.iter().copied())
+                .chain(std::iter::once(end))
+                .collect::<Vec<_>>()
+                .windows(2)
+                .map(|pair| get_distance(pair[0], pair[1]))
+                .sum::<f64>()
+        };
+        let length_a = length_of(polyline[0], &intermediate_a, site);
+        let length_b = length_of(site, &intermediate_b, polyline[polyline.len() - 1]);
+        let ratio = if length_a + length_b > 0.0 {
+            length_a / (length_a + length_b)
+        } else {
+            0.5
+        };
+
+        let index_new = self.add_node(x, y);
+        self.graph.delete_edge(index_a, index_b);
+        self.geometries.remove(index_a, index_b);
+        // the one-way direction is relative to the coordinates of the ends, so it is recomputed for each half
+        let half = |ratio: f64, site_from: Site2D, site_to: Site2D| EdgeAttr {
+            cost: attr.cost * ratio,
+            one_way: match attr.one_way {
+                OneWay::TwoWay => OneWay::TwoWay,
+                _ => {
+                    let is_forward = attr
+                        .one_way
+                        .allows(&self.nodes[index_a], &self.nodes[index_b]);
+                    if is_forward {
+                        OneWay::from_travel(&site_from, &site_to)
+                    } else {
+                        OneWay::from_travel(&site_to, &site_from)
+                    }
+                }
+            },
+            ..attr
+        };
+        let attr_a = half(ratio, self.nodes[index_a], site);
+        let attr_b = half(1.0 - ratio, site, self.nodes[index_b]);
+        self.graph.add_edge(index_a, index_new, attr_a);
+        self.graph.add_edge(index_new, index_b, attr_b);
+        self.geometries.insert(index_a, index_new, intermediate_a);
+        self.geometries.insert(index_new, index_b, intermediate_b);
+        self.invalidate_spatial_index();
+        self.update_overlays(
+            self.highway_overlay.tolerance,
+            self.normal_overlay.tolerance,
+        );
+        Some(index_new)
+    }
+}
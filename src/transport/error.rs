@@ -0,0 +1,52 @@
+use std::fmt;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// An error which stops the growth of a network.
+/// With the `wasm` feature it is thrown to JS as an `Error` with the message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransportError {
+    /// The path to be split at a crossing was missing from the path tree.
+    PathNotFound,
+    /// A parameter of the builder cannot be used for the growth.
+    InvalidParameter {
+        name: &'static str,
+        requirement: &'static str,
+    },
+    /// The number of the iterations is zero, so nothing would grow.
+    NoIterations,
+    /// The start is outside the terrain or the bounds.
+    StartOutsideTerrain,
+    /// The origin of `add_start` at `index`, counted from zero, is outside the terrain or the bounds.
+    AdditionalStartOutsideTerrain { index: usize },
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PathNotFound => write!(f, "the path to be split is missing from the path tree"),
+            Self::InvalidParameter { name, requirement } => {
+                write!(f, "{} must be {}", name, requirement)
+            }
+            Self::NoIterations => write!(f, "iterations is zero; set it with set_iterations"),
+            Self::StartOutsideTerrain => {
+                write!(f, "the start is outside the terrain or the bounds")
+            }
+            Self::AdditionalStartOutsideTerrain { index } => write!(
+                f,
+                "the origin {} added by add_start is outside the terrain or the bounds",
+                index
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+#[cfg(feature = "wasm")]
+impl From<TransportError> for JsValue {
+    fn from(error: TransportError) -> Self {
+        js_sys::Error::new(&error.to_string()).into()
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        geometry::EdgeGeometries,
+        math::get_distance,
+        oneway::OneWay,
+        road_class::RoadClass,
+        transport::{EdgeAttr, PathAttr, TransportNetwork},
+    },
+    Site2D,
+};
+
+/// The meters of a degree of latitude.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// The attributes of a line read from the properties of its feature.
+#[derive(Clone, Copy)]
+struct LineProperties {
+    road_class: RoadClass,
+    is_bridge: bool,
+    /// `Some(true)` if the line is one-way along its coordinates, `Some(false)` if against them.
+    one_way: Option<bool>,
+}
+
+fn parse_properties(properties: &Value) -> LineProperties {
+    let text = |key: &str| properties.get(key).and_then(|value| value.as_str());
+    let flag = |key: &str| match properties.get(key) {
+        Some(Value::Bool(value)) => Some(*value),
+        Some(Value::String(value)) => match value.as_str() {
+            "yes" | "true" | "1" => Some(true),
+            "-1" | "reverse" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    };
+    // the names of `RoadClass` are accepted as well as the OSM highway tags
+    let road_class = match text("road_class").or(text("highway")) {
+        Some("motorway" | "motorway_link" | "trunk" | "trunk_link") => RoadClass::Motorway,
+        Some("arterial" | "primary" | "primary_link") => RoadClass::Arterial,
+        Some("collector" | "secondary" | "secondary_link" | "tertiary" | "tertiary_link") => {
+            RoadClass::Collector
+        }
+        _ => RoadClass::Local,
+    };
+    LineProperties {
+        road_class,
+        is_bridge: flag("bridge") == Some(true),
+        one_way: flag("oneway"),
+    }
+}
+
+/// Collect the coordinates of the lines in the GeoJSON object with the properties of their features.
+fn collect_lines(
+    object: &Value,
+    properties: LineProperties,
+    lines: &mut Vec<(Vec<(f64, f64)>, LineProperties)>,
+) -> Option<()> {
+    let parse_line = |coordinates: &Value| {
+        coordinates
+            .as_array()?
+            .iter()
+            .map(|position| Some((position.get(0)?.as_f64()?, position.get(1)?.as_f64()?)))
+            .collect::<Option<Vec<_>>>()
+    };
+    match object.get("type")?.as_str()? {
+        "FeatureCollection" => object
+            .get("features")?
+            .as_array()?
+            .iter()
+            .try_for_each(|feature| collect_lines(feature, properties, lines)),
+        "Feature" => {
+            let properties = parse_properties(object.get("properties").unwrap_or(&Value::Null));
+            match object.get("geometry") {
+                Some(Value::Null) | None => Some(()),
+                Some(geometry) => collect_lines(geometry, properties, lines),
+            }
+        }
+        "GeometryCollection" => object
+            .get("geometries")?
+            .as_array()?
+            .iter()
+            .try_for_each(|geometry| collect_lines(geometry, properties, lines)),
+        "LineString" => {
+            lines.push((parse_line(object.get("coordinates")?)?, properties));
+            Some(())
+        }
+        "MultiLineString" => {
+            object
+                .get("coordinates")?
+                .as_array()?
+                .iter()
+                .try_for_each(|coordinates| {
+                    lines.push((parse_line(coordinates)?, properties));
+                    Some(())
+                })
+        }
+        // the other geometries are not roads
+        _ => Some(()),
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Load the roads from the `LineString` and `MultiLineString` features of a GeoJSON document, such as an export of OpenStreetMap.
+    /// The loaded network can be extended by `TransportNetworkBuilder::set_existing_network`.
+    ///
+    /// The coordinates are projected as the inverse of `to_osm_xml`: `x` to the east and `y` to the north of (`origin_lat`, `origin_lon`)
+    /// in units of `meters_per_unit` meters.
+    /// The road class is read from the property `road_class` or the OSM tag `highway`, and the properties `oneway` and `bridge` are also read.
+    /// The lines are joined where they share exactly the same coordinates, which become the nodes with the ends of the lines.
+    ///
+    /// Returns `None` if the document is not valid GeoJSON.
+    pub fn from_geojson(
+        json: &str,
+        origin_lat: f64,
+        origin_lon: f64,
+        meters_per_unit: f64,
+    ) -> Option<TransportNetwork> {
+        let document: Value = serde_json::from_str(json).ok()?;
+        let mut lines = vec![];
+        collect_lines(&document, parse_properties(&Value::Null), &mut lines)?;
+        lines.retain(|(coordinates, _)| coordinates.len() >= 2);
+
+        let meters_per_degree_lon = METERS_PER_DEGREE * origin_lat.to_radians().cos();
+        let project = |(lon, lat): (f64, f64)| Site2D {
+            x: (lon - origin_lon) * meters_per_degree_lon / meters_per_unit,
+            y: (lat - origin_lat) * METERS_PER_DEGREE / meters_per_unit,
+        };
+        let key = |(lon, lat): (f64, f64)| (lon.to_bits(), lat.to_bits());
+
+        // the ends of the lines and the coordinates shared by the lines are the nodes
+        let mut occurrences = HashMap::new();
+        lines.iter().for_each(|(coordinates, _)| {
+            coordinates.iter().enumerate().for_each(|(i, position)| {
+                let is_end = i == 0 || i == coordinates.len() - 1;
+                *occurrences.entry(key(*position)).or_insert(0) += if is_end { 2 } else { 1 };
+            });
+        });
+        let mut nodes = vec![];
+        let mut node_of = HashMap::new();
+        let mut node_index = |position: (f64, f64), nodes: &mut Vec<Site2D>| {
+            *node_of.entry(key(position)).or_insert_with(|| {
+                nodes.push(project(position));
+                nodes.len() - 1
+            })
+        };
+
+        let mut edges = vec![];
+        let mut geometries = EdgeGeometries::default();
+        let mut connected = HashSet::new();
+        lines.iter().for_each(|(coordinates, properties)| {
+            let mut start = node_index(coordinates[0], &mut nodes);
+            let mut intermediate: Vec<(f64, f64)> = vec![];
+            coordinates.iter().skip(1).for_each(|position| {
+                if occurrences[&key(*position)] < 2 {
+                    intermediate.push(*position);
+                    return;
+                }
+                let end = node_index(*position, &mut nodes);
+                // a loop or a second road between the same nodes is split at its middle vertex
+                let mut pieces = vec![];
+                if (start == end || connected.contains(&(start.min(end), start.max(end))))
+                    && !intermediate.is_empty()
+                {
+                    let middle = intermediate.len() / 2;
+                    let middle_node = nodes.len();
+                    nodes.push(project(intermediate[middle]));
+                    pieces.push((start, middle_node, intermediate[..middle].to_vec()));
+                    pieces.push((middle_node, end, intermediate[middle + 1..].to_vec()));
+                } else {
+                    pieces.push((start, end, intermediate.clone()));
+                }
+                pieces.into_iter().for_each(|(from, to, vertices)| {
+                    if from == to || !connected.insert((from.min(to), from.max(to))) {
+                        return;
+                    }
+                    let polyline = std::iter::once(nodes[from])
+                        .chain(vertices.iter().map(|position| project(*position)))
+                        .chain(std::iter::once(nodes[to]))
+                        .collect::<Vec<_>>();
+                    let length = polyline
+                        .windows(2)
+                        .map(|pair| get_distance(pair[0], pair[1]))
+                        .sum::<f64>();
+                    let one_way = match properties.one_way {
+                        Some(true) => OneWay::from_travel(&nodes[from], &nodes[to]),
+                        Some(false) => OneWay::from_travel(&nodes[to], &nodes[from]),
+                        None => OneWay::TwoWay,
+                    };
+                    edges.push((
+                        from,
+                        to,
+                        EdgeAttr {
+                            path_attr: PathAttr {
+                                road_class: properties.road_class,
+                                is_even: false,
+                                is_bridge: properties.is_bridge,
+                            },
+                            cost: length,
+                            one_way,
+                            iteration: None,
+                        },
+                    ));
+                    geometries.insert(from, to, polyline[1..polyline.len() - 1].to_vec());
+                });
+                intermediate.clear();
+                start = end;
+            });
+        });
+
+        let mut network = TransportNetwork::from_edges(nodes, edges, geometries);
+        network.update_overlays(0.0, 0.0);
+        Some(network)
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{math::get_distance, transport::TransportNetwork},
+    Site2D,
+};
+
+/// Intermediate vertices of edges which are not straight segments.
+/// The vertices are stored in the direction from the smaller node index to the larger one.
+#[derive(Clone, Default)]
+pub(crate) struct EdgeGeometries {
+    intermediates: HashMap<(usize, usize), Vec<Site2D>>,
+}
+
+impl EdgeGeometries {
+    pub(crate) fn insert(&mut self, index_a: usize, index_b: usize, mut intermediate: Vec<Site2D>) {
+        if intermediate.is_empty() {
+            self.remove(index_a, index_b);
+            return;
+        }
+        if index_a > index_b {
+            intermediate.reverse();
+        }
+        self.intermediates
+            .insert((index_a.min(index_b), index_a.max(index_b)), intermediate);
+    }
+
+    pub(crate) fn remove(&mut self, index_a: usize, index_b: usize) -> Option<Vec<Site2D>> {
+        let intermediate = self
+            .intermediates
+            .remove(&(index_a.min(index_b), index_a.max(index_b)));
+        if index_a > index_b {
+            intermediate.map(|mut intermediate| {
+                intermediate.reverse();
+                intermediate
+            })
+        } else {
+            intermediate
+        }
+    }
+
+    /// Iterate over the edges with intermediate vertices, oriented from the smaller node index.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, usize, &Vec<Site2D>)> {
+        self.intermediates
+            .iter()
+            .map(|(key, intermediate)| (key.0, key.1, intermediate))
+    }
+
+    /// Get the intermediate vertices oriented from `index_a` to `index_b`.
+    pub(crate) fn get(&self, index_a: usize, index_b: usize) -> Vec<Site2D> {
+        let intermediate = self
+            .intermediates
+            .get(&(index_a.min(index_b), index_a.max(index_b)));
+        match intermediate {
+            Some(intermediate) if index_a > index_b => {
+                intermediate.iter().rev().copied().collect::<Vec<_>>()
+            }
+            Some(intermediate) => intermediate.clone(),
+            None => vec![],
+        }
+    }
+}
+
+impl TransportNetwork {
+    /// Get the whole polyline of the edge including both end nodes, oriented from `index_a` to `index_b`.
+    pub(crate) fn get_polyline(&self, index_a: usize, index_b: usize) -> Vec<Site2D> {
+        let mut polyline = vec![self.nodes[index_a]];
+        polyline.extend(self.geometries.get(index_a, index_b));
+        polyline.push(self.nodes[index_b]);
+        polyline
+    }
+
+    /// Get the length of the whole polyline of the edge.
+    pub(crate) fn get_edge_length(&self, index_a: usize, index_b: usize) -> f64 {
+        self.get_polyline(index_a, index_b)
+            .windows(2)
+            .map(|segment| get_distance(segment[0], segment[1]))
+            .sum::<f64>()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Get the polyline of the edge as flattened `[x0, y0, x1, y1, ...]` including both end nodes.
+    pub fn get_edge_polyline(&self, index_a: usize, index_b: usize) -> Vec<f64> {
+        self.get_polyline(index_a, index_b)
+            .iter()
+            .flat_map(|site| [site.x, site.y])
+            .collect::<Vec<_>>()
+    }
+
+    /// Replace the intermediate vertices of the edge with flattened `[x0, y0, x1, y1, ...]`.
+    /// The vertices are ordered from `index_a` to `index_b`, excluding both end nodes.
+    pub fn set_edge_geometry(&mut self, index_a: usize, index_b: usize, intermediate: Vec<f64>) {
+        if !self.graph.has_edge(index_a, index_b).0 {
+            return;
+        }
+        let intermediate = intermediate
+            .chunks_exact(2)
+            .map(|xy| Site2D { x: xy[0], y: xy[1] })
+            .collect::<Vec<_>>();
+        self.geometries.insert(index_a, index_b, intermediate);
+        self.invalidate_spatial_index();
+    }
+}
@@ -0,0 +1,1203 @@
+use std::{cell::Cell, collections::BinaryHeap};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    terrain::Terrain,
+    transport::{
+        dedup::{is_within_merge_distance, merge_close_sites},
+        error::TransportError,
+        geometry::EdgeGeometries,
+        log::{GenerationEventKind, GenerationLog},
+        math::{get_cross, get_distance},
+        oneway::{assign_one_way, OneWay},
+        report::BuildReport,
+        road_class::RoadClass,
+        stats::BuildStats,
+        strategy::{BranchCandidate, BranchContext, GrowingPath, GrowthStrategy},
+        transport::{
+            BranchPattern, Candidate, CandidateRejection, Edge, EdgeAttr, PathAttr,
+            TransportNetwork, TransportNetworkBuilder,
+        },
+        treeobj::{PathTree, PathTreeObject, PathTreeQuery},
+    },
+    Site2D,
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Path {
+    start: usize,
+    end: usize,
+    angle: f64,
+    // the direction which the grid of the normal branches is aligned to
+    axis: f64,
+    cost: f64,
+    path_attr: PathAttr,
+    // the sites of the switchback which the path is a leg of, from its first start to its last end
+    switchback: Vec<usize>,
+}
+
+impl Ord for Path {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for Path {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start
+            && self.end == other.end
+            && self.angle == other.angle
+            && self.cost == other.cost
+    }
+}
+
+impl Eq for Path {}
+
+/// The clock is read once per this number of iterations in the growth with a time budget.
+const TIME_CHECK_INTERVAL: usize = 64;
+
+/// Get the current time in milliseconds, from which only the differences are meaningful.
+pub(crate) fn now_ms() -> f64 {
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    {
+        use std::sync::OnceLock;
+        static EPOCH: OnceLock<std::time::Instant> = OnceLock::new();
+        EPOCH
+            .get_or_init(std::time::Instant::now)
+            .elapsed()
+            .as_secs_f64()
+            * 1000.0
+    }
+}
+
+/// Snap the angle to the nearest of the four directions along and across the axis.
+fn snap_angle(angle: f64, axis: f64) -> f64 {
+    let quarter = std::f64::consts::PI * 0.5;
+    axis + ((angle - axis) / quarter).round() * quarter
+}
+
+const GROWTH_STATE_VERSION: u32 = 4;
+
+/// The roads which grow in the current stage of a growth.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum GrowthPhase {
+    /// The highways and the local streets grow together.
+    Interleaved,
+    /// Only the highways grow, until the iterations for them run out or they stop growing.
+    Highways,
+    /// Only the local streets grow, off the sides of the highways.
+    Infill,
+}
+
+/// A growth of a transport network in progress.
+/// The same terrain must be passed to every step.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TransportGrowth {
+    builder: TransportNetworkBuilder,
+    // the same generator as `StdRng`, which can be serialized
+    rng: ChaCha12Rng,
+    sites_collection: Vec<(Site2D, f64)>,
+    path_heap: BinaryHeap<Path>,
+    path_tree: PathTree,
+    log: GenerationLog,
+    report: BuildReport,
+    stats: BuildStats,
+    check_times: usize,
+    rotation_probabilities: [f64; 4],
+    iteration: usize,
+    phase: GrowthPhase,
+}
+
+impl TransportGrowth {
+    pub(crate) fn new(builder: TransportNetworkBuilder, seed: u64, terrain: &Terrain) -> Self {
+        let mut builder = builder.vary_u64(seed);
+        builder.prepare_altitude_cache(terrain);
+        let mut rng = ChaCha12Rng::seed_from_u64(seed);
+
+        // the existing network is laid first so that its nodes keep their indices
+        let mut sites_collection = builder
+            .existing_sites
+            .iter()
+            .map(|site| (*site, terrain.get_altitude(site.x, site.y).unwrap_or(0.0)))
+            .collect::<Vec<_>>();
+        let mut path_heap = BinaryHeap::new();
+
+        let has_rings = !builder.ring_radii.is_empty();
+        let mut center_index = None;
+        // the sites which an origin is merged into, as it does not pass through the growth
+        let mut origin_indices = (0..sites_collection.len()).collect::<Vec<_>>();
+
+        // every origin starts with a pair of highways in opposite directions
+        // unless the radial highways start from it
+        std::iter::once(builder.start)
+            .chain(builder.additional_starts.iter().copied())
+            .enumerate()
+            .for_each(|(origin_index, start)| {
+                let initial_angle = rng.gen_range(0.0..std::f64::consts::PI);
+                let initial_opposite_angle = initial_angle + std::f64::consts::PI;
+
+                let merged_index = origin_indices.iter().copied().find(|&index| {
+                    is_within_merge_distance(
+                        start,
+                        sites_collection[index].0,
+                        builder.merge_distance,
+                    )
+                });
+                let start_index = if let Some(merged_index) = merged_index {
+                    merged_index
+                } else if let Some(altitude) = builder.get_altitude(terrain, start) {
+                    sites_collection.push((start, altitude));
+                    origin_indices.push(sites_collection.len() - 1);
+                    sites_collection.len() - 1
+                } else {
+                    return;
+                };
+                if origin_index == 0 && has_rings {
+                    center_index = Some(start_index);
+                    return;
+                }
+
+                [initial_angle, initial_opposite_angle]
+                    .iter()
+                    .for_each(|&angle| {
+                        let site = Site2D {
+                            x: start.x + builder.branch_length * libm::cos(angle),
+                            y: start.y + builder.branch_length * libm::sin(angle),
+                        };
+                        if let Some(altitude) = builder.get_altitude(terrain, site) {
+                            sites_collection.push((site, altitude));
+                            path_heap.push(Path {
+                                start: start_index,
+                                end: sites_collection.len() - 1,
+                                angle,
+                                axis: angle,
+                                cost: 0.0,
+                                path_attr: PathAttr {
+                                    road_class: RoadClass::Motorway,
+                                    is_even: false,
+                                    is_bridge: false,
+                                },
+                                switchback: vec![],
+                            });
+                        }
+                    });
+            });
+
+        let (check_times, rotation_probabilities, report) = builder.adjusted_parameters();
+
+        let mut path_tree = PathTree::new();
+        if let Some((bound_min, bound_max, _)) = builder.coverage_target {
+            path_tree.measure_coverage(bound_min, bound_max);
+        }
+        builder
+            .existing_edges
+            .iter()
+            .for_each(|(start, end, path_attr, cost)| {
+                path_tree.insert(
+                    *start,
+                    *end,
+                    sites_collection[*start].0,
+                    sites_collection[*end].0,
+                    *path_attr,
+                    *cost,
+                );
+            });
+
+        let phase = if builder.highway_iterations.is_some() {
+            GrowthPhase::Highways
+        } else {
+            GrowthPhase::Interleaved
+        };
+
+        let mut growth = Self {
+            log: GenerationLog::new(builder.record_log),
+            builder,
+            rng,
+            sites_collection,
+            path_heap,
+            path_tree,
+            report,
+            stats: BuildStats::default(),
+            check_times,
+            rotation_probabilities,
+            iteration: 0,
+            phase,
+        };
+        if has_rings {
+            growth.seed_rings(center_index, terrain);
+        }
+        growth
+    }
+
+    /// Lay ring highways around the start and radial highways through them.
+    /// The radials keep growing outward from the outermost ring,
+    /// and the other roads grow from the sides of every ring and radial.
+    fn seed_rings(&mut self, center_index: Option<usize>, terrain: &Terrain) {
+        let tau = std::f64::consts::TAU;
+        let center = self.builder.start;
+        let segment_length = self.builder.branch_length * self.builder.highway_path_length_weight;
+        let num_radials = self.builder.num_radials;
+        if segment_length <= 0.0 {
+            return;
+        }
+        // the rings are laid out before the first iteration
+        self.path_tree.set_iteration(Some(self.iteration));
+        let mut radii = self
+            .builder
+            .ring_radii
+            .iter()
+            .copied()
+            .filter(|radius| *radius > 0.0)
+            .collect::<Vec<_>>();
+        radii.sort_by(|a, b| a.total_cmp(b));
+        let angle_offset = self.rng.gen_range(0.0..tau);
+
+        let site_at = |radius: f64, angle: f64| Site2D {
+            x: center.x + radius * libm::cos(angle),
+            y: center.y + radius * libm::sin(angle),
+        };
+        let push_site = |sites_collection: &mut Vec<(Site2D, f64)>, site: Site2D| {
+            let altitude = self.builder.get_altitude(terrain, site)?;
+            sites_collection.push((site, altitude));
+            Some(sites_collection.len() - 1)
+        };
+
+        let mut segments = vec![];
+        // the vertices of each ring, including the crossings with the radials
+        let rings = radii
+            .iter()
+            .map(|&radius| {
+                let num_vertices = ((tau * radius / segment_length).ceil() as usize).max(3);
+                let num_vertices = if num_radials > 0 {
+                    num_vertices.div_ceil(num_radials) * num_radials
+                } else {
+                    num_vertices
+                };
+                let vertices = (0..num_vertices)
+                    .map(|j| {
+                        let angle = angle_offset + tau * j as f64 / num_vertices as f64;
+                        push_site(&mut self.sites_collection, site_at(radius, angle))
+                    })
+                    .collect::<Vec<_>>();
+                (0..num_vertices).for_each(|j| {
+                    segments.push((vertices[j], vertices[(j + 1) % num_vertices]));
+                });
+                vertices
+            })
+            .collect::<Vec<_>>();
+
+        let mut outward_paths = vec![];
+        (0..num_radials).for_each(|k| {
+            let angle = angle_offset + tau * k as f64 / num_radials as f64;
+            let mut previous = (center_index, 0.0);
+            radii
+                .iter()
+                .zip(rings.iter())
+                .for_each(|(&radius, vertices)| {
+                    let num_pieces =
+                        (((radius - previous.1) / segment_length).ceil() as usize).max(1);
+                    let mut from = previous.0;
+                    (1..num_pieces).for_each(|piece| {
+                        let piece_radius =
+                            previous.1 + (radius - previous.1) * piece as f64 / num_pieces as f64;
+                        let to =
+                            push_site(&mut self.sites_collection, site_at(piece_radius, angle));
+                        segments.push((from, to));
+                        from = to;
+                    });
+                    let to = vertices[k * vertices.len() / num_radials];
+                    segments.push((from, to));
+                    previous = (to, radius);
+                });
+            if let Some(last) = previous.0 {
+                let site = site_at(previous.1 + segment_length, angle);
+                if let Some(end) = push_site(&mut self.sites_collection, site) {
+                    outward_paths.push((last, end, angle));
+                }
+            }
+        });
+
+        let attr = PathAttr {
+            road_class: RoadClass::Motorway,
+            is_even: false,
+            is_bridge: false,
+        };
+        segments.into_iter().for_each(|segment| {
+            let (Some(start), Some(end)) = segment else {
+                return;
+            };
+            let (site_start, altitude_start) = self.sites_collection[start];
+            let (site_end, altitude_end) = self.sites_collection[end];
+            let Ok(cost) = self.builder.evaluate_cost(
+                site_start,
+                altitude_start,
+                site_end,
+                altitude_end,
+                attr,
+            ) else {
+                return;
+            };
+            self.path_tree
+                .insert(start, end, site_start, site_end, attr, cost);
+            let angle = libm::atan2(site_end.y - site_start.y, site_end.x - site_start.x);
+            let path = Path {
+                start,
+                end,
+                angle,
+                axis: angle,
+                cost,
+                path_attr: attr,
+                switchback: vec![],
+            };
+            self.push_branches(&path, &[-1, 1], terrain);
+        });
+
+        outward_paths.into_iter().for_each(|(start, end, angle)| {
+            self.path_heap.push(Path {
+                start,
+                end,
+                angle,
+                axis: angle,
+                cost: 0.0,
+                path_attr: attr,
+                switchback: vec![],
+            });
+        });
+    }
+
+    /// Discard the highways still growing, and start the local streets from both sides of every highway.
+    fn start_infill(&mut self, terrain: &Terrain) {
+        self.phase = GrowthPhase::Infill;
+        self.path_heap.clear();
+        let mut highways = vec![];
+        self.path_tree.for_each(|path| {
+            if path.path_attr.is_highway() {
+                highways.push(*path);
+            }
+        });
+        highways.sort_by_key(|path| path.path_index);
+        highways.iter().for_each(|path| {
+            let angle = libm::atan2(
+                path.site_end.y - path.site_start.y,
+                path.site_end.x - path.site_start.x,
+            );
+            let highway = Path {
+                start: path.site_index_start,
+                end: path.site_index_end,
+                angle,
+                axis: angle,
+                cost: path.cost,
+                path_attr: path.path_attr,
+                switchback: vec![],
+            };
+            self.push_branches(&highway, &[-1, 1], terrain);
+        });
+    }
+
+    fn step_once(&mut self, terrain: &Terrain) -> Result<(), TransportError> {
+        // a growth restored from bytes samples the terrain again
+        self.builder.prepare_altitude_cache(terrain);
+        if self.phase == GrowthPhase::Highways
+            && (self.path_heap.is_empty()
+                || Some(self.iteration) >= self.builder.highway_iterations)
+        {
+            self.start_infill(terrain);
+        }
+        let Self {
+            builder,
+            sites_collection,
+            path_heap,
+            path_tree,
+            log,
+            stats,
+            iteration,
+            ..
+        } = self;
+        let iteration = *iteration;
+        path_tree.set_iteration(Some(iteration));
+
+        let current_path = path_heap.pop();
+        if current_path.is_none() {
+            return Ok(());
+        }
+        let current_path = current_path.unwrap();
+        stats.iterations += 1;
+        let site_start = sites_collection[current_path.start];
+        let site_end = sites_collection[current_path.end];
+
+        let is_accepted = builder.strategy().accept(&GrowingPath {
+            site_start: site_start.0,
+            site_end: site_end.0,
+            road_class: current_path.path_attr.road_class,
+            is_bridge: current_path.path_attr.is_bridge,
+            cost: current_path.cost,
+            iteration,
+        });
+        if !is_accepted {
+            log.record(
+                iteration,
+                GenerationEventKind::RejectedByStrategy,
+                0.0,
+                current_path.path_attr.road_class,
+            );
+            return Ok(());
+        }
+
+        let intersection_distance = builder.branch_length * builder.look_ahead;
+
+        // the legs of a switchback run close to each other without connecting
+        let excluded_sites = if current_path.switchback.is_empty() {
+            vec![current_path.start]
+        } else {
+            current_path.switchback.clone()
+        };
+
+        // find path intersection
+        stats.path_tree_queries += 1;
+        let intersection = path_tree.find(
+            &site_start.0,
+            &site_end.0,
+            intersection_distance,
+            &excluded_sites,
+            builder.snap_preference,
+        );
+        let mut intersection_pushed = false;
+        let road_class = current_path.path_attr.road_class;
+        if let PathTreeQuery::Site(site_index) = intersection {
+            log.record(
+                iteration,
+                GenerationEventKind::Snapped,
+                get_distance(site_start.0, sites_collection[site_index].0),
+                road_class,
+            );
+            path_tree.insert(
+                current_path.start,
+                site_index,
+                site_start.0,
+                sites_collection[site_index].0,
+                current_path.path_attr,
+                current_path.cost,
+            );
+            intersection_pushed = true;
+        } else if let PathTreeQuery::Path(intersection) = intersection {
+            let cross = get_cross(
+                intersection.site_start,
+                intersection.site_end,
+                site_start.0,
+                site_end.0,
+            );
+            if let Some(cross) = cross {
+                intersection_pushed = true;
+                if !cross.1 {
+                    log.record(
+                        iteration,
+                        GenerationEventKind::RejectedNearPath,
+                        0.0,
+                        road_class,
+                    );
+                } else {
+                    let cross_site = cross.0;
+                    // a crossing outside the bounds is rejected like a branch leaving them
+                    let altitude = builder.get_altitude(terrain, cross_site);
+                    if altitude.is_none() {
+                        log.record(
+                            iteration,
+                            GenerationEventKind::RejectedOutOfTerrain,
+                            0.0,
+                            road_class,
+                        );
+                    }
+                    // a split point close to a committed site is merged into it
+                    stats.path_tree_queries += 1;
+                    let coincident_site =
+                        path_tree.find_site(&cross_site, builder.merge_distance, &[]);
+                    if let (Some(_), Some((site_index, site))) = (altitude, coincident_site) {
+                        log.record(
+                            iteration,
+                            GenerationEventKind::Snapped,
+                            get_distance(site_start.0, site),
+                            road_class,
+                        );
+                        if site_index != current_path.start {
+                            path_tree.insert(
+                                current_path.start,
+                                site_index,
+                                site_start.0,
+                                site,
+                                current_path.path_attr,
+                                current_path.cost,
+                            );
+                        }
+                    } else if let Some(altitude) = altitude {
+                        log.record(
+                            iteration,
+                            GenerationEventKind::Split,
+                            get_distance(site_start.0, cross_site),
+                            road_class,
+                        );
+                        // push
+                        let site_next_index = sites_collection.len();
+                        sites_collection.push((cross_site, altitude));
+                        path_tree.split(*intersection, &cross_site, site_next_index)?;
+                        path_tree.insert(
+                            current_path.start,
+                            site_next_index,
+                            site_start.0,
+                            cross_site,
+                            current_path.path_attr,
+                            current_path.cost,
+                        );
+                    }
+                }
+            }
+        }
+
+        if intersection_pushed {
+            return Ok(());
+        }
+        // an end close to a committed site is merged into it, and no branch grows from it again
+        stats.path_tree_queries += 1;
+        if let Some((site_index, site)) =
+            path_tree.find_site(&site_end.0, builder.merge_distance, &[current_path.end])
+        {
+            if site_index == current_path.start {
+                log.record(
+                    iteration,
+                    GenerationEventKind::RejectedNearPath,
+                    0.0,
+                    road_class,
+                );
+            } else {
+                log.record(
+                    iteration,
+                    GenerationEventKind::Snapped,
+                    get_distance(site_start.0, site),
+                    road_class,
+                );
+                path_tree.insert(
+                    current_path.start,
+                    site_index,
+                    site_start.0,
+                    site,
+                    current_path.path_attr,
+                    current_path.cost,
+                );
+            }
+            return Ok(());
+        }
+        log.record(
+            iteration,
+            GenerationEventKind::Accepted,
+            get_distance(site_start.0, site_end.0),
+            road_class,
+        );
+        path_tree.insert(
+            current_path.start,
+            current_path.end,
+            site_start.0,
+            site_end.0,
+            current_path.path_attr,
+            current_path.cost,
+        );
+
+        // the branches grow from the end of the last leg of a switchback
+        let next_leg = current_path
+            .switchback
+            .iter()
+            .position(|&site_index| site_index == current_path.end)
+            .and_then(|position| current_path.switchback.get(position + 1));
+        if let Some(&next_end) = next_leg {
+            path_heap.push(Path {
+                start: current_path.end,
+                end: next_end,
+                ..current_path
+            });
+            return Ok(());
+        }
+        self.push_branches(&current_path, &[-1, 0, 1], terrain);
+        Ok(())
+    }
+
+    /// Push the branches from the end of the committed path.
+    /// `turns` are the directions of the branches: -1 and 1 for the sides and 0 for straight.
+    fn push_branches(&mut self, current_path: &Path, turns: &[i32], terrain: &Terrain) {
+        let Self {
+            builder,
+            rng,
+            sites_collection,
+            path_heap,
+            log,
+            stats,
+            check_times,
+            rotation_probabilities,
+            iteration,
+            phase,
+            ..
+        } = self;
+        let (check_times, rotation_probabilities, iteration) =
+            (*check_times, *rotation_probabilities, *iteration);
+        let site_end = sites_collection[current_path.end];
+
+        turns.iter().for_each(|&riter| {
+            let mut min_cost_candidate: Option<(f64, f64, Candidate)> = None;
+            let mut has_sea_candidate = false;
+            let mut has_steep_candidate = false;
+            let mut has_forbidden_candidate = false;
+
+            // a side branch keeps the class of its road or becomes the next lower class
+            let current_class = current_path.path_attr.road_class;
+            let mut road_class = current_class;
+            let mut is_even = current_path.path_attr.is_even;
+            if riter != 0 {
+                is_even = !is_even;
+                if *phase == GrowthPhase::Infill && current_class != RoadClass::Local {
+                    if !rng.gen_bool(rotation_probabilities[RoadClass::Local.index()]) {
+                        return;
+                    }
+                    road_class = RoadClass::Local;
+                } else if current_class != RoadClass::Local
+                    && rng.gen_bool(rotation_probabilities[current_class.index()])
+                {
+                    road_class = current_class;
+                } else if !rng.gen_bool(rotation_probabilities[RoadClass::Local.index()]) {
+                    return;
+                } else {
+                    road_class = builder.lower_class(current_class);
+                }
+            }
+            if *phase == GrowthPhase::Highways && road_class == RoadClass::Local {
+                return;
+            }
+            let site_next_attr = PathAttr {
+                road_class,
+                is_even,
+                is_bridge: false,
+            };
+
+            let current_angle = current_path.angle + riter as f64 * std::f64::consts::PI * 0.5;
+            let branch_length = {
+                let mut branch_length = builder.branch_length;
+                if site_next_attr.is_even {
+                    branch_length *= builder.even_path_length_weight
+                }
+                branch_length
+                    * builder
+                        .class_parameters(site_next_attr.road_class)
+                        .branch_length_weight
+            };
+            let axis = if current_path.path_attr.is_highway() {
+                current_path.angle
+            } else {
+                current_path.axis
+            };
+            let snapped_angle = if site_next_attr.is_highway() {
+                None
+            } else {
+                match builder.branch_pattern {
+                    BranchPattern::Organic => None,
+                    BranchPattern::Grid => Some(snap_angle(current_angle, axis)),
+                    BranchPattern::Radial => {
+                        let origin = builder.nearest_origin(site_end.0);
+                        let radial = libm::atan2(site_end.0.y - origin.y, site_end.0.x - origin.x);
+                        Some(snap_angle(current_angle, radial))
+                    }
+                    BranchPattern::TensorField => builder
+                        .tensor_field
+                        .as_ref()
+                        .and_then(|field| field.get_angle(site_end.0.x, site_end.0.y, terrain))
+                        .map(|angle| snap_angle(current_angle, angle)),
+                }
+            };
+            let branch = BranchContext {
+                site: site_end.0,
+                altitude: site_end.1,
+                path_angle: current_path.angle,
+                turn: riter,
+                road_class,
+                branch_length,
+                iteration,
+            };
+            let angles = if let Some(snapped_angle) = snapped_angle {
+                vec![snapped_angle]
+            } else {
+                (0..check_times + 1)
+                    .flat_map(|i| {
+                        [
+                            current_angle + builder.branch_angle_deviation * (i as f64),
+                            current_angle - builder.branch_angle_deviation * (i as f64),
+                        ]
+                        .into_iter()
+                        .take(if i == 0 { 1 } else { 2 })
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let angles = builder.strategy().propose_angles(&branch, angles);
+            let mut has_strategy_rejection = false;
+            angles.iter().for_each(|&angle| {
+                stats.candidate_evaluations += 1;
+                let candidate = builder.evaluate_candidate(
+                    terrain,
+                    site_end.0,
+                    site_end.1,
+                    angle,
+                    branch_length,
+                    site_next_attr,
+                );
+                match candidate {
+                    Ok(candidate) => {
+                        let score = candidate.cost
+                            + builder.get_steering_penalty(candidate.site, site_next_attr);
+                        let evaluated = builder.strategy().evaluate(
+                            &branch,
+                            &BranchCandidate {
+                                angle,
+                                site: candidate.site,
+                                altitude: candidate.altitude,
+                                is_bridge: candidate.attr.is_bridge,
+                                is_switchback: !candidate.switchback.is_empty(),
+                                cost: candidate.cost,
+                            },
+                            score,
+                        );
+                        let Some(cost) = evaluated else {
+                            has_strategy_rejection = true;
+                            return;
+                        };
+                        let min_cost = min_cost_candidate
+                            .as_ref()
+                            .map_or(f64::MAX, |(_, min_cost, _)| *min_cost);
+                        if cost < min_cost {
+                            min_cost_candidate = Some((angle, cost, candidate));
+                        }
+                    }
+                    Err(CandidateRejection::Sea) => has_sea_candidate = true,
+                    Err(CandidateRejection::TooSteep) => has_steep_candidate = true,
+                    Err(CandidateRejection::Forbidden) => has_forbidden_candidate = true,
+                    Err(CandidateRejection::OutOfTerrain) => {}
+                }
+            });
+
+            if let Some((angle, _, candidate)) = min_cost_candidate {
+                // the turning points of a switchback are added as sites and climbed leg by leg
+                let switchback = if candidate.switchback.is_empty() {
+                    vec![]
+                } else {
+                    let mut switchback = vec![current_path.end];
+                    candidate.switchback.iter().for_each(|vertex| {
+                        switchback.push(sites_collection.len());
+                        sites_collection.push(*vertex);
+                    });
+                    switchback.push(sites_collection.len());
+                    switchback
+                };
+                let site_next_index = sites_collection.len();
+                sites_collection.push((candidate.site, candidate.altitude));
+                path_heap.push(Path {
+                    start: current_path.end,
+                    end: switchback.get(1).copied().unwrap_or(site_next_index),
+                    angle,
+                    axis: if candidate.attr.is_highway() {
+                        angle
+                    } else {
+                        axis
+                    },
+                    cost: candidate.cost,
+                    path_attr: candidate.attr,
+                    switchback,
+                });
+            } else {
+                log.record(
+                    iteration,
+                    if has_strategy_rejection {
+                        GenerationEventKind::RejectedByStrategy
+                    } else if has_sea_candidate {
+                        GenerationEventKind::BranchRejectedSea
+                    } else if has_steep_candidate {
+                        GenerationEventKind::BranchRejectedSteep
+                    } else if has_forbidden_candidate {
+                        GenerationEventKind::BranchRejectedForbidden
+                    } else {
+                        GenerationEventKind::BranchRejectedOutOfTerrain
+                    },
+                    0.0,
+                    site_next_attr.road_class,
+                );
+            }
+        });
+    }
+
+    fn create_network(&self, log: GenerationLog) -> TransportNetwork {
+        let (sites, new_indices) = merge_close_sites(
+            &self
+                .sites_collection
+                .iter()
+                .map(|(site, _)| *site)
+                .collect::<Vec<_>>(),
+            self.builder.merge_distance,
+        );
+        let mut edges = vec![];
+        self.path_tree.for_each(|path| {
+            edges.push((
+                new_indices[path.site_index_start],
+                new_indices[path.site_index_end],
+                EdgeAttr {
+                    path_attr: path.path_attr,
+                    cost: path.cost,
+                    one_way: OneWay::TwoWay,
+                    iteration: path.iteration,
+                },
+            ));
+        });
+        assign_one_way(
+            &sites,
+            &mut edges,
+            self.builder.one_way_fraction,
+            &mut self.rng.clone(),
+        );
+
+        let mut network = TransportNetwork::from_edges(sites, edges, EdgeGeometries::default());
+        if self.builder.strict_planarity {
+            network = network.planarize();
+        }
+        network.update_overlays(
+            self.builder.highway_overlay_tolerance,
+            self.builder.normal_overlay_tolerance,
+        );
+        network.log = log;
+        network.report = self.report.clone();
+        network
+    }
+
+    /// Advance the growth by `iterations` iterations,
+    /// passing each path to `observer` with the sites of its ends as soon as it is committed.
+    /// The growth stops as soon as `observer` returns `false`.
+    pub(crate) fn step_observed<F>(
+        &mut self,
+        iterations: usize,
+        terrain: &Terrain,
+        observer: &mut F,
+    ) -> Result<(), TransportError>
+    where
+        F: FnMut(Edge, Site2D, Site2D) -> bool,
+    {
+        let stopped = Cell::new(false);
+        let mut observe = |path: &PathTreeObject| {
+            if stopped.get() {
+                return;
+            }
+            let edge = Edge {
+                start_index: path.site_index_start,
+                end_index: path.site_index_end,
+                road_class: path.path_attr.road_class,
+                is_one_way: false,
+                is_highway: path.path_attr.is_highway(),
+                is_even: path.path_attr.is_even,
+                is_bridge: path.path_attr.is_bridge,
+                cost: path.cost,
+            };
+            if !observer(edge, path.site_start, path.site_end) {
+                stopped.set(true);
+            }
+        };
+        // the paths committed before the growth, such as the ring roads
+        self.path_tree.for_each(&mut observe);
+        self.path_tree.record_committed();
+
+        for _ in 0..iterations {
+            if stopped.get() || self.is_finished() {
+                break;
+            }
+            self.step_once(terrain)?;
+            self.iteration += 1;
+            self.path_tree
+                .take_committed()
+                .iter()
+                .for_each(&mut observe);
+        }
+        Ok(())
+    }
+}
+
+impl TransportGrowth {
+    /// Replace the decisions of the rest of the growth by `strategy`,
+    /// such as to restore the strategy of a growth loaded by `GrowthState::from_bytes`.
+    pub fn set_growth_strategy<S: GrowthStrategy + 'static>(&mut self, strategy: S) {
+        self.builder = std::mem::take(&mut self.builder).set_growth_strategy(strategy);
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportGrowth {
+    /// Advance the growth by `iterations` iterations.
+    /// Returns `false` if there is no path left to grow.
+    pub fn step(&mut self, iterations: usize, terrain: &Terrain) -> Result<bool, TransportError> {
+        for _ in 0..iterations {
+            if self.is_target_reached() {
+                break;
+            }
+            self.step_once(terrain)?;
+            self.iteration += 1;
+        }
+        Ok(!self.is_finished())
+    }
+
+    /// Advance the growth until `budget_ms` milliseconds have passed, up to the iterations of the builder.
+    /// At least one iteration runs unless the growth is complete.
+    /// Returns `false` if the growth is complete, having no path left to grow or having run all the iterations.
+    pub fn step_with_time_budget(
+        &mut self,
+        budget_ms: f64,
+        terrain: &Terrain,
+    ) -> Result<bool, TransportError> {
+        let deadline = now_ms() + budget_ms;
+        let iterations = self.builder.growth_iterations();
+        loop {
+            let remaining = iterations.saturating_sub(self.iteration);
+            if remaining == 0 || !self.step(remaining.min(TIME_CHECK_INTERVAL), terrain)? {
+                return Ok(false);
+            }
+            if now_ms() >= deadline {
+                return Ok(self.iteration < iterations);
+            }
+        }
+    }
+
+    pub fn num_iterations(&self) -> usize {
+        self.iteration
+    }
+
+    /// Check whether there is no path left to grow, or the growth has reached the coverage or the total length of the builder.
+    pub fn is_finished(&self) -> bool {
+        (self.path_heap.is_empty() && self.phase != GrowthPhase::Highways)
+            || self.is_target_reached()
+    }
+
+    /// Check whether the growth has reached the coverage or the total length of the builder.
+    pub fn is_target_reached(&self) -> bool {
+        let is_covered =
+            self.builder
+                .coverage_target
+                .is_some_and(|(bound_min, bound_max, target_density)| {
+                    let area = (bound_max.x - bound_min.x) * (bound_max.y - bound_min.y);
+                    self.path_tree.covered_length() >= target_density * area
+                });
+        is_covered || self.path_tree.total_length() >= self.builder.max_total_length
+    }
+
+    /// Get the total length of the roads grown so far.
+    pub fn get_total_length(&self) -> f64 {
+        self.path_tree.total_length()
+    }
+
+    /// Get the counters of the work done by the growth so far.
+    pub fn get_stats(&self) -> BuildStats {
+        BuildStats {
+            path_tree_insertions: self.path_tree.num_insertions(),
+            ..self.stats
+        }
+    }
+
+    /// Create the network grown so far without stopping the growth.
+    pub fn snapshot(&self) -> TransportNetwork {
+        self.create_network(self.log.clone())
+    }
+
+    pub fn finish(mut self) -> TransportNetwork {
+        let log = std::mem::take(&mut self.log);
+        let network = self.create_network(log);
+        // the finished network is usually queried, so the index is built before it is handed over
+        network.spatial_index();
+        network
+    }
+}
+
+/// A checkpoint of a growth, from which the growth can be continued identically.
+/// The contents are opaque, and can be saved as bytes to send the growth to another thread or to resume it later.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct GrowthState {
+    growth: TransportGrowth,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl GrowthState {
+    pub fn num_iterations(&self) -> usize {
+        self.growth.iteration
+    }
+
+    /// Serialize the state into bytes.
+    /// The growth is continued identically only by the same version of this crate.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = GROWTH_STATE_VERSION.to_le_bytes().to_vec();
+        bytes.extend(bincode::serialize(&self.growth).unwrap());
+        bytes
+    }
+
+    /// Load a state written by `to_bytes`.
+    /// Returns `None` if the data is broken or written in an unsupported version.
+    pub fn from_bytes(bytes: &[u8]) -> Option<GrowthState> {
+        let version = u32::from_le_bytes(bytes.get(0..4)?.try_into().ok()?);
+        if version != GROWTH_STATE_VERSION {
+            return None;
+        }
+        let growth = bincode::deserialize(&bytes[4..]).ok()?;
+        Some(GrowthState { growth })
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportGrowth {
+    /// Take a checkpoint of the growth, which can be restored to undo the steps after it.
+    pub fn save_state(&self) -> GrowthState {
+        GrowthState {
+            growth: self.clone(),
+        }
+    }
+
+    /// Return the growth to the checkpoint.
+    pub fn restore_state(&mut self, state: &GrowthState) {
+        *self = state.growth.clone();
+    }
+
+    /// Continue a growth from the checkpoint.
+    pub fn from_state(state: &GrowthState) -> TransportGrowth {
+        state.growth.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::transport::transport::SnapPreference;
+
+    fn flat_terrain() -> Terrain {
+        Terrain::from_heightmap(11, 11, vec![1.0; 121], 0.0, 0.0, 10.0, 10.0).unwrap()
+    }
+
+    // nothing snaps by the look-ahead, so only the merging keeps the sites apart
+    fn builder() -> TransportNetworkBuilder {
+        TransportNetworkBuilder::default_city()
+            .set_start(5.0, 5.0)
+            .set_iterations(400)
+            .set_look_ahead(0.0)
+    }
+
+    /// Get the minimum distance between the committed sites of different indices.
+    fn min_site_distance(builder: TransportNetworkBuilder) -> f64 {
+        let mut sites = BTreeMap::new();
+        builder
+            .build_with_observer(0, &flat_terrain(), |edge, start, end| {
+                sites.insert(edge.start_index, start);
+                sites.insert(edge.end_index, end);
+            })
+            .unwrap();
+        assert!(sites.len() > 2);
+        let sites = sites.into_values().collect::<Vec<_>>();
+        (0..sites.len())
+            .flat_map(|i| (i + 1..sites.len()).map(move |j| (i, j)))
+            .map(|(i, j)| get_distance(sites[i], sites[j]))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn sites_closer_than_the_distance_are_merged() {
+        let distance = min_site_distance(builder().set_merge_distance(0.3));
+        assert!(distance >= 0.3, "{}", distance);
+    }
+
+    #[test]
+    fn coincident_origins_grow_from_one_site() {
+        let builder = builder().add_start(5.0, 5.0).add_start(5.0, 5.0);
+        assert!(min_site_distance(builder) > 0.0);
+    }
+
+    #[test]
+    fn coincident_sites_are_merged_without_distance() {
+        for merge_distance in [0.0, -1.0, f64::NAN] {
+            let builder = builder()
+                .add_start(5.0, 5.0)
+                .set_merge_distance(merge_distance);
+            assert!(min_site_distance(builder) > 0.0);
+        }
+    }
+
+    fn count_events(builder: TransportNetworkBuilder, kind: GenerationEventKind) -> usize {
+        builder
+            .set_record_log(true)
+            .build(0, &flat_terrain())
+            .unwrap()
+            .get_generation_log()
+            .count(kind)
+    }
+
+    #[test]
+    fn rings_of_non_finite_or_huge_radii_are_rejected() {
+        for radius in [f64::INFINITY, f64::NAN, 1e300] {
+            assert!(matches!(
+                builder().add_ring(radius).validate(&flat_terrain()),
+                Err(TransportError::InvalidParameter { .. })
+            ));
+        }
+        assert!(builder()
+            .add_ring(3.0)
+            .add_ring(0.0)
+            .validate(&flat_terrain())
+            .is_ok());
+    }
+
+    #[test]
+    fn additional_starts_outside_the_terrain_are_rejected() {
+        let builder = builder().add_start(2.0, 2.0).add_start(20.0, 5.0);
+        assert_eq!(
+            builder.validate(&flat_terrain()),
+            Err(TransportError::AdditionalStartOutsideTerrain { index: 1 })
+        );
+        assert_eq!(
+            builder.build_parallel(0, &flat_terrain(), 1.0).err(),
+            Some(TransportError::AdditionalStartOutsideTerrain { index: 1 })
+        );
+    }
+
+    #[test]
+    fn zero_look_ahead_never_splits() {
+        for preference in [SnapPreference::SitesFirst, SnapPreference::PathsFirst] {
+            let builder = builder().set_snap_preference(preference);
+            assert_eq!(count_events(builder, GenerationEventKind::Split), 0);
+        }
+    }
+
+    #[test]
+    fn huge_look_ahead_closes_the_paths_instead_of_growing() {
+        let default_accepted =
+            count_events(builder().set_look_ahead(0.8), GenerationEventKind::Accepted);
+        for preference in [SnapPreference::SitesFirst, SnapPreference::PathsFirst] {
+            let builder = builder()
+                .set_look_ahead(1e9)
+                .set_snap_preference(preference);
+            assert!(count_events(builder, GenerationEventKind::Accepted) < default_accepted);
+        }
+        // every path reaching the others connects to the start of the nearest one
+        let builder = builder()
+            .set_look_ahead(1e9)
+            .set_snap_preference(SnapPreference::SitesFirst);
+        assert_eq!(count_events(builder, GenerationEventKind::Split), 0);
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{math::site_order, transport::TransportNetwork},
+    Site2D,
+};
+
+/// 64-bit FNV-1a, which is stable across platforms and Rust versions unlike `DefaultHasher`.
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        bytes.iter().for_each(|byte| {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        });
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_f64(&mut self, value: f64) {
+        // treat -0.0 and 0.0 as the same value
+        let value = if value == 0.0 { 0.0 } else { value };
+        self.write_u64(value.to_bits());
+    }
+
+    fn write_site(&mut self, site: &Site2D) {
+        self.write_f64(site.x);
+        self.write_f64(site.y);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Compute a stable hash of the nodes, the edges and their attributes.
+    /// The hash does not depend on the node indices or the order of edges.
+    pub fn content_hash(&self) -> u64 {
+        let mut node_hashes = self
+            .nodes
+            .iter()
+            .map(|site| {
+                let mut hasher = StableHasher::new();
+                hasher.write_site(site);
+                hasher.finish()
+            })
+            .collect::<Vec<_>>();
+        node_hashes.sort_unstable();
+
+        let mut edge_hashes = self
+            .iter_edges()
+            .map(|(start, end, attr)| {
+                // orient the edge by its coordinates rather than by the node indices
+                let mut polyline = self.get_polyline(start, end);
+                if site_order(&self.nodes[start], &self.nodes[end]).is_gt() {
+                    polyline.reverse();
+                }
+                let mut hasher = StableHasher::new();
+                polyline.iter().for_each(|site| hasher.write_site(site));
+                hasher.write(&[
+                    attr.path_attr.road_class.index() as u8,
+                    attr.path_attr.is_even as u8,
+                    attr.path_attr.is_bridge as u8,
+                    attr.one_way as u8,
+                ]);
+                hasher.write_f64(attr.cost);
+                hasher.finish()
+            })
+            .collect::<Vec<_>>();
+        edge_hashes.sort_unstable();
+
+        let mut hasher = StableHasher::new();
+        hasher.write_u64(node_hashes.len() as u64);
+        node_hashes.iter().for_each(|hash| hasher.write_u64(*hash));
+        hasher.write_u64(edge_hashes.len() as u64);
+        edge_hashes.iter().for_each(|hash| hasher.write_u64(*hash));
+        hasher.finish()
+    }
+}
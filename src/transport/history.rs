@@ -0,0 +1,69 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::transport::TransportNetwork;
+
+/// Get the construction time of a path committed in `iteration` among the iterations of all the commits, sorted without duplicates.
+fn construction_time(iterations: &[usize], iteration: usize) -> f64 {
+    let position = iterations.binary_search(&iteration).unwrap_or(0);
+    position as f64 / iterations.len().saturating_sub(1).max(1) as f64
+}
+
+impl TransportNetwork {
+    /// Get the iterations in which the paths of the edges were committed, sorted without duplicates.
+    fn commit_iterations(&self) -> Vec<usize> {
+        let mut iterations = self
+            .iter_edges()
+            .filter_map(|(_, _, attr)| attr.iteration)
+            .collect::<Vec<_>>();
+        iterations.sort_unstable();
+        iterations.dedup();
+        iterations
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Get the order in which the edges were grown as flattened `[edge0, iteration0, edge1, iteration1, ...]`,
+    /// where each edge is an index into `get_edges`, to replay the growth as an animation.
+    ///
+    /// The edges are ordered by the iteration in which their paths were committed, and the edges of the same iteration by their indices.
+    /// The halves of a path split by a later one keep the iteration of the path.
+    /// The edges which were not grown, such as those given to the builder or added by editing, are left out.
+    pub fn history(&self) -> Vec<usize> {
+        let mut history = self
+            .iter_edges()
+            .enumerate()
+            .filter_map(|(index, (_, _, attr))| attr.iteration.map(|iteration| (iteration, index)))
+            .collect::<Vec<_>>();
+        history.sort_unstable();
+        history
+            .into_iter()
+            .flat_map(|(iteration, index)| [index, iteration])
+            .collect::<Vec<_>>()
+    }
+
+    /// Get the construction time of the edge from 0 for the first committed path to 1 for the last one,
+    /// spaced evenly by the order of the commits, to color the roads by age or to model the growth of a city over time.
+    /// The halves of a path split by a later one keep the time of the path.
+    ///
+    /// Returns `None` if there is no such edge or it was not grown.
+    pub fn edge_construction_time(&self, index_a: usize, index_b: usize) -> Option<f64> {
+        self.edge_length(index_a, index_b)?;
+        let iteration = self.graph.has_edge(index_a, index_b).1.iteration?;
+        Some(construction_time(&self.commit_iterations(), iteration))
+    }
+
+    /// Get the construction times of all the edges as `edge_construction_time` in the order of `get_edges`,
+    /// which are NaN for the edges which were not grown.
+    pub fn construction_times(&self) -> Vec<f64> {
+        let iterations = self.commit_iterations();
+        self.iter_edges()
+            .map(|(_, _, attr)| {
+                attr.iteration.map_or(f64::NAN, |iteration| {
+                    construction_time(&iterations, iteration)
+                })
+            })
+            .collect::<Vec<_>>()
+    }
+}
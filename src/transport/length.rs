@@ -0,0 +1,79 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    terrain::Terrain,
+    transport::{road_class::ROAD_CLASSES, transport::TransportNetwork},
+};
+
+impl TransportNetwork {
+    /// Get the length of the edge along the surface of the terrain.
+    fn get_edge_length_3d(
+        &self,
+        index_a: usize,
+        index_b: usize,
+        terrain: &Terrain,
+        sample_spacing: f64,
+    ) -> f64 {
+        self.sample_edge_3d(index_a, index_b, terrain, sample_spacing)
+            .windows(2)
+            .map(|pair| {
+                let run = pair[1].1 - pair[0].1;
+                let rise = pair[1].2 - pair[0].2;
+                (run * run + rise * rise).sqrt()
+            })
+            .sum::<f64>()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Get the length of the polyline of the edge, or `None` if there is no such edge.
+    pub fn edge_length(&self, index_a: usize, index_b: usize) -> Option<f64> {
+        if index_a >= self.nodes.len()
+            || index_b >= self.nodes.len()
+            || !self.graph.has_edge(index_a, index_b).0
+        {
+            return None;
+        }
+        Some(self.get_edge_length(index_a, index_b))
+    }
+
+    /// Get the length of the edge along the surface of the terrain, or `None` if there is no such edge.
+    /// The altitudes are sampled as `polylines_3d` at most every `sample_spacing`.
+    pub fn edge_length_3d(
+        &self,
+        index_a: usize,
+        index_b: usize,
+        terrain: &Terrain,
+        sample_spacing: f64,
+    ) -> Option<f64> {
+        self.edge_length(index_a, index_b)?;
+        Some(self.get_edge_length_3d(index_a, index_b, terrain, sample_spacing))
+    }
+
+    /// Get the lengths of all the edges in the order of `get_edges`.
+    pub fn edge_lengths(&self) -> Vec<f64> {
+        self.edges()
+            .map(|edge| self.get_edge_length(edge.start_index, edge.end_index))
+            .collect::<Vec<_>>()
+    }
+
+    /// Get the lengths of all the edges along the surface of the terrain in the order of `get_edges`.
+    pub fn edge_lengths_3d(&self, terrain: &Terrain, sample_spacing: f64) -> Vec<f64> {
+        self.edges()
+            .map(|edge| {
+                self.get_edge_length_3d(edge.start_index, edge.end_index, terrain, sample_spacing)
+            })
+            .collect::<Vec<_>>()
+    }
+
+    /// Get the total length of the edges of each road class, in the order of `RoadClass`.
+    pub fn total_length_by_class(&self) -> Vec<f64> {
+        let mut lengths = vec![0.0; ROAD_CLASSES.len()];
+        self.iter_edges().for_each(|(start, end, attr)| {
+            lengths[attr.path_attr.road_class.index()] += self.get_edge_length(start, end);
+        });
+        lengths
+    }
+}
@@ -0,0 +1,283 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::{
+    road_class::{RoadClass, ROAD_CLASSES},
+    transport::TransportNetwork,
+};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GenerationEventKind {
+    /// The path was committed and its branches were evaluated.
+    Accepted,
+    /// The path was connected to an existing site nearby instead.
+    Snapped,
+    /// The path was connected to the middle of an existing path, splitting it.
+    Split,
+    /// The path was discarded because it runs too close to an existing path.
+    RejectedNearPath,
+    /// The path was discarded because the crossing with an existing path is outside the terrain.
+    RejectedOutOfTerrain,
+    /// No branch could be placed because every candidate was in the sea.
+    BranchRejectedSea,
+    /// No branch could be placed because every candidate was outside the terrain.
+    BranchRejectedOutOfTerrain,
+    /// No branch could be placed because every candidate on the land exceeded the maximum grade.
+    BranchRejectedSteep,
+    /// No branch could be placed because every candidate on the land entered a forbidden area.
+    BranchRejectedForbidden,
+    /// The path, or every candidate of the branch, was rejected by the growth strategy.
+    RejectedByStrategy,
+}
+
+const EVENT_KINDS: [GenerationEventKind; 10] = [
+    GenerationEventKind::Accepted,
+    GenerationEventKind::Snapped,
+    GenerationEventKind::Split,
+    GenerationEventKind::RejectedNearPath,
+    GenerationEventKind::RejectedOutOfTerrain,
+    GenerationEventKind::BranchRejectedSea,
+    GenerationEventKind::BranchRejectedOutOfTerrain,
+    GenerationEventKind::BranchRejectedSteep,
+    GenerationEventKind::BranchRejectedForbidden,
+    GenerationEventKind::RejectedByStrategy,
+];
+
+impl GenerationEventKind {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Snapped => "snapped",
+            Self::Split => "split",
+            Self::RejectedNearPath => "rejected_near_path",
+            Self::RejectedOutOfTerrain => "rejected_out_of_terrain",
+            Self::BranchRejectedSea => "branch_rejected_sea",
+            Self::BranchRejectedOutOfTerrain => "branch_rejected_out_of_terrain",
+            Self::BranchRejectedSteep => "branch_rejected_steep",
+            Self::BranchRejectedForbidden => "branch_rejected_forbidden",
+            Self::RejectedByStrategy => "rejected_by_strategy",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct GenerationEvent {
+    iteration: usize,
+    kind: GenerationEventKind,
+    length: f64,
+    road_class: RoadClass,
+}
+
+/// The ordered record of the decisions made while growing a network.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GenerationLog {
+    enabled: bool,
+    events: Vec<GenerationEvent>,
+}
+
+impl GenerationLog {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            events: vec![],
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        iteration: usize,
+        kind: GenerationEventKind,
+        length: f64,
+        road_class: RoadClass,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        self.events.push(GenerationEvent {
+            iteration,
+            kind,
+            length,
+            road_class,
+        });
+    }
+
+    /// Merge the logs of the networks grown separately, as if they had grown in lockstep:
+    /// the events are ordered by their iterations, and the events of the same iteration follow the order of `logs`.
+    pub(crate) fn merge<'a, I>(logs: I) -> Self
+    where
+        I: IntoIterator<Item = &'a GenerationLog>,
+    {
+        let mut merged = Self::new(false);
+        logs.into_iter().for_each(|log| {
+            merged.enabled |= log.enabled;
+            merged.events.extend(log.events.iter().copied());
+        });
+        merged.events.sort_by_key(|event| event.iteration);
+        merged
+    }
+
+    fn events_of(&self, kind: GenerationEventKind) -> impl Iterator<Item = &GenerationEvent> {
+        self.events.iter().filter(move |event| event.kind == kind)
+    }
+
+    fn length_until<F>(&self, is_counted: F, until_iteration: usize) -> f64
+    where
+        F: Fn(RoadClass) -> bool,
+    {
+        self.events
+            .iter()
+            .filter(|event| is_counted(event.road_class) && event.iteration < until_iteration)
+            .map(|event| event.length)
+            .sum()
+    }
+
+    fn length_series<F>(&self, is_counted: F, bucket_size: usize) -> Vec<f64>
+    where
+        F: Fn(RoadClass) -> bool,
+    {
+        let last_iteration = self.events.last().map_or(0, |event| event.iteration);
+        if bucket_size == 0 {
+            return vec![];
+        }
+        let mut series = vec![0.0; last_iteration / bucket_size + 1];
+        self.events
+            .iter()
+            .filter(|event| is_counted(event.road_class))
+            .for_each(|event| series[event.iteration / bucket_size] += event.length);
+        (1..series.len()).for_each(|i| series[i] += series[i - 1]);
+        series
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl GenerationLog {
+    pub fn num_events(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn count(&self, kind: GenerationEventKind) -> usize {
+        self.events_of(kind).count()
+    }
+
+    /// Count the events of `kind` recorded in iterations `from_iteration..to_iteration`.
+    pub fn count_between(
+        &self,
+        kind: GenerationEventKind,
+        from_iteration: usize,
+        to_iteration: usize,
+    ) -> usize {
+        self.events_of(kind)
+            .filter(|event| (from_iteration..to_iteration).contains(&event.iteration))
+            .count()
+    }
+
+    /// The total length of the paths committed (accepted, snapped or split) before `until_iteration`.
+    pub fn committed_length(&self, is_highway: bool, until_iteration: usize) -> f64 {
+        self.length_until(
+            |road_class| (road_class != RoadClass::Local) == is_highway,
+            until_iteration,
+        )
+    }
+
+    /// The cumulative committed length at the end of every `bucket_size` iterations.
+    pub fn committed_length_series(&self, is_highway: bool, bucket_size: usize) -> Vec<f64> {
+        self.length_series(
+            |road_class| (road_class != RoadClass::Local) == is_highway,
+            bucket_size,
+        )
+    }
+
+    /// Same as `committed_length` for the paths of `road_class`.
+    pub fn committed_length_of(&self, road_class: RoadClass, until_iteration: usize) -> f64 {
+        self.length_until(|class| class == road_class, until_iteration)
+    }
+
+    /// Same as `committed_length_series` for the paths of `road_class`.
+    pub fn committed_length_series_of(
+        &self,
+        road_class: RoadClass,
+        bucket_size: usize,
+    ) -> Vec<f64> {
+        self.length_series(|class| class == road_class, bucket_size)
+    }
+
+    pub fn to_json(&self) -> String {
+        let counts = EVENT_KINDS
+            .iter()
+            .map(|kind| format!("\"{}\":{}", kind.name(), self.count(*kind)))
+            .collect::<Vec<_>>()
+            .join(",");
+        let events = self
+            .events
+            .iter()
+            .map(|event| {
+                format!(
+                    "{{\"iteration\":{},\"kind\":\"{}\",\"length\":{},\"road_class\":\"{}\",\"is_highway\":{}}}",
+                    event.iteration,
+                    event.kind.name(),
+                    event.length,
+                    event.road_class.name(),
+                    event.road_class != RoadClass::Local
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let class_lengths = ROAD_CLASSES
+            .iter()
+            .map(|class| {
+                format!(
+                    "\"{}\":{}",
+                    class.name(),
+                    self.committed_length_of(*class, usize::MAX)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{{\"counts\":{{{}}},\"committed_length\":{{\"highway\":{},\"normal\":{},{}}},\"events\":[{}]}}",
+            counts,
+            self.committed_length(true, usize::MAX),
+            self.committed_length(false, usize::MAX),
+            class_lengths,
+            events
+        )
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Get the generation log. It is empty unless the builder was set to record it.
+    pub fn get_generation_log(&self) -> GenerationLog {
+        self.log.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn committed_lengths_are_kept_per_class() {
+        let mut log = GenerationLog::new(true);
+        log.record(0, GenerationEventKind::Accepted, 1.0, RoadClass::Motorway);
+        log.record(1, GenerationEventKind::Accepted, 2.0, RoadClass::Arterial);
+        log.record(1, GenerationEventKind::Split, 4.0, RoadClass::Collector);
+        log.record(2, GenerationEventKind::Snapped, 8.0, RoadClass::Local);
+
+        assert_eq!(
+            log.committed_length_of(RoadClass::Arterial, usize::MAX),
+            2.0
+        );
+        assert_eq!(log.committed_length_of(RoadClass::Collector, 1), 0.0);
+        assert_eq!(
+            log.committed_length_series_of(RoadClass::Collector, 1),
+            vec![0.0, 4.0, 4.0]
+        );
+        assert_eq!(log.committed_length(true, usize::MAX), 7.0);
+        assert_eq!(log.committed_length(false, usize::MAX), 8.0);
+        assert!(log.to_json().contains("\"collector\":4"));
+    }
+}
@@ -0,0 +1,502 @@
+use std::collections::BinaryHeap;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    density::DensityMap,
+    terrain::{Terrain, SEA_LEVEL},
+    transport::{
+        error::TransportError,
+        geometry::EdgeGeometries,
+        math::get_distance,
+        oneway::OneWay,
+        road_class::RoadClass,
+        transport::{EdgeAttr, PathAttr, SnapPreference, TransportNetwork},
+        treeobj::{PathTree, PathTreeObject, PathTreeQuery},
+    },
+    Site2D,
+};
+
+/// A road segment waiting in the queue, which is placed by the local constraints when its delay comes.
+struct Proposal {
+    delay: usize,
+    sequence: usize,
+    start: usize,
+    angle: f64,
+    is_highway: bool,
+}
+
+impl Ord for Proposal {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (other.delay, other.sequence).cmp(&(self.delay, self.sequence))
+    }
+}
+
+impl PartialOrd for Proposal {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Proposal {
+    fn eq(&self, other: &Self) -> bool {
+        self.delay == other.delay && self.sequence == other.sequence
+    }
+}
+
+impl Eq for Proposal {}
+
+/// How a proposed segment is connected to the network by the local constraints.
+enum Connection {
+    /// The segment ends at a new site, from which the road keeps growing.
+    Open(Site2D),
+    /// The segment ends at an existing site.
+    Site(usize),
+    /// The segment ends on an existing path, which is split there.
+    Path(PathTreeObject, Site2D),
+}
+
+/// Grows a network by the L-system of Parish and Müller (2001), as an alternative to `TransportNetworkBuilder`
+/// for comparing the two algorithms on the same terrain.
+///
+/// Road segments are proposed by the global goals: the highways head for the densest population among a few sampled directions,
+/// and the streets grow straight and branch at right angles where the population exceeds the threshold.
+/// The proposals are then adjusted by the local constraints: the segments on the sea or steeper than the maximum grade are dropped,
+/// and those crossing or ending near the existing roads are cut or extended to form junctions.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct LSystemBuilder {
+    start: Site2D,
+    iterations: usize,
+    highway_length: f64,
+    street_length: f64,
+    highway_sample_angle: f64,
+    highway_samples: usize,
+    highway_branch_probability: f64,
+    street_branch_probability: f64,
+    street_branch_delay: usize,
+    population_threshold: f64,
+    snap_distance: f64,
+    max_grade: f64,
+    density_map: Option<DensityMap>,
+}
+
+impl Default for LSystemBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl LSystemBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            start: Site2D { x: 0.0, y: 0.0 },
+            iterations: 0,
+            highway_length: 1.0,
+            street_length: 0.5,
+            highway_sample_angle: std::f64::consts::PI / 12.0,
+            highway_samples: 5,
+            highway_branch_probability: 0.05,
+            street_branch_probability: 0.4,
+            street_branch_delay: 5,
+            population_threshold: 0.1,
+            snap_distance: 0.25,
+            max_grade: 0.5,
+            density_map: None,
+        }
+    }
+
+    pub fn set_start(self, start_x: f64, start_y: f64) -> Self {
+        Self {
+            start: Site2D {
+                x: start_x,
+                y: start_y,
+            },
+            ..self
+        }
+    }
+
+    /// Set the number of the segments to place.
+    pub fn set_iterations(self, iterations: usize) -> Self {
+        Self { iterations, ..self }
+    }
+
+    /// Set the length of the segments of the highways.
+    pub fn set_highway_length(self, highway_length: f64) -> Self {
+        Self {
+            highway_length,
+            ..self
+        }
+    }
+
+    /// Set the length of the segments of the streets.
+    pub fn set_street_length(self, street_length: f64) -> Self {
+        Self {
+            street_length,
+            ..self
+        }
+    }
+
+    /// Set the directions sampled for the next segment of a highway:
+    /// `samples` directions within `sample_angle` to each side of the current direction.
+    pub fn set_highway_samples(self, sample_angle: f64, samples: usize) -> Self {
+        Self {
+            highway_sample_angle: sample_angle,
+            highway_samples: samples,
+            ..self
+        }
+    }
+
+    /// Set the probability that a highway branches into another highway to each side at each segment.
+    pub fn set_highway_branch_probability(self, highway_branch_probability: f64) -> Self {
+        Self {
+            highway_branch_probability,
+            ..self
+        }
+    }
+
+    /// Set the probability that a road branches into a street to each side at each segment.
+    pub fn set_street_branch_probability(self, street_branch_probability: f64) -> Self {
+        Self {
+            street_branch_probability,
+            ..self
+        }
+    }
+
+    /// Set how many steps the streets branching from a highway wait,
+    /// so that the highways spread before the streets fill the area around them.
+    pub fn set_street_branch_delay(self, street_branch_delay: usize) -> Self {
+        Self {
+            street_branch_delay,
+            ..self
+        }
+    }
+
+    /// Set the population below which the streets do not grow.
+    pub fn set_population_threshold(self, population_threshold: f64) -> Self {
+        Self {
+            population_threshold,
+            ..self
+        }
+    }
+
+    /// Set the distance within which a segment is snapped to an existing site or extended to an existing road.
+    pub fn set_snap_distance(self, snap_distance: f64) -> Self {
+        Self {
+            snap_distance,
+            ..self
+        }
+    }
+
+    /// Set the maximum grade (rise over run) of the segments.
+    pub fn set_max_grade(self, max_grade: f64) -> Self {
+        Self { max_grade, ..self }
+    }
+
+    /// Set the population density which guides the global goals. Without it, the population is uniform on the land.
+    pub fn set_density_map(self, density_map: DensityMap) -> Self {
+        Self {
+            density_map: Some(density_map),
+            ..self
+        }
+    }
+
+    /// Check the parameters and the start.
+    pub fn validate(&self, terrain: &Terrain) -> Result<(), TransportError> {
+        let check = |name: &'static str, is_valid: bool, requirement: &'static str| {
+            if is_valid {
+                Ok(())
+            } else {
+                Err(TransportError::InvalidParameter { name, requirement })
+            }
+        };
+        let positive = |value: f64| value > 0.0 && value.is_finite();
+        const POSITIVE: &str = "positive and finite";
+        const PROBABILITY: &str = "between 0 and 1";
+
+        if self.iterations == 0 {
+            return Err(TransportError::NoIterations);
+        }
+        check("highway_length", positive(self.highway_length), POSITIVE)?;
+        check("street_length", positive(self.street_length), POSITIVE)?;
+        check(
+            "sample_angle of set_highway_samples",
+            self.highway_sample_angle >= 0.0 && self.highway_sample_angle.is_finite(),
+            "zero or positive and finite",
+        )?;
+        check(
+            "samples of set_highway_samples",
+            self.highway_samples > 0,
+            "positive",
+        )?;
+        check(
+            "highway_branch_probability",
+            (0.0..=1.0).contains(&self.highway_branch_probability),
+            PROBABILITY,
+        )?;
+        check(
+            "street_branch_probability",
+            (0.0..=1.0).contains(&self.street_branch_probability),
+            PROBABILITY,
+        )?;
+        check(
+            "snap_distance",
+            self.snap_distance >= 0.0 && self.snap_distance.is_finite(),
+            "zero or positive and finite",
+        )?;
+        check("max_grade", self.max_grade >= 0.0, "zero or positive")?;
+        if self.get_land_altitude(terrain, self.start).is_none() {
+            return Err(TransportError::StartOutsideTerrain);
+        }
+        Ok(())
+    }
+
+    pub fn build(&self, seed: u32, terrain: &Terrain) -> Result<TransportNetwork, TransportError> {
+        self.validate(terrain)?;
+        let mut rng = ChaCha12Rng::seed_from_u64(seed as u64);
+
+        let mut sites = vec![self.start];
+        let mut path_tree = PathTree::new();
+        let mut queue = BinaryHeap::new();
+        let mut sequence = 0;
+        let mut propose = |queue: &mut BinaryHeap<Proposal>,
+                           delay: usize,
+                           start: usize,
+                           angle: f64,
+                           is_highway: bool| {
+            queue.push(Proposal {
+                delay,
+                sequence,
+                start,
+                angle,
+                is_highway,
+            });
+            sequence += 1;
+        };
+
+        let initial_angle = rng.gen_range(0.0..std::f64::consts::PI);
+        propose(&mut queue, 0, 0, initial_angle, true);
+        propose(&mut queue, 0, 0, initial_angle + std::f64::consts::PI, true);
+
+        let mut placed = 0;
+        while placed < self.iterations {
+            let Some(proposal) = queue.pop() else {
+                break;
+            };
+            let site_start = sites[proposal.start];
+            let length = if proposal.is_highway {
+                self.highway_length
+            } else {
+                self.street_length
+            };
+            let site_end = Self::advance(site_start, proposal.angle, length);
+            let Some(connection) =
+                self.localize(terrain, &path_tree, &sites, proposal.start, site_end)
+            else {
+                continue;
+            };
+
+            let path_attr = PathAttr {
+                road_class: if proposal.is_highway {
+                    RoadClass::Motorway
+                } else {
+                    RoadClass::Local
+                },
+                is_even: false,
+                is_bridge: false,
+            };
+            let (end, is_open) = match connection {
+                Connection::Open(site) => {
+                    sites.push(site);
+                    (sites.len() - 1, true)
+                }
+                Connection::Site(index) => (index, false),
+                Connection::Path(path, site) => {
+                    sites.push(site);
+                    let index = sites.len() - 1;
+                    path_tree.split(path, &site, index)?;
+                    (index, false)
+                }
+            };
+            let site_end = sites[end];
+            path_tree.set_iteration(Some(placed));
+            path_tree.insert(
+                proposal.start,
+                end,
+                site_start,
+                site_end,
+                path_attr,
+                get_distance(site_start, site_end),
+            );
+            placed += 1;
+            if !is_open {
+                continue;
+            }
+
+            // global goals
+            let delay = proposal.delay + 1;
+            let right_angle = std::f64::consts::FRAC_PI_2;
+            if proposal.is_highway {
+                let angle = (0..self.highway_samples)
+                    .map(|_| proposal.angle + rng.gen_range(-1.0..=1.0) * self.highway_sample_angle)
+                    .map(|angle| {
+                        let population =
+                            self.population(Self::advance(site_end, angle, self.highway_length));
+                        (angle, population)
+                    })
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map_or(proposal.angle, |(angle, _)| angle);
+                propose(&mut queue, delay, end, angle, true);
+                [-right_angle, right_angle].iter().for_each(|turn| {
+                    if rng.gen_bool(self.highway_branch_probability) {
+                        propose(&mut queue, delay, end, proposal.angle + turn, true);
+                    } else if rng.gen_bool(self.street_branch_probability)
+                        && self.is_populated(site_end, proposal.angle + turn)
+                    {
+                        propose(
+                            &mut queue,
+                            delay + self.street_branch_delay,
+                            end,
+                            proposal.angle + turn,
+                            false,
+                        );
+                    }
+                });
+            } else {
+                if self.is_populated(site_end, proposal.angle) {
+                    propose(&mut queue, delay, end, proposal.angle, false);
+                }
+                [-right_angle, right_angle].iter().for_each(|turn| {
+                    if rng.gen_bool(self.street_branch_probability)
+                        && self.is_populated(site_end, proposal.angle + turn)
+                    {
+                        propose(&mut queue, delay, end, proposal.angle + turn, false);
+                    }
+                });
+            }
+        }
+
+        let mut edges = vec![];
+        path_tree.for_each(|path| {
+            edges.push((
+                path.site_index_start,
+                path.site_index_end,
+                EdgeAttr {
+                    path_attr: path.path_attr,
+                    cost: path.cost,
+                    one_way: OneWay::TwoWay,
+                    iteration: path.iteration,
+                },
+            ));
+        });
+        let mut network = TransportNetwork::from_edges(sites, edges, EdgeGeometries::default());
+        network.update_overlays(0.0, 0.0);
+        Ok(network)
+    }
+}
+
+impl LSystemBuilder {
+    fn advance(site: Site2D, angle: f64, length: f64) -> Site2D {
+        Site2D {
+            x: site.x + length * libm::cos(angle),
+            y: site.y + length * libm::sin(angle),
+        }
+    }
+
+    fn get_land_altitude(&self, terrain: &Terrain, site: Site2D) -> Option<f64> {
+        terrain
+            .get_altitude(site.x, site.y)
+            .filter(|altitude| *altitude >= SEA_LEVEL)
+    }
+
+    fn population(&self, site: Site2D) -> f64 {
+        self.density_map
+            .as_ref()
+            .map_or(1.0, |density_map| density_map.sample(site.x, site.y))
+    }
+
+    /// Check whether a street from `site` toward `angle` would reach the population threshold.
+    fn is_populated(&self, site: Site2D, angle: f64) -> bool {
+        self.population(Self::advance(site, angle, self.street_length)) >= self.population_threshold
+    }
+
+    /// Join a segment to the path at `site`, or to an end of the path if it is within the snap distance.
+    fn join(&self, path: PathTreeObject, site: Site2D) -> Connection {
+        if get_distance(site, path.site_start) < self.snap_distance {
+            Connection::Site(path.site_index_start)
+        } else if get_distance(site, path.site_end) < self.snap_distance {
+            Connection::Site(path.site_index_end)
+        } else {
+            Connection::Path(path, site)
+        }
+    }
+
+    /// Apply the local constraints to the segment from the site `start` to `site_end`,
+    /// or return `None` if the segment is dropped.
+    fn localize(
+        &self,
+        terrain: &Terrain,
+        path_tree: &PathTree,
+        sites: &[Site2D],
+        start: usize,
+        site_end: Site2D,
+    ) -> Option<Connection> {
+        let site_start = sites[start];
+        let length = get_distance(site_start, site_end);
+        let min_length = self.snap_distance.min(length) * 0.5;
+
+        // a crossing road cuts the segment to form a junction
+        let connection =
+            if let Some((path, site)) = path_tree.find_crossing(&site_start, &site_end, &[start]) {
+                self.join(path, site)
+            } else if let PathTreeQuery::Site(index) = path_tree.find(
+                &site_start,
+                &site_end,
+                self.snap_distance,
+                &[start],
+                SnapPreference::SitesFirst,
+            ) {
+                Connection::Site(index)
+            } else {
+                // a road just beyond the end is reached by extending the segment
+                let extended_end = Self::advance(
+                    site_start,
+                    libm::atan2(site_end.y - site_start.y, site_end.x - site_start.x),
+                    length + self.snap_distance,
+                );
+                match path_tree.find_crossing(&site_start, &extended_end, &[start]) {
+                    Some((path, site)) => self.join(path, site),
+                    None => Connection::Open(site_end),
+                }
+            };
+
+        let connected_end = match connection {
+            Connection::Open(site) | Connection::Path(_, site) => site,
+            Connection::Site(index) => {
+                // the segment turned to the site must not cross another road on the way
+                if path_tree
+                    .find_crossing(&site_start, &sites[index], &[start, index])
+                    .is_some()
+                {
+                    return None;
+                }
+                sites[index]
+            }
+        };
+        let connected_length = get_distance(site_start, connected_end);
+        if connected_length < min_length {
+            return None;
+        }
+        let altitude_start = self.get_land_altitude(terrain, site_start)?;
+        let altitude_end = self.get_land_altitude(terrain, connected_end)?;
+        if (altitude_end - altitude_start).abs() > self.max_grade * connected_length {
+            return None;
+        }
+        Some(connection)
+    }
+}
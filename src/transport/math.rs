@@ -30,3 +30,128 @@ pub fn get_cross(
 
     Some((Site2D { x, y }, passing))
 }
+
+/// Order the sites by their coordinates, which does not depend on the node indices.
+pub fn site_order(a: &Site2D, b: &Site2D) -> std::cmp::Ordering {
+    a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+}
+
+pub fn get_distance(site_a: Site2D, site_b: Site2D) -> f64 {
+    ((site_a.x - site_b.x).powi(2) + (site_a.y - site_b.y).powi(2)).sqrt()
+}
+
+pub fn get_distance_to_segment(site: Site2D, line_start: Site2D, line_end: Site2D) -> f64 {
+    let dx = line_end.x - line_start.x;
+    let dy = line_end.y - line_start.y;
+    let squared_length = dx * dx + dy * dy;
+    if squared_length == 0.0 {
+        return ((site.x - line_start.x).powi(2) + (site.y - line_start.y).powi(2)).sqrt();
+    }
+    let t = (((site.x - line_start.x) * dx + (site.y - line_start.y) * dy) / squared_length)
+        .clamp(0.0, 1.0);
+    let x = line_start.x + t * dx;
+    let y = line_start.y + t * dy;
+    ((site.x - x).powi(2) + (site.y - y).powi(2)).sqrt()
+}
+
+/// Simplify a polyline with the Douglas-Peucker algorithm.
+/// The first and the last sites are always kept.
+pub fn simplify_polyline(polyline: &[Site2D], tolerance: f64) -> Vec<Site2D> {
+    if polyline.len() < 3 || tolerance <= 0.0 {
+        return polyline.to_vec();
+    }
+
+    let mut keep = vec![false; polyline.len()];
+    keep[0] = true;
+    keep[polyline.len() - 1] = true;
+
+    let mut stack = vec![(0, polyline.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let mut max_distance = 0.0;
+        let mut max_index = first;
+        (first + 1..last).for_each(|i| {
+            let distance = get_distance_to_segment(polyline[i], polyline[first], polyline[last]);
+            if distance > max_distance {
+                max_distance = distance;
+                max_index = i;
+            }
+        });
+        if max_distance > tolerance {
+            keep[max_index] = true;
+            stack.push((first, max_index));
+            stack.push((max_index, last));
+        }
+    }
+
+    polyline
+        .iter()
+        .zip(keep.iter())
+        .filter(|(_, keep)| **keep)
+        .map(|(site, _)| *site)
+        .collect::<Vec<_>>()
+}
+
+/// Check whether `site` is inside `polygon` by the even-odd rule.
+/// The polygon is closed implicitly, so the last vertex need not repeat the first one.
+pub fn is_inside_polygon(site: Site2D, polygon: &[Site2D]) -> bool {
+    let mut is_inside = false;
+    let mut prev = match polygon.last() {
+        Some(prev) => *prev,
+        None => return false,
+    };
+    polygon.iter().for_each(|&vertex| {
+        if (vertex.y > site.y) != (prev.y > site.y)
+            && site.x < (prev.x - vertex.x) * (site.y - vertex.y) / (prev.y - vertex.y) + vertex.x
+        {
+            is_inside = !is_inside;
+        }
+        prev = vertex;
+    });
+    is_inside
+}
+
+/// Get the range of the parameter `t` of the part of the segment inside the rectangle from `bound_min` to `bound_max`,
+/// where `t` runs from 0 at `line_start` to 1 at `line_end`, or `None` if the segment misses the rectangle.
+pub fn get_clipped_range(
+    line_start: Site2D,
+    line_end: Site2D,
+    bound_min: Site2D,
+    bound_max: Site2D,
+) -> Option<(f64, f64)> {
+    let dx = line_end.x - line_start.x;
+    let dy = line_end.y - line_start.y;
+    // the Liang-Barsky algorithm
+    let (mut t_min, mut t_max) = (0.0_f64, 1.0_f64);
+    for (p, q) in [
+        (-dx, line_start.x - bound_min.x),
+        (dx, bound_max.x - line_start.x),
+        (-dy, line_start.y - bound_min.y),
+        (dy, bound_max.y - line_start.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t_min = t_min.max(q / p);
+        } else {
+            t_max = t_max.min(q / p);
+        }
+    }
+    if t_min > t_max {
+        return None;
+    }
+    Some((t_min, t_max))
+}
+
+/// Get the length of the part of the segment inside the rectangle from `bound_min` to `bound_max`.
+pub fn get_clipped_length(
+    line_start: Site2D,
+    line_end: Site2D,
+    bound_min: Site2D,
+    bound_max: Site2D,
+) -> f64 {
+    get_clipped_range(line_start, line_end, bound_min, bound_max).map_or(0.0, |(t_min, t_max)| {
+        (t_max - t_min) * get_distance(line_start, line_end)
+    })
+}
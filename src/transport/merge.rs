@@ -0,0 +1,38 @@
+use crate::transport::{geometry::EdgeGeometries, transport::TransportNetwork};
+
+impl TransportNetwork {
+    /// Merge networks into one network without connecting them.
+    /// The node indices of each network are shifted by the returned offsets.
+    pub(crate) fn merge(networks: &[TransportNetwork]) -> (TransportNetwork, Vec<usize>) {
+        let mut offsets = vec![];
+        let mut nodes = vec![];
+        let mut edges = vec![];
+        let mut geometries = EdgeGeometries::default();
+
+        networks.iter().for_each(|network| {
+            let offset = nodes.len();
+            offsets.push(offset);
+            nodes.extend(network.nodes.iter().copied());
+            edges.extend(
+                network
+                    .iter_edges()
+                    .map(|(start, end, attr)| (start + offset, end + offset, attr)),
+            );
+            network
+                .geometries
+                .iter()
+                .for_each(|(start, end, intermediate)| {
+                    geometries.insert(start + offset, end + offset, intermediate.clone());
+                });
+        });
+
+        let mut merged = TransportNetwork::from_edges(nodes, edges, geometries);
+        if let Some(first) = networks.first() {
+            merged.update_overlays(
+                first.highway_overlay.tolerance,
+                first.normal_overlay.tolerance,
+            );
+        }
+        (merged, offsets)
+    }
+}
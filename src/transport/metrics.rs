@@ -0,0 +1,103 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::{
+    dedup::UnionFind, math::get_distance, shortest::shortest_path_tree, transport::TransportNetwork,
+};
+
+/// The number of the origins from which the circuity is measured.
+const CIRCUITY_SAMPLES: usize = 32;
+
+/// Measures of a network for comparing parameter sets.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug)]
+pub struct NetworkMetrics {
+    pub total_length: f64,
+    /// The ratio of the length of the highways to the total length.
+    pub highway_ratio: f64,
+    /// The mean number of the roads at the nodes with three or more roads.
+    pub average_intersection_degree: f64,
+    /// The mean ratio of the distance along the roads to the straight distance between nodes.
+    pub circuity: f64,
+    /// The number of the connected components, not counting the nodes without any road.
+    pub num_components: usize,
+    pub num_dead_ends: usize,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Compute the metrics of the network.
+    /// The circuity is measured from nodes taken at equal intervals of the node indices to every node reachable from them.
+    pub fn metrics(&self) -> NetworkMetrics {
+        let num_nodes = self.nodes.len();
+        let (mut total_length, mut highway_length) = (0.0, 0.0);
+        let mut union_find = UnionFind::new(num_nodes);
+        self.iter_edges().for_each(|(start, end, attr)| {
+            let length = self.get_edge_length(start, end);
+            total_length += length;
+            if attr.path_attr.is_highway() {
+                highway_length += length;
+            }
+            union_find.union(start, end);
+        });
+
+        let degrees = (0..num_nodes)
+            .map(|index| self.graph.neighbors_of(index).len())
+            .collect::<Vec<_>>();
+        let intersection_degrees = degrees
+            .iter()
+            .filter(|degree| **degree >= 3)
+            .collect::<Vec<_>>();
+        let num_components = (0..num_nodes)
+            .filter(|index| degrees[*index] > 0 && union_find.find(*index) == *index)
+            .count();
+
+        let origins = (0..num_nodes)
+            .filter(|index| degrees[*index] > 0)
+            .collect::<Vec<_>>();
+        let stride = origins.len().div_ceil(CIRCUITY_SAMPLES).max(1);
+        let (mut circuity_sum, mut num_pairs) = (0.0, 0);
+        origins.iter().step_by(stride).for_each(|origin| {
+            let tree = shortest_path_tree(num_nodes, *origin, |index| {
+                self.graph
+                    .neighbors_of(index)
+                    .iter()
+                    .map(|n| (n.0, self.get_edge_length(index, n.0)))
+                    .collect::<Vec<_>>()
+            });
+            tree.distances
+                .iter()
+                .enumerate()
+                .filter(|(_, distance)| **distance < f64::MAX)
+                .for_each(|(index, distance)| {
+                    let straight = get_distance(self.nodes[*origin], self.nodes[index]);
+                    if straight > 0.0 {
+                        circuity_sum += distance / straight;
+                        num_pairs += 1;
+                    }
+                });
+        });
+
+        NetworkMetrics {
+            total_length,
+            highway_ratio: if total_length > 0.0 {
+                highway_length / total_length
+            } else {
+                0.0
+            },
+            average_intersection_degree: if intersection_degrees.is_empty() {
+                0.0
+            } else {
+                intersection_degrees.iter().copied().sum::<usize>() as f64
+                    / intersection_degrees.len() as f64
+            },
+            circuity: if num_pairs > 0 {
+                circuity_sum / num_pairs as f64
+            } else {
+                0.0
+            },
+            num_components,
+            num_dead_ends: degrees.iter().filter(|degree| **degree == 1).count(),
+        }
+    }
+}
@@ -1,3 +1,58 @@
+mod altitude;
+mod astar;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod block;
+pub mod buildings;
+mod centrality;
+mod chain;
+pub mod classify;
+pub mod colonization;
+pub mod connectivity;
+pub mod continent;
+pub mod corner;
+mod dedup;
+mod edit;
+pub mod error;
+mod geojson;
+pub mod geometry;
+pub mod growth;
+pub mod hash;
+mod history;
+mod length;
+pub mod log;
+pub mod lsystem;
 mod math;
+mod merge;
+pub mod metrics;
+pub mod oneway;
+pub mod overlay;
+mod parallel;
+pub mod parcel;
+mod planarity;
+pub mod poi;
+pub mod polyline3d;
+pub mod preset;
+mod profile;
+pub mod prune;
+pub mod rail;
+pub mod report;
+pub mod road;
+pub mod road_class;
+mod route;
+pub mod serialize;
+mod shortest;
+mod simplify;
+pub mod simulation;
+pub mod smooth;
+mod spatial;
+pub mod stats;
+pub mod strategy;
+pub mod style;
+pub mod tensor;
+pub mod tile;
+pub mod transfer;
+#[allow(clippy::module_inception)]
 pub mod transport;
 pub mod treeobj;
+pub mod zoning;
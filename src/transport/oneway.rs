@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        dedup::UnionFind,
+        math::site_order,
+        road_class::RoadClass,
+        transport::{EdgeAttr, Neighbor, TransportNetwork},
+    },
+    Site2D,
+};
+
+/// Two roads at a node continue the same street if they deviate from a straight line by less than this angle.
+const STREET_STRAIGHT_TOLERANCE: f64 = std::f64::consts::PI / 8.0;
+/// The streets are grouped into this number of directions, in each of which the one-way streets alternate.
+const STREET_DIRECTION_BINS: usize = 8;
+
+/// The direction in which an edge can be traveled.
+/// The directions are relative to the order of the coordinates of the ends, so they do not depend on the node indices.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub(crate) enum OneWay {
+    #[default]
+    TwoWay,
+    /// Only from the end with the smaller coordinates to the other.
+    Ascending,
+    /// Only from the end with the larger coordinates to the other.
+    Descending,
+}
+
+impl OneWay {
+    /// Check whether the edge with this direction can be traveled from `site_from` to `site_to`.
+    pub(crate) fn allows(self, site_from: &Site2D, site_to: &Site2D) -> bool {
+        match self {
+            OneWay::TwoWay => true,
+            OneWay::Ascending => site_order(site_from, site_to).is_lt(),
+            OneWay::Descending => site_order(site_from, site_to).is_gt(),
+        }
+    }
+
+    /// Get the direction which allows traveling only from `site_from` to `site_to`.
+    pub(crate) fn from_travel(site_from: &Site2D, site_to: &Site2D) -> Self {
+        if site_order(site_from, site_to).is_lt() {
+            OneWay::Ascending
+        } else {
+            OneWay::Descending
+        }
+    }
+}
+
+/// Make about `fraction` of the local streets one-way.
+///
+/// A street is a run of local roads continuing straight through the nodes.
+/// The streets are grouped by their direction, and in each group the one-way streets alternate
+/// their direction in the order of their offsets across the direction, like the streets of a grid.
+pub(crate) fn assign_one_way<R: Rng>(
+    sites: &[Site2D],
+    edges: &mut [(usize, usize, EdgeAttr)],
+    fraction: f64,
+    rng: &mut R,
+) {
+    let fraction = if fraction.is_nan() {
+        0.0
+    } else {
+        fraction.clamp(0.0, 1.0)
+    };
+    if fraction == 0.0 {
+        return;
+    }
+    let is_local = |attr: &EdgeAttr| attr.path_attr.road_class == RoadClass::Local;
+    let angle_of = |start: usize, end: usize| {
+        libm::atan2(sites[end].y - sites[start].y, sites[end].x - sites[start].x)
+    };
+
+    // join the local edges continuing straight at each node into streets
+    let mut incident = HashMap::<usize, Vec<(usize, f64)>>::new();
+    edges
+        .iter()
+        .enumerate()
+        .filter(|(_, (start, end, attr))| is_local(attr) && start != end)
+        .for_each(|(index, (start, end, _))| {
+            incident
+                .entry(*start)
+                .or_default()
+                .push((index, angle_of(*start, *end)));
+            incident
+                .entry(*end)
+                .or_default()
+                .push((index, angle_of(*end, *start)));
+        });
+    let mut streets = UnionFind::new(edges.len());
+    let mut nodes = incident.keys().copied().collect::<Vec<_>>();
+    nodes.sort_unstable();
+    nodes.iter().for_each(|node| {
+        let outgoing = &incident[node];
+        outgoing
+            .iter()
+            .enumerate()
+            .for_each(|(i, (edge_a, angle_a))| {
+                outgoing[i + 1..].iter().for_each(|(edge_b, angle_b)| {
+                    let turn = (angle_a - angle_b).rem_euclid(std::f64::consts::TAU);
+                    if (turn - std::f64::consts::PI).abs() < STREET_STRAIGHT_TOLERANCE {
+                        streets.union(*edge_a, *edge_b);
+                    }
+                });
+            });
+    });
+
+    let mut street_edges = HashMap::<usize, Vec<usize>>::new();
+    (0..edges.len())
+        .filter(|index| is_local(&edges[*index].2) && edges[*index].0 != edges[*index].1)
+        .for_each(|index| {
+            street_edges
+                .entry(streets.find(index))
+                .or_default()
+                .push(index)
+        });
+    let mut roots = street_edges.keys().copied().collect::<Vec<_>>();
+    roots.sort_unstable();
+
+    // the direction and the offset of each street, taken from the edge with the smallest index
+    let mut bins = vec![vec![]; STREET_DIRECTION_BINS];
+    roots.iter().for_each(|root| {
+        if !rng.gen_bool(fraction) {
+            return;
+        }
+        let (start, end, _) = edges[*root];
+        let angle = angle_of(start, end).rem_euclid(std::f64::consts::PI);
+        let bin = ((angle / std::f64::consts::PI * STREET_DIRECTION_BINS as f64).round() as usize)
+            % STREET_DIRECTION_BINS;
+        let bin_angle = bin as f64 / STREET_DIRECTION_BINS as f64 * std::f64::consts::PI;
+        let offset = -sites[start].x * libm::sin(bin_angle) + sites[start].y * libm::cos(bin_angle);
+        bins[bin].push((offset, *root, bin_angle));
+    });
+
+    bins.iter_mut().for_each(|bin| {
+        bin.sort_by(|a, b| a.0.total_cmp(&b.0));
+        bin.iter()
+            .enumerate()
+            .for_each(|(order, (_, root, bin_angle))| {
+                // the streets alternately run along and against the direction of the group
+                let sign = if order % 2 == 0 { 1.0 } else { -1.0 };
+                let (dx, dy) = (libm::cos(*bin_angle) * sign, libm::sin(*bin_angle) * sign);
+                street_edges[root].iter().for_each(|index| {
+                    let (start, end, attr) = &mut edges[*index];
+                    let (site_start, site_end) = (sites[*start], sites[*end]);
+                    let along = (site_end.x - site_start.x) * dx + (site_end.y - site_start.y) * dy;
+                    attr.one_way = if along >= 0.0 {
+                        OneWay::from_travel(&site_start, &site_end)
+                    } else {
+                        OneWay::from_travel(&site_end, &site_start)
+                    };
+                });
+            });
+    });
+}
+
+impl TransportNetwork {
+    fn directed_neighbors(&self, index: usize, is_outgoing: bool) -> Vec<Neighbor> {
+        self.graph
+            .neighbors_of(index)
+            .iter()
+            .filter(|n| {
+                let (site, other) = (&self.nodes[index], &self.nodes[n.0]);
+                if is_outgoing {
+                    n.1.one_way.allows(site, other)
+                } else {
+                    n.1.one_way.allows(other, site)
+                }
+            })
+            .map(|n| Neighbor {
+                index: n.0,
+                road_class: n.1.path_attr.road_class,
+                is_highway: n.1.path_attr.is_highway(),
+                is_bridge: n.1.path_attr.is_bridge,
+                cost: n.1.cost,
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Get the neighbors which can be reached directly from the node, following the one-way streets.
+    pub fn get_out_neighbors(&self, index: usize) -> Vec<Neighbor> {
+        self.directed_neighbors(index, true)
+    }
+
+    /// Get the neighbors from which the node can be reached directly, following the one-way streets.
+    pub fn get_in_neighbors(&self, index: usize) -> Vec<Neighbor> {
+        self.directed_neighbors(index, false)
+    }
+}
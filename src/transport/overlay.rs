@@ -0,0 +1,65 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{chain::extract_chains, math::simplify_polyline, transport::TransportNetwork},
+    Site2D,
+};
+
+/// Pre-simplified polylines of the roads in a single class.
+#[derive(Clone, Default)]
+pub(crate) struct Overlay {
+    pub(crate) tolerance: f64,
+    polylines: Vec<Vec<Site2D>>,
+}
+
+impl Overlay {
+    pub(crate) fn new(network: &TransportNetwork, is_highway: bool, tolerance: f64) -> Self {
+        let polylines = extract_chains(&network.graph, |attr| {
+            attr.path_attr.is_highway() == is_highway
+        })
+        .iter()
+        .map(|chain| {
+            let mut polyline = vec![network.nodes[chain[0]]];
+            chain.windows(2).for_each(|pair| {
+                polyline.extend(network.get_polyline(pair[0], pair[1]).into_iter().skip(1));
+            });
+            simplify_polyline(&polyline, tolerance)
+        })
+        .collect::<Vec<_>>();
+        Self {
+            tolerance,
+            polylines,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    pub fn num_overlay_polylines(&self, is_highway: bool) -> usize {
+        self.get_overlay(is_highway).polylines.len()
+    }
+
+    /// Get a simplified polyline of the overlay as flattened `[x0, y0, x1, y1, ...]`.
+    pub fn get_overlay_polyline(&self, is_highway: bool, index: usize) -> Vec<f64> {
+        self.get_overlay(is_highway).polylines[index]
+            .iter()
+            .flat_map(|site| [site.x, site.y])
+            .collect::<Vec<_>>()
+    }
+}
+
+impl TransportNetwork {
+    pub(crate) fn update_overlays(&mut self, highway_tolerance: f64, normal_tolerance: f64) {
+        self.highway_overlay = Overlay::new(self, true, highway_tolerance);
+        self.normal_overlay = Overlay::new(self, false, normal_tolerance);
+    }
+
+    fn get_overlay(&self, is_highway: bool) -> &Overlay {
+        if is_highway {
+            &self.highway_overlay
+        } else {
+            &self.normal_overlay
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+
+use rstar::{primitives::GeomWithData, RTree};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    config::derive_seed,
+    terrain::Terrain,
+    transport::{
+        error::TransportError,
+        log::GenerationLog,
+        math::get_distance,
+        tile::{stitch_attr, stitchable_nodes},
+        transport::{EdgeAttr, TransportNetwork, TransportNetworkBuilder},
+    },
+    Site2D,
+};
+
+/// Get the index of the origin nearest to `site`, the first one of those at the same distance.
+fn nearest_origin_index(origins: &[Site2D], site: Site2D) -> usize {
+    origins
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| get_distance(site, **a).total_cmp(&get_distance(site, **b)))
+        .map_or(0, |(index, _)| index)
+}
+
+impl TransportNetworkBuilder {
+    /// Grow the network of the origin at `index` within its Voronoi cell,
+    /// with the iterations and the targets divided evenly between the origins.
+    /// Only the nodes of the existing network in the cell and the edges between them are laid.
+    fn build_origin(
+        &self,
+        origins: &[Site2D],
+        index: usize,
+        seed: u64,
+        terrain: &Terrain,
+    ) -> Result<TransportNetwork, TransportError> {
+        let num_origins = origins.len();
+        let mut builder = self
+            .clone()
+            .set_start(origins[index].x, origins[index].y)
+            .set_iterations(self.iterations.div_ceil(num_origins));
+        builder.additional_starts.clear();
+        builder.other_origins = origins
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .map(|(_, origin)| *origin)
+            .collect::<Vec<_>>();
+        builder.highway_iterations = self
+            .highway_iterations
+            .map(|highway_iterations| highway_iterations.div_ceil(num_origins));
+        builder.max_total_length = self.max_total_length / num_origins as f64;
+        builder.coverage_target = self.coverage_target.map(|(bound_min, bound_max, density)| {
+            (bound_min, bound_max, density / num_origins as f64)
+        });
+
+        let mut local_indices = vec![None; self.existing_sites.len()];
+        builder.existing_sites.clear();
+        self.existing_sites
+            .iter()
+            .enumerate()
+            .filter(|(_, site)| nearest_origin_index(origins, **site) == index)
+            .for_each(|(existing, site)| {
+                local_indices[existing] = Some(builder.existing_sites.len());
+                builder.existing_sites.push(*site);
+            });
+        builder.existing_edges = self
+            .existing_edges
+            .iter()
+            .filter_map(|(start, end, path_attr, cost)| {
+                Some((
+                    local_indices[*start]?,
+                    local_indices[*end]?,
+                    *path_attr,
+                    *cost,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        builder.build_u64(derive_seed(seed, index as u64), terrain)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetworkBuilder {
+    /// Grow the network from each origin separately and join them, instead of growing them together as `build`.
+    /// With the `parallel` feature the origins grow on the rayon thread pool, which is also available on wasm
+    /// after `init_thread_pool`.
+    ///
+    /// Each origin grows within the area nearer to it than to the other origins, with the iterations,
+    /// the total length and the coverage divided evenly, and a random stream derived from `seed` and its order,
+    /// so the result does not depend on the scheduling.
+    /// The existing network of `set_existing_network` is divided between the areas by its nodes:
+    /// each origin lays the edges within its area, and the edges between the areas are laid once after the growth.
+    /// The networks are then joined as `stitch` joins tiles: the nodes near the borders of the areas within `stitch_distance`
+    /// of a node of another network are connected, closest pairs first.
+    /// The node indices of each network follow those of the networks of the preceding origins.
+    /// The generation logs and the build reports of the origins are merged into those of the network.
+    pub fn build_parallel(
+        self,
+        seed: u32,
+        terrain: &Terrain,
+        stitch_distance: f64,
+    ) -> Result<TransportNetwork, TransportError> {
+        self.validate(terrain)?;
+        let origins = std::iter::once(self.start)
+            .chain(self.additional_starts.iter().copied())
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "parallel")]
+        let networks = {
+            use rayon::prelude::*;
+            (0..origins.len())
+                .into_par_iter()
+                .map(|index| self.build_origin(&origins, index, seed as u64, terrain))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        #[cfg(not(feature = "parallel"))]
+        let networks = (0..origins.len())
+            .map(|index| self.build_origin(&origins, index, seed as u64, terrain))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (merged, offsets) = TransportNetwork::merge(&networks);
+        let mut edges = merged.iter_edges().collect::<Vec<_>>();
+        let TransportNetwork {
+            nodes, geometries, ..
+        } = merged;
+
+        // the existing edges between the areas are laid once, between the nodes laid by the origin of each area
+        let existing_node = |site: Site2D| {
+            let origin = nearest_origin_index(&origins, site);
+            networks[origin]
+                .nearest_node(site.x, site.y)
+                .map(|node| node + offsets[origin])
+        };
+        let mut bordering_nodes = HashSet::new();
+        self.existing_edges
+            .iter()
+            .for_each(|(start, end, path_attr, cost)| {
+                let (site_start, site_end) =
+                    (self.existing_sites[*start], self.existing_sites[*end]);
+                if nearest_origin_index(&origins, site_start)
+                    == nearest_origin_index(&origins, site_end)
+                {
+                    return;
+                }
+                if let (Some(node_start), Some(node_end)) =
+                    (existing_node(site_start), existing_node(site_end))
+                {
+                    bordering_nodes.insert(node_start);
+                    bordering_nodes.insert(node_end);
+                    edges.push((
+                        node_start,
+                        node_end,
+                        EdgeAttr {
+                            path_attr: *path_attr,
+                            cost: *cost,
+                            ..Default::default()
+                        },
+                    ));
+                }
+            });
+
+        // the nodes which may be stitched are those within the distance from the border of their area
+        let is_near_border = |origin: usize, site: Site2D| {
+            let distance = get_distance(site, origins[origin]);
+            origins.iter().enumerate().any(|(other, other_origin)| {
+                let spacing = get_distance(origins[origin], *other_origin);
+                other != origin
+                    && spacing > 0.0
+                    && (get_distance(site, *other_origin).powi(2) - distance.powi(2))
+                        / (2.0 * spacing)
+                        < stitch_distance
+            })
+        };
+        let offsets = &offsets;
+        let candidates = networks
+            .iter()
+            .enumerate()
+            .flat_map(|(origin, network)| {
+                stitchable_nodes(network)
+                    .into_iter()
+                    .filter(move |(index, _, _)| is_near_border(origin, network.nodes[*index]))
+                    .map(move |(index, class, cost)| (index + offsets[origin], origin, class, cost))
+            })
+            // the nodes already joined by the existing network are not stitched again
+            .filter(|(index, _, _, _)| !bordering_nodes.contains(index))
+            .collect::<Vec<_>>();
+        let tree = RTree::bulk_load(
+            candidates
+                .iter()
+                .enumerate()
+                .map(|(i, (index, _, _, _))| {
+                    GeomWithData::new([nodes[*index].x, nodes[*index].y], i)
+                })
+                .collect::<Vec<_>>(),
+        );
+        let mut pairs = vec![];
+        candidates
+            .iter()
+            .enumerate()
+            .for_each(|(i, (index, origin, _, _))| {
+                let site = nodes[*index];
+                tree.locate_within_distance([site.x, site.y], stitch_distance.powi(2))
+                    .filter(|other| other.data > i && candidates[other.data].1 != *origin)
+                    .for_each(|other| {
+                        let length = get_distance(site, nodes[candidates[other.data].0]);
+                        pairs.push((length, i, other.data));
+                    });
+            });
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+
+        let mut used = vec![false; candidates.len()];
+        pairs.iter().for_each(|(_, a, b)| {
+            if used[*a] || used[*b] {
+                return;
+            }
+            used[*a] = true;
+            used[*b] = true;
+            let (index_a, _, class_a, cost_a) = candidates[*a];
+            let (index_b, _, class_b, cost_b) = candidates[*b];
+            edges.push((
+                index_a,
+                index_b,
+                stitch_attr((class_a, cost_a), (class_b, cost_b)),
+            ));
+        });
+
+        let mut network = TransportNetwork::from_edges(nodes, edges, geometries);
+        network.update_overlays(
+            self.highway_overlay_tolerance,
+            self.normal_overlay_tolerance,
+        );
+        network.log = GenerationLog::merge(networks.iter().map(|network| &network.log));
+        networks
+            .iter()
+            .for_each(|origin_network| network.report.merge(&origin_network.report));
+        Ok(network)
+    }
+}
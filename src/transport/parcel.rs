@@ -0,0 +1,200 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        block::Blocks,
+        math::{get_cross, get_distance, is_inside_polygon},
+        transport::TransportNetwork,
+    },
+    Site2D,
+};
+
+/// The lots subdividing the blocks, each facing a road.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Parcels {
+    pub(crate) polygons: Vec<[Site2D; 4]>,
+    pub(crate) edges: Vec<(usize, usize)>,
+    pub(crate) blocks: Vec<usize>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Parcels {
+    pub fn num_parcels(&self) -> usize {
+        self.polygons.len()
+    }
+
+    /// Get the polygon of the lot as flattened `[x0, y0, x1, y1, ...]`.
+    /// The polygon is counterclockwise and starts with the two corners on the road.
+    pub fn get_parcel_polygon(&self, index: usize) -> Vec<f64> {
+        self.polygons[index]
+            .iter()
+            .flat_map(|site| [site.x, site.y])
+            .collect::<Vec<_>>()
+    }
+
+    /// Get the nodes `[start, end]` of the edge which the lot faces.
+    pub fn get_parcel_edge(&self, index: usize) -> Vec<usize> {
+        let (start, end) = self.edges[index];
+        vec![start, end]
+    }
+
+    /// Get the index of the block containing the lot.
+    pub fn get_parcel_block(&self, index: usize) -> usize {
+        self.blocks[index]
+    }
+}
+
+/// Shrink the quadrilateral slightly toward its center, so that the neighbors sharing its sides do not overlap it.
+fn shrink(quad: &[Site2D; 4]) -> [Site2D; 4] {
+    let center = Site2D {
+        x: quad.iter().map(|site| site.x).sum::<f64>() / 4.0,
+        y: quad.iter().map(|site| site.y).sum::<f64>() / 4.0,
+    };
+    quad.map(|site| Site2D {
+        x: center.x + (site.x - center.x) * (1.0 - 1e-6),
+        y: center.y + (site.y - center.y) * (1.0 - 1e-6),
+    })
+}
+
+fn crosses_polygon(quad: &[Site2D; 4], polygon: &[Site2D]) -> bool {
+    (0..4).any(|i| {
+        let (a, b) = (quad[i], quad[(i + 1) % 4]);
+        (0..polygon.len()).any(|j| {
+            let (c, d) = (polygon[j], polygon[(j + 1) % polygon.len()]);
+            get_cross(a, b, c, d).is_some_and(|(_, passing)| passing)
+        })
+    })
+}
+
+/// Subdivides the blocks into lots along their roads.
+///
+/// The lots are cut at equal widths of about the frontage along each road around a block,
+/// and reach inward by the depth, or by half of it if the full depth does not fit.
+/// The lots which overlap the others or leave the block, mainly at the corners, are dropped.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct ParcelBuilder {
+    frontage: f64,
+    depth: f64,
+}
+
+impl Default for ParcelBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ParcelBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            frontage: 0.1,
+            depth: 0.15,
+        }
+    }
+
+    /// Set the width of the lots along the road.
+    pub fn set_frontage(self, frontage: f64) -> Self {
+        Self { frontage, ..self }
+    }
+
+    /// Set the distance by which the lots reach from the road into the block.
+    pub fn set_depth(self, depth: f64) -> Self {
+        Self { depth, ..self }
+    }
+
+    pub fn build(&self, network: &TransportNetwork, blocks: &Blocks) -> Parcels {
+        let mut parcels = Parcels {
+            polygons: vec![],
+            edges: vec![],
+            blocks: vec![],
+        };
+        if self.frontage <= 0.0 || self.depth <= 0.0 {
+            return parcels;
+        }
+
+        blocks
+            .node_cycles
+            .iter()
+            .zip(blocks.polygons.iter())
+            .enumerate()
+            .for_each(|(block_index, (cycle, polygon))| {
+                let mut lots: Vec<[Site2D; 4]> = vec![];
+                (0..cycle.len()).for_each(|i| {
+                    let (start, end) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+                    let polyline = network.get_polyline(start, end);
+                    let length = network.get_edge_length(start, end);
+                    let num_lots = (length / self.frontage).floor() as usize;
+                    if num_lots == 0 {
+                        return;
+                    }
+                    let fronts = self.divide(&polyline, length / num_lots as f64, num_lots);
+                    fronts.windows(2).for_each(|front| {
+                        let (a, b) = (front[0], front[1]);
+                        let width = get_distance(a, b);
+                        if width == 0.0 {
+                            return;
+                        }
+                        // the block is on the left of the road, as it is counterclockwise
+                        let normal = ((a.y - b.y) / width, (b.x - a.x) / width);
+                        let fitting = [self.depth, self.depth * 0.5].iter().find_map(|depth| {
+                            let lot = [
+                                a,
+                                b,
+                                Site2D {
+                                    x: b.x + normal.0 * depth,
+                                    y: b.y + normal.1 * depth,
+                                },
+                                Site2D {
+                                    x: a.x + normal.0 * depth,
+                                    y: a.y + normal.1 * depth,
+                                },
+                            ];
+                            let shrunk = shrink(&lot);
+                            let is_inside =
+                                shrunk.iter().all(|site| is_inside_polygon(*site, polygon))
+                                    && !crosses_polygon(&shrunk, polygon);
+                            let overlaps = lots.iter().any(|other| {
+                                shrunk.iter().any(|site| is_inside_polygon(*site, other))
+                                    || other.iter().any(|site| is_inside_polygon(*site, &shrunk))
+                                    || crosses_polygon(&shrunk, other)
+                            });
+                            (is_inside && !overlaps).then_some(lot)
+                        });
+                        if let Some(lot) = fitting {
+                            lots.push(lot);
+                            parcels.polygons.push(lot);
+                            parcels.edges.push((start, end));
+                            parcels.blocks.push(block_index);
+                        }
+                    });
+                });
+            });
+        parcels
+    }
+}
+
+impl ParcelBuilder {
+    /// Get the `num_lots + 1` sites dividing the polyline at every `interval` of the distance along it.
+    fn divide(&self, polyline: &[Site2D], interval: f64, num_lots: usize) -> Vec<Site2D> {
+        let mut sites = vec![polyline[0]];
+        let mut next_distance = interval;
+        let mut distance = 0.0;
+        polyline.windows(2).for_each(|segment| {
+            let segment_length = get_distance(segment[0], segment[1]);
+            while sites.len() < num_lots && next_distance < distance + segment_length {
+                let t = (next_distance - distance) / segment_length;
+                sites.push(Site2D {
+                    x: segment[0].x + (segment[1].x - segment[0].x) * t,
+                    y: segment[0].y + (segment[1].y - segment[0].y) * t,
+                });
+                next_distance += interval;
+            }
+            distance += segment_length;
+        });
+        sites.push(polyline[polyline.len() - 1]);
+        sites
+    }
+}
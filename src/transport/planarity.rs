@@ -0,0 +1,207 @@
+use std::collections::{BTreeSet, HashMap};
+
+use rstar::{
+    primitives::{GeomWithData, Rectangle},
+    RTree, RTreeObject,
+};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        math::get_distance,
+        transport::{EdgeAttr, TransportNetwork},
+    },
+    Site2D,
+};
+
+/// A straight piece of an edge, with the nodes at its ends if they are not intermediate vertices.
+struct Segment {
+    edge: (usize, usize),
+    start: (Site2D, Option<usize>),
+    end: (Site2D, Option<usize>),
+}
+
+/// A point where a segment meets another edge without a shared node.
+struct Contact {
+    edge_a: (usize, usize),
+    edge_b: (usize, usize),
+    site: Site2D,
+    /// The node at the point if it is an end of one of the edges, which then splits only the other edge.
+    node: Option<usize>,
+}
+
+fn orientation(a: Site2D, b: Site2D, c: Site2D) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Check whether `site` lies on the segment strictly between its ends.
+fn is_inside_segment(site: Site2D, start: Site2D, end: Site2D) -> bool {
+    orientation(start, end, site) == 0.0
+        && (site.x, site.y) != (start.x, start.y)
+        && (site.x, site.y) != (end.x, end.y)
+        && (site.x - start.x) * (site.x - end.x) <= 0.0
+        && (site.y - start.y) * (site.y - end.y) <= 0.0
+}
+
+impl TransportNetwork {
+    fn segments(&self) -> Vec<Segment> {
+        self.iter_edges()
+            .flat_map(|(start, end, _)| {
+                let polyline = self.get_polyline(start, end);
+                let last = polyline.len() - 1;
+                (0..last)
+                    .map(|i| Segment {
+                        edge: (start, end),
+                        start: (polyline[i], (i == 0).then_some(start)),
+                        end: (polyline[i + 1], (i + 1 == last).then_some(end)),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    }
+
+    /// Find the points where the roads cross or touch each other without a shared node.
+    fn find_contacts(&self) -> Vec<Contact> {
+        let segments = self.segments();
+        let tree = RTree::bulk_load(
+            segments
+                .iter()
+                .enumerate()
+                .map(|(index, segment)| {
+                    GeomWithData::new(
+                        Rectangle::from_corners(
+                            [segment.start.0.x, segment.start.0.y],
+                            [segment.end.0.x, segment.end.0.y],
+                        ),
+                        index,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut contacts = vec![];
+        segments.iter().enumerate().for_each(|(index_a, a)| {
+            let envelope =
+                Rectangle::from_corners([a.start.0.x, a.start.0.y], [a.end.0.x, a.end.0.y])
+                    .envelope();
+            tree.locate_in_envelope_intersecting(&envelope)
+                .filter(|object| object.data > index_a && segments[object.data].edge != a.edge)
+                .for_each(|object| {
+                    let b = &segments[object.data];
+                    let orientations = [
+                        orientation(a.start.0, a.end.0, b.start.0),
+                        orientation(a.start.0, a.end.0, b.end.0),
+                        orientation(b.start.0, b.end.0, a.start.0),
+                        orientation(b.start.0, b.end.0, a.end.0),
+                    ];
+                    if orientations[0] * orientations[1] < 0.0
+                        && orientations[2] * orientations[3] < 0.0
+                    {
+                        let t = orientations[2] / (orientations[2] - orientations[3]);
+                        contacts.push(Contact {
+                            edge_a: a.edge,
+                            edge_b: b.edge,
+                            site: Site2D {
+                                x: a.start.0.x + (a.end.0.x - a.start.0.x) * t,
+                                y: a.start.0.y + (a.end.0.y - a.start.0.y) * t,
+                            },
+                            node: None,
+                        });
+                        return;
+                    }
+                    // an end lying on the other segment, which also covers the collinear overlaps
+                    [(a, b), (b, a)].iter().for_each(|(on, at)| {
+                        [at.start, at.end]
+                            .iter()
+                            .filter(|(site, _)| is_inside_segment(*site, on.start.0, on.end.0))
+                            .for_each(|(site, node)| {
+                                contacts.push(Contact {
+                                    edge_a: on.edge,
+                                    edge_b: at.edge,
+                                    site: *site,
+                                    node: *node,
+                                });
+                            });
+                    });
+                });
+        });
+        contacts
+    }
+
+    /// Split the edges at every point where they cross or touch another edge without a shared node.
+    /// The crossings are given new nodes after the existing ones, so the existing node indices are kept.
+    /// The edges are expected to be straight, and the cost is shared in proportion to the lengths of the pieces.
+    pub(crate) fn planarize(&self) -> TransportNetwork {
+        let mut nodes = self.nodes.clone();
+        let mut splits = HashMap::<(usize, usize), Vec<usize>>::new();
+        self.find_contacts()
+            .iter()
+            .for_each(|contact| match contact.node {
+                Some(node) => splits.entry(contact.edge_a).or_default().push(node),
+                None => {
+                    let node = nodes.len();
+                    nodes.push(contact.site);
+                    splits.entry(contact.edge_a).or_default().push(node);
+                    splits.entry(contact.edge_b).or_default().push(node);
+                }
+            });
+
+        let edges = self
+            .iter_edges()
+            .flat_map(|(start, end, attr)| {
+                let mut chain = splits.remove(&(start, end)).unwrap_or_default();
+                chain.sort_by(|a, b| {
+                    get_distance(nodes[start], nodes[*a])
+                        .total_cmp(&get_distance(nodes[start], nodes[*b]))
+                });
+                chain.dedup();
+                chain.insert(0, start);
+                chain.push(end);
+                let length = get_distance(nodes[start], nodes[end]);
+                chain
+                    .windows(2)
+                    .map(|pair| {
+                        let ratio = if length > 0.0 {
+                            get_distance(nodes[pair[0]], nodes[pair[1]]) / length
+                        } else {
+                            1.0
+                        };
+                        (
+                            pair[0],
+                            pair[1],
+                            EdgeAttr {
+                                cost: attr.cost * ratio,
+                                ..attr
+                            },
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut network = TransportNetwork::from_edges(nodes, edges, self.geometries.clone());
+        network.log = self.log.clone();
+        network.report = self.report.clone();
+        network
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Find the pairs of roads which cross or touch each other without a shared node.
+    /// Returns flattened `[start_a, end_a, start_b, end_b, ...]`, four node indices for each pair.
+    /// The network is planar if the result is empty.
+    pub fn validate_planarity(&self) -> Vec<usize> {
+        self.find_contacts()
+            .iter()
+            .map(|contact| {
+                let (a, b) = (contact.edge_a, contact.edge_b);
+                (a.min(b), a.max(b))
+            })
+            .collect::<BTreeSet<_>>()
+            .iter()
+            .flat_map(|(a, b)| [a.0, a.1, b.0, b.1])
+            .collect::<Vec<_>>()
+    }
+}
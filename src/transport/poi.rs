@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    density::DensityMap,
+    transport::{
+        chain::extract_chains,
+        math::get_distance,
+        transport::{PathAttr, TransportNetwork},
+    },
+    Site2D,
+};
+
+/// A point of interest on the network.
+pub(crate) struct Poi {
+    pub(crate) site: Site2D,
+    /// The nodes of the edge which the point lies on, which are the same node for a point at a node.
+    pub(crate) edge: (usize, usize),
+}
+
+/// Points of interest placed on the network, such as stations, bus stops or shops.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Pois {
+    pub(crate) pois: Vec<Poi>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Pois {
+    pub fn num_pois(&self) -> usize {
+        self.pois.len()
+    }
+
+    pub fn get_poi_site(&self, index: usize) -> Site2D {
+        self.pois[index].site
+    }
+
+    /// Get the nodes `[start, end]` of the edge on which the point lies.
+    /// Both are the same node if the point is at a node.
+    pub fn get_poi_edge(&self, index: usize) -> Vec<usize> {
+        let (start, end) = self.pois[index].edge;
+        vec![start, end]
+    }
+
+    /// Get the sites of all the points as flattened `[x0, y0, x1, y1, ...]`.
+    pub fn get_poi_sites(&self) -> Vec<f64> {
+        self.pois
+            .iter()
+            .flat_map(|poi| [poi.site.x, poi.site.y])
+            .collect::<Vec<_>>()
+    }
+}
+
+/// Place the points at the ends of the chains of the edges accepted by `filter`,
+/// and along the chains between them at about `spacing`. Zero spacing places the points only at the ends.
+pub(crate) fn place_along_chains<F>(network: &TransportNetwork, filter: F, spacing: f64) -> Vec<Poi>
+where
+    F: Fn(&PathAttr) -> bool,
+{
+    let mut pois = vec![];
+    let mut poi_nodes = HashSet::new();
+    extract_chains(&network.graph, |attr| filter(&attr.path_attr))
+        .iter()
+        .for_each(|chain| {
+            [chain[0], chain[chain.len() - 1]].iter().for_each(|node| {
+                if poi_nodes.insert(*node) {
+                    pois.push(Poi {
+                        site: network.nodes[*node],
+                        edge: (*node, *node),
+                    });
+                }
+            });
+            if spacing <= 0.0 {
+                return;
+            }
+
+            // the points divide the chain evenly at about the spacing
+            let length = chain
+                .windows(2)
+                .map(|pair| network.get_edge_length(pair[0], pair[1]))
+                .sum::<f64>();
+            let num_intervals = (length / spacing).round().max(1.0) as usize;
+            let interval = length / num_intervals as f64;
+            let mut next_distance = interval;
+            let mut distance = 0.0;
+            chain.windows(2).for_each(|pair| {
+                network
+                    .get_polyline(pair[0], pair[1])
+                    .windows(2)
+                    .for_each(|segment| {
+                        let segment_length = get_distance(segment[0], segment[1]);
+                        while next_distance < distance + segment_length
+                            && next_distance < length - interval * 0.5
+                        {
+                            let t = (next_distance - distance) / segment_length;
+                            pois.push(Poi {
+                                site: Site2D {
+                                    x: segment[0].x + (segment[1].x - segment[0].x) * t,
+                                    y: segment[0].y + (segment[1].y - segment[0].y) * t,
+                                },
+                                edge: (pair[0], pair[1]),
+                            });
+                            next_distance += interval;
+                        }
+                        distance += segment_length;
+                    });
+            });
+        });
+    pois
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Place points at the ends and the junctions of the highways, and along the highways between them at about `spacing`.
+    /// Zero spacing places the points only at the ends and the junctions.
+    pub fn place_pois_along_highways(&self, spacing: f64) -> Pois {
+        Pois {
+            pois: place_along_chains(self, |attr| attr.is_highway(), spacing),
+        }
+    }
+
+    /// Place points at the `count` intersections with the highest centrality,
+    /// which is the sum of the betweenness of the edges at the intersection.
+    /// See `edge_betweenness` for `sample_size`.
+    pub fn place_pois_at_central_intersections(&self, count: usize, sample_size: usize) -> Pois {
+        let mut centrality = vec![0.0; self.nodes.len()];
+        self.edges()
+            .zip(self.edge_betweenness(sample_size))
+            .for_each(|(edge, betweenness)| {
+                centrality[edge.start_index] += betweenness;
+                centrality[edge.end_index] += betweenness;
+            });
+        let mut intersections = (0..self.nodes.len())
+            .filter(|index| self.graph.neighbors_of(*index).len() >= 3)
+            .collect::<Vec<_>>();
+        intersections.sort_by(|a, b| centrality[*b].total_cmp(&centrality[*a]));
+        Pois {
+            pois: intersections
+                .into_iter()
+                .take(count)
+                .map(|index| Poi {
+                    site: self.nodes[index],
+                    edge: (index, index),
+                })
+                .collect::<Vec<_>>(),
+        }
+    }
+
+    /// Place points at up to `count` nodes where the density peaks,
+    /// taking the densest nodes first and skipping those within `min_distance` of the points already placed.
+    /// The nodes without roads or without density are not taken.
+    pub fn place_pois_at_density_peaks(
+        &self,
+        density_map: &DensityMap,
+        count: usize,
+        min_distance: f64,
+    ) -> Pois {
+        let mut candidates = (0..self.nodes.len())
+            .filter(|index| !self.graph.neighbors_of(*index).is_empty())
+            .map(|index| {
+                let site = self.nodes[index];
+                (index, density_map.sample(site.x, site.y))
+            })
+            .filter(|(_, density)| *density > 0.0)
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let mut pois: Vec<Poi> = vec![];
+        for (index, _) in candidates {
+            if pois.len() >= count {
+                break;
+            }
+            let site = self.nodes[index];
+            if pois
+                .iter()
+                .all(|poi| get_distance(poi.site, site) >= min_distance)
+            {
+                pois.push(Poi {
+                    site,
+                    edge: (index, index),
+                });
+            }
+        }
+        Pois { pois }
+    }
+}
@@ -0,0 +1,138 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    terrain::Terrain,
+    transport::{chain::extract_chains, math::get_distance, transport::TransportNetwork},
+    Site2D,
+};
+
+/// Polylines with altitudes, packed for uploading to GPU buffers.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Polylines3D {
+    vertices: Vec<f32>,
+    offsets: Vec<u32>,
+    is_highway: Vec<bool>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Polylines3D {
+    pub fn num_polylines(&self) -> usize {
+        self.is_highway.len()
+    }
+
+    /// Get the vertices as `[x0, y0, z0, x1, y1, z1, ...]` where `z` is the altitude.
+    pub fn get_vertices(&self) -> Vec<f32> {
+        self.vertices.clone()
+    }
+
+    /// Get the index of the first vertex of each polyline, followed by the total number of vertices.
+    pub fn get_offsets(&self) -> Vec<u32> {
+        self.offsets.clone()
+    }
+
+    pub fn is_highway(&self, index: usize) -> bool {
+        self.is_highway[index]
+    }
+}
+
+/// Resample the polyline so that no segment is longer than `sample_spacing`.
+/// Each vertex is paired with its distance from the start.
+fn resample(polyline: &[Site2D], sample_spacing: f64) -> Vec<(Site2D, f64)> {
+    let mut samples = vec![(polyline[0], 0.0)];
+    let mut distance = 0.0;
+    polyline.windows(2).for_each(|pair| {
+        let length = get_distance(pair[0], pair[1]);
+        let num_samples = ((length / sample_spacing).ceil() as usize).max(1);
+        (1..=num_samples).for_each(|i| {
+            let t = i as f64 / num_samples as f64;
+            samples.push((
+                Site2D {
+                    x: pair[0].x + (pair[1].x - pair[0].x) * t,
+                    y: pair[0].y + (pair[1].y - pair[0].y) * t,
+                },
+                distance + length * t,
+            ));
+        });
+        distance += length;
+    });
+    samples
+}
+
+impl TransportNetwork {
+    /// Sample the edge from `start` to `end` at most every `sample_spacing`, or only at its vertices if the spacing is not positive.
+    /// Each sample is the site, the distance from `start` and the altitude above the sea level.
+    /// Bridges are laid straight between the altitudes of both ends, but never under the terrain.
+    pub(crate) fn sample_edge_3d(
+        &self,
+        start: usize,
+        end: usize,
+        terrain: &Terrain,
+        sample_spacing: f64,
+    ) -> Vec<(Site2D, f64, f64)> {
+        let sample_spacing = if sample_spacing > 0.0 {
+            sample_spacing
+        } else {
+            f64::MAX
+        };
+        let get_altitude =
+            |site: &Site2D| terrain.get_altitude(site.x, site.y).unwrap_or(0.0).max(0.0);
+        let samples = resample(&self.get_polyline(start, end), sample_spacing);
+        let total_length = samples.last().map(|sample| sample.1).unwrap_or(0.0);
+        let is_bridge = self.graph.has_edge(start, end).1.path_attr.is_bridge;
+        let altitude_start = get_altitude(&self.nodes[start]);
+        let altitude_end = get_altitude(&self.nodes[end]);
+        samples
+            .into_iter()
+            .map(|(site, distance)| {
+                let mut altitude = get_altitude(&site);
+                if is_bridge && total_length > 0.0 {
+                    let t = distance / total_length;
+                    altitude = altitude.max(altitude_start + (altitude_end - altitude_start) * t);
+                }
+                (site, distance, altitude)
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Export the chains of each road class as polylines with altitudes sampled from the terrain
+    /// at most every `sample_spacing`.
+    /// Bridges are laid straight between the altitudes of both ends, but never under the terrain.
+    pub fn polylines_3d(&self, terrain: &Terrain, sample_spacing: f64) -> Polylines3D {
+        let mut polylines = Polylines3D {
+            vertices: vec![],
+            offsets: vec![],
+            is_highway: vec![],
+        };
+        [true, false].iter().for_each(|&is_highway| {
+            extract_chains(&self.graph, |attr| {
+                attr.path_attr.is_highway() == is_highway
+            })
+            .iter()
+            .for_each(|chain| {
+                polylines
+                    .offsets
+                    .push((polylines.vertices.len() / 3) as u32);
+                polylines.is_highway.push(is_highway);
+
+                chain.windows(2).enumerate().for_each(|(i, pair)| {
+                    let samples = self.sample_edge_3d(pair[0], pair[1], terrain, sample_spacing);
+                    // the first vertex is shared with the previous edge
+                    let skip = if i == 0 { 0 } else { 1 };
+                    samples.iter().skip(skip).for_each(|(site, _, altitude)| {
+                        polylines
+                            .vertices
+                            .extend([site.x as f32, site.y as f32, *altitude as f32]);
+                    });
+                });
+            });
+        });
+        polylines
+            .offsets
+            .push((polylines.vertices.len() / 3) as u32);
+        polylines
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::transport::{BranchPattern, TransportNetworkBuilder};
+
+/// The archetypes of cities, which configure the growth of the roads.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum CityPreset {
+    /// Long straight blocks aligned to the highways, with many one-way streets.
+    #[default]
+    AmericanGrid,
+    /// Short winding streets which close many loops.
+    EuropeanOrganic,
+    /// Dense narrow streets branching off a few winding highways.
+    JapaneseSuburb,
+    /// Roads following the contours, which climb steep slopes by switchbacks.
+    MountainTown,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetworkBuilder {
+    /// Set the parameters of the growth of the roads to those of `preset`.
+    /// The start, the iterations, the bounds and the maps are kept, and the parameters can be changed by the setters afterward.
+    pub fn set_city_preset(self, preset: CityPreset) -> Self {
+        let builder = self
+            .set_highway_construction_priority(30.0)
+            .set_even_path_length_weight(1.5)
+            .set_highway_path_length_weight(1.5)
+            .set_branch_pattern(BranchPattern::Organic)
+            .set_one_way_fraction(0.0)
+            .set_look_ahead(0.8)
+            .set_max_grade(f64::INFINITY);
+        match preset {
+            CityPreset::AmericanGrid => builder
+                .set_branch_length(0.6)
+                .set_branch_angle_deviation(std::f64::consts::PI / 60.0)
+                .set_branch_max_angle(std::f64::consts::PI / 60.0)
+                .set_normal_rotation_probability(0.9)
+                .set_highway_rotation_probability(0.01)
+                .set_branch_pattern(BranchPattern::Grid)
+                .set_one_way_fraction(0.3),
+            CityPreset::EuropeanOrganic => builder
+                .set_branch_length(0.35)
+                .set_branch_angle_deviation(std::f64::consts::PI / 10.0)
+                .set_branch_max_angle(std::f64::consts::PI / 8.0)
+                .set_normal_rotation_probability(0.6)
+                .set_highway_rotation_probability(0.05)
+                .set_highway_construction_priority(20.0)
+                .set_even_path_length_weight(1.2)
+                .set_look_ahead(1.2),
+            CityPreset::JapaneseSuburb => builder
+                .set_branch_length(0.3)
+                .set_branch_angle_deviation(std::f64::consts::PI / 30.0)
+                .set_branch_max_angle(std::f64::consts::PI / 20.0)
+                .set_normal_rotation_probability(0.85)
+                .set_highway_rotation_probability(0.03)
+                .set_highway_construction_priority(40.0)
+                .set_even_path_length_weight(2.0),
+            CityPreset::MountainTown => builder
+                .set_branch_length(0.5)
+                .set_branch_angle_deviation(std::f64::consts::PI / 20.0)
+                .set_branch_max_angle(std::f64::consts::PI / 12.0)
+                .set_normal_rotation_probability(0.7)
+                .set_highway_rotation_probability(0.04)
+                .set_even_path_length_weight(2.0)
+                .set_highway_path_length_weight(2.0)
+                .set_max_grade(0.3),
+        }
+    }
+}
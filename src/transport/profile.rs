@@ -0,0 +1,41 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{terrain::Terrain, transport::transport::TransportNetwork};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Get the profile of the altitudes along the route through `path_node_indices`
+    /// as flattened `[distance0, altitude0, distance1, altitude1, ...]`.
+    ///
+    /// Each edge is divided into `samples_per_edge` intervals of equal length, which are split further at the vertices of curved edges,
+    /// and is sampled only at its vertices if `samples_per_edge` is zero.
+    /// The altitudes are sampled as `polylines_3d`. The distance is measured on the plane from the first node.
+    ///
+    /// Returns `None` if two consecutive nodes are not connected by an edge.
+    pub fn elevation_profile(
+        &self,
+        path_node_indices: Vec<usize>,
+        samples_per_edge: usize,
+        terrain: &Terrain,
+    ) -> Option<Vec<f64>> {
+        let mut profile = vec![];
+        let mut distance = 0.0;
+        for (i, pair) in path_node_indices.windows(2).enumerate() {
+            let length = self.edge_length(pair[0], pair[1])?;
+            let sample_spacing = if samples_per_edge > 0 {
+                length / samples_per_edge as f64
+            } else {
+                0.0
+            };
+            let samples = self.sample_edge_3d(pair[0], pair[1], terrain, sample_spacing);
+            // the first sample is shared with the previous edge
+            let skip = if i == 0 { 0 } else { 1 };
+            samples.iter().skip(skip).for_each(|(_, along, altitude)| {
+                profile.extend([distance + along, *altitude]);
+            });
+            distance += length;
+        }
+        Some(profile)
+    }
+}
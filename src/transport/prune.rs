@@ -0,0 +1,99 @@
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::{
+    chain::extract_chains, dedup::UnionFind, geometry::EdgeGeometries, transport::TransportNetwork,
+};
+
+fn edge_key(index_a: usize, index_b: usize) -> (usize, usize) {
+    (index_a.min(index_b), index_a.max(index_b))
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Create a cleaned copy of the network.
+    ///
+    /// Dead ends shorter than `min_dead_end_length` are removed up to the junction they leave from,
+    /// and then the connected components with fewer nodes than `min_component_size` are dropped.
+    /// The nodes left without any road are removed, so the node indices are renumbered.
+    pub fn prune(&self, min_dead_end_length: f64, min_component_size: usize) -> TransportNetwork {
+        let degrees = (0..self.nodes.len())
+            .map(|index| self.graph.neighbors_of(index).len())
+            .collect::<Vec<_>>();
+
+        let mut removed = HashSet::new();
+        extract_chains(&self.graph, |_| true)
+            .iter()
+            .filter(|chain| degrees[chain[0]] == 1 || degrees[chain[chain.len() - 1]] == 1)
+            .for_each(|chain| {
+                let length = chain
+                    .windows(2)
+                    .map(|pair| self.get_edge_length(pair[0], pair[1]))
+                    .sum::<f64>();
+                if length < min_dead_end_length {
+                    chain.windows(2).for_each(|pair| {
+                        removed.insert(edge_key(pair[0], pair[1]));
+                    });
+                }
+            });
+
+        let remaining = self
+            .iter_edges()
+            .filter(|(start, end, _)| !removed.contains(&edge_key(*start, *end)))
+            .collect::<Vec<_>>();
+
+        // the number of the nodes with any remaining road in each component
+        let mut union_find = UnionFind::new(self.nodes.len());
+        remaining
+            .iter()
+            .for_each(|(start, end, _)| union_find.union(*start, *end));
+        let mut has_edge = vec![false; self.nodes.len()];
+        remaining.iter().for_each(|(start, end, _)| {
+            has_edge[*start] = true;
+            has_edge[*end] = true;
+        });
+        let mut component_sizes = HashMap::new();
+        (0..self.nodes.len())
+            .filter(|index| has_edge[*index])
+            .for_each(|index| *component_sizes.entry(union_find.find(index)).or_insert(0) += 1);
+        let remaining = remaining
+            .into_iter()
+            .filter(|(start, _, _)| component_sizes[&union_find.find(*start)] >= min_component_size)
+            .collect::<Vec<_>>();
+
+        let mut is_kept = vec![false; self.nodes.len()];
+        remaining.iter().for_each(|(start, end, _)| {
+            is_kept[*start] = true;
+            is_kept[*end] = true;
+        });
+        let mut new_indices = vec![None; self.nodes.len()];
+        let mut nodes = vec![];
+        (0..self.nodes.len())
+            .filter(|index| is_kept[*index])
+            .for_each(|index| {
+                new_indices[index] = Some(nodes.len());
+                nodes.push(self.nodes[index]);
+            });
+        let mut geometries = EdgeGeometries::default();
+        let edges = remaining
+            .iter()
+            .map(|(start, end, attr)| {
+                let (new_start, new_end) =
+                    (new_indices[*start].unwrap(), new_indices[*end].unwrap());
+                geometries.insert(new_start, new_end, self.geometries.get(*start, *end));
+                (new_start, new_end, *attr)
+            })
+            .collect::<Vec<_>>();
+
+        let mut network = TransportNetwork::from_edges(nodes, edges, geometries);
+        network.update_overlays(
+            self.highway_overlay.tolerance,
+            self.normal_overlay.tolerance,
+        );
+        network.log = self.log.clone();
+        network.report = self.report.clone();
+        network
+    }
+}
@@ -0,0 +1,175 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    terrain::Terrain,
+    transport::{
+        error::TransportError,
+        poi::{place_along_chains, Poi},
+        transport::{TransportNetwork, TransportNetworkBuilder},
+    },
+    Site2D,
+};
+
+/// A rail network with the stations along its lines.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct RailNetwork {
+    network: TransportNetwork,
+    stations: Vec<Poi>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl RailNetwork {
+    /// Get the tracks as a network, whose roads are all motorways and bridges.
+    pub fn get_network(&self) -> TransportNetwork {
+        self.network.clone()
+    }
+
+    pub fn num_stations(&self) -> usize {
+        self.stations.len()
+    }
+
+    pub fn get_station_site(&self, index: usize) -> Site2D {
+        self.stations[index].site
+    }
+
+    /// Get the nodes `[start, end]` of the edge on which the station lies.
+    /// Both are the same node if the station is at a terminal or a junction.
+    pub fn get_station_edge(&self, index: usize) -> Vec<usize> {
+        let (start, end) = self.stations[index].edge;
+        vec![start, end]
+    }
+
+    /// Get the sites of all the stations as flattened `[x0, y0, x1, y1, ...]`.
+    pub fn get_station_sites(&self) -> Vec<f64> {
+        self.stations
+            .iter()
+            .flat_map(|station| [station.site.x, station.site.y])
+            .collect::<Vec<_>>()
+    }
+}
+
+/// Grows rail lines by the growth of the roads under the constraints of railways:
+/// long straight segments, gentle curves and grades, and few branches.
+///
+/// The stations are placed at the terminals and the junctions,
+/// and along the lines between them at about the station spacing.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct RailNetworkBuilder {
+    start: Site2D,
+    iterations: usize,
+    segment_length: f64,
+    max_curve_angle: f64,
+    max_grade: f64,
+    branch_probability: f64,
+    bridge_max_length: f64,
+    station_spacing: f64,
+}
+
+impl Default for RailNetworkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl RailNetworkBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            start: Site2D { x: 0.0, y: 0.0 },
+            iterations: 0,
+            segment_length: 2.0,
+            max_curve_angle: std::f64::consts::PI / 24.0,
+            max_grade: 0.25,
+            branch_probability: 0.01,
+            bridge_max_length: 0.0,
+            station_spacing: 0.0,
+        }
+    }
+
+    pub fn set_start(self, start_x: f64, start_y: f64) -> Self {
+        Self {
+            start: Site2D {
+                x: start_x,
+                y: start_y,
+            },
+            ..self
+        }
+    }
+
+    pub fn set_iterations(self, iterations: usize) -> Self {
+        Self { iterations, ..self }
+    }
+
+    /// Set the length of the straight segments of the tracks.
+    pub fn set_segment_length(self, segment_length: f64) -> Self {
+        Self {
+            segment_length,
+            ..self
+        }
+    }
+
+    /// Set the maximum angle by which the tracks turn at each segment.
+    pub fn set_max_curve_angle(self, max_curve_angle: f64) -> Self {
+        Self {
+            max_curve_angle,
+            ..self
+        }
+    }
+
+    /// Set the maximum grade (rise over run) of the tracks in the units of the terrain, which climb by switchbacks where they are steeper.
+    /// It should be lower than the typical slopes of the terrain so that the tracks keep to the valleys and the plains.
+    pub fn set_max_grade(self, max_grade: f64) -> Self {
+        Self { max_grade, ..self }
+    }
+
+    /// Set the probability that a line branches to each side at each segment.
+    pub fn set_branch_probability(self, branch_probability: f64) -> Self {
+        Self {
+            branch_probability,
+            ..self
+        }
+    }
+
+    /// Set the maximum length of the bridges over the sea. Bridges are disabled by default.
+    pub fn set_bridge_max_length(self, bridge_max_length: f64) -> Self {
+        Self {
+            bridge_max_length,
+            ..self
+        }
+    }
+
+    /// Set the distance between the stations along the lines. Zero places the stations only at the terminals and the junctions.
+    pub fn set_station_spacing(self, station_spacing: f64) -> Self {
+        Self {
+            station_spacing,
+            ..self
+        }
+    }
+
+    /// Get the builder of the roads which grows the tracks.
+    pub fn to_transport_builder(&self) -> TransportNetworkBuilder {
+        TransportNetworkBuilder::new()
+            .set_start(self.start.x, self.start.y)
+            .set_iterations(self.iterations)
+            .set_branch_length(self.segment_length)
+            .set_branch_angle_deviation(self.max_curve_angle / 4.0)
+            .set_branch_max_angle(self.max_curve_angle)
+            .set_highway_rotation_probability(self.branch_probability)
+            .set_normal_rotation_probability(0.0)
+            .set_highway_construction_priority(1.0)
+            .set_even_path_length_weight(1.0)
+            .set_highway_path_length_weight(1.0)
+            .set_max_grade(self.max_grade)
+            .set_bridge_max_length(self.bridge_max_length)
+            .set_bridge_cost_multiplier(2.0)
+    }
+
+    pub fn build(&self, seed: u32, terrain: &Terrain) -> Result<RailNetwork, TransportError> {
+        let network = self.to_transport_builder().build(seed, terrain)?;
+        let stations = place_along_chains(&network, |_| true, self.station_spacing);
+        Ok(RailNetwork { network, stations })
+    }
+}
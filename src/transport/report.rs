@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::transport::TransportNetwork;
+
+/// The maximum number of angle steps checked on each side of a branch.
+pub(crate) const MAX_CHECK_TIMES: usize = 64;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum BuildWarning {
+    /// Both rotation probabilities are zero, so the network consists of straight highways only.
+    RotationDisabled,
+    /// The angle deviation exceeds the maximum angle, so only the straight candidate is checked.
+    SingleCandidate,
+    /// The angle deviation is zero, negative or NaN. Only the straight candidate is checked.
+    AngleDeviationAdjusted,
+    /// The candidates per side exceeded `MAX_CHECK_TIMES` and were clamped.
+    CandidatesClamped,
+    /// A rotation probability was outside `[0, 1]` or NaN and was clamped into the range.
+    ProbabilityClamped,
+}
+
+impl BuildWarning {
+    fn message(&self) -> &'static str {
+        match self {
+            Self::RotationDisabled => {
+                "both rotation probabilities are zero; the network will consist of straight highways only"
+            }
+            Self::SingleCandidate => {
+                "branch_angle_deviation exceeds branch_max_angle; only the straight candidate is checked"
+            }
+            Self::AngleDeviationAdjusted => {
+                "branch_angle_deviation is not positive; only the straight candidate is checked"
+            }
+            Self::CandidatesClamped => {
+                "branch_max_angle / branch_angle_deviation is too large; the candidates were clamped to 64 per side"
+            }
+            Self::ProbabilityClamped => {
+                "a rotation probability was outside [0, 1] and was clamped into the range"
+            }
+        }
+    }
+}
+
+/// Warnings about degenerate parameter combinations and the adjustments made for them.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BuildReport {
+    warnings: Vec<BuildWarning>,
+}
+
+impl BuildReport {
+    pub(crate) fn warn(&mut self, warning: BuildWarning) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    /// Add the warnings of `other` which are not reported yet.
+    pub(crate) fn merge(&mut self, other: &BuildReport) {
+        other
+            .warnings
+            .iter()
+            .for_each(|warning| self.warn(*warning));
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BuildReport {
+    pub fn num_warnings(&self) -> usize {
+        self.warnings.len()
+    }
+
+    pub fn get_warning(&self, index: usize) -> BuildWarning {
+        self.warnings[index]
+    }
+
+    pub fn get_message(&self, index: usize) -> String {
+        self.warnings[index].message().to_string()
+    }
+
+    pub fn has_warning(&self, warning: BuildWarning) -> bool {
+        self.warnings.contains(&warning)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    pub fn get_build_report(&self) -> BuildReport {
+        self.report.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{road_class::RoadClass, transport::TransportNetworkBuilder};
+
+    fn builder(max_angle: f64, deviation: f64) -> TransportNetworkBuilder {
+        TransportNetworkBuilder::new()
+            .set_branch_max_angle(max_angle)
+            .set_branch_angle_deviation(deviation)
+            .set_normal_rotation_probability(0.5)
+            .set_highway_rotation_probability(0.5)
+    }
+
+    fn probabilities(probability: f64) -> ([f64; 4], BuildReport) {
+        let (_, probabilities, report) = builder(1.0, 1.0)
+            .set_normal_rotation_probability(probability)
+            .set_highway_rotation_probability(probability)
+            .adjusted_parameters();
+        (probabilities, report)
+    }
+
+    #[test]
+    fn probabilities_at_the_bounds_are_kept() {
+        for probability in [0.0, 1.0] {
+            let (probabilities, report) = probabilities(probability);
+            assert_eq!(probabilities[RoadClass::Motorway.index()], probability);
+            assert_eq!(probabilities[RoadClass::Local.index()], probability);
+            assert!(!report.has_warning(BuildWarning::ProbabilityClamped));
+        }
+    }
+
+    #[test]
+    fn probabilities_outside_the_bounds_are_clamped() {
+        for (probability, clamped) in [(-0.5, 0.0), (1.5, 1.0), (f64::NAN, 0.0)] {
+            let (probabilities, report) = probabilities(probability);
+            assert_eq!(probabilities[RoadClass::Motorway.index()], clamped);
+            assert_eq!(probabilities[RoadClass::Local.index()], clamped);
+            assert!(report.has_warning(BuildWarning::ProbabilityClamped));
+        }
+    }
+
+    #[test]
+    fn zero_rotation_is_diagnosed() {
+        let (_, report) = probabilities(0.0);
+        assert!(report.has_warning(BuildWarning::RotationDisabled));
+        let (_, report) = probabilities(f64::MIN_POSITIVE);
+        assert!(!report.has_warning(BuildWarning::RotationDisabled));
+    }
+
+    #[test]
+    fn deviation_equal_to_the_max_angle_checks_one_step() {
+        let (check_times, _, report) = builder(1.0, 1.0).adjusted_parameters();
+        assert_eq!(check_times, 1);
+        assert_eq!(report.num_warnings(), 0);
+    }
+
+    #[test]
+    fn deviation_above_the_max_angle_checks_the_straight_candidate() {
+        let (check_times, _, report) = builder(1.0, 1.0 + 1e-9).adjusted_parameters();
+        assert_eq!(check_times, 0);
+        assert!(report.has_warning(BuildWarning::SingleCandidate));
+        let (check_times, _, report) = builder(f64::NAN, 1.0).adjusted_parameters();
+        assert_eq!(check_times, 0);
+        assert!(report.has_warning(BuildWarning::SingleCandidate));
+    }
+
+    #[test]
+    fn steps_are_clamped_above_the_maximum() {
+        let max = MAX_CHECK_TIMES as f64;
+        let (check_times, _, report) = builder(max, 1.0).adjusted_parameters();
+        assert_eq!(check_times, MAX_CHECK_TIMES);
+        assert!(!report.has_warning(BuildWarning::CandidatesClamped));
+        let (check_times, _, report) = builder(max + 1.0, 1.0).adjusted_parameters();
+        assert_eq!(check_times, MAX_CHECK_TIMES);
+        assert!(report.has_warning(BuildWarning::CandidatesClamped));
+        let (check_times, _, report) = builder(f64::INFINITY, 1.0).adjusted_parameters();
+        assert_eq!(check_times, MAX_CHECK_TIMES);
+        assert!(report.has_warning(BuildWarning::CandidatesClamped));
+    }
+
+    #[test]
+    fn zero_and_negative_deviations_are_adjusted() {
+        for deviation in [0.0, -0.0, -1.0, f64::NAN] {
+            let report = builder(1.0, deviation).diagnose();
+            assert!(report.has_warning(BuildWarning::AngleDeviationAdjusted));
+            assert_eq!(builder(1.0, deviation).adjusted_parameters().0, 0);
+        }
+    }
+
+    #[test]
+    fn warnings_are_reported_once() {
+        let report = builder(-1.0, -1.0)
+            .set_normal_rotation_probability(2.0)
+            .set_highway_rotation_probability(-2.0)
+            .diagnose();
+        assert_eq!(report.num_warnings(), 2);
+        assert!(report.has_warning(BuildWarning::AngleDeviationAdjusted));
+        assert!(report.has_warning(BuildWarning::ProbabilityClamped));
+    }
+}
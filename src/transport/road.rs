@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{road_class::RoadClass, transport::TransportNetwork},
+    Site2D,
+};
+
+const NAME_STEMS: [&str; 24] = [
+    "Oak", "Maple", "Cedar", "Pine", "Elm", "Willow", "Birch", "Ash", "Hill", "Lake", "River",
+    "Park", "Church", "Mill", "Station", "Market", "Spring", "Meadow", "Harbor", "Bridge",
+    "Castle", "Forest", "Garden", "Valley",
+];
+
+fn name_suffixes(road_class: RoadClass) -> &'static [&'static str] {
+    match road_class {
+        RoadClass::Motorway => &["Expressway", "Highway", "Freeway"],
+        RoadClass::Arterial => &["Avenue", "Boulevard", "Parkway"],
+        RoadClass::Collector => &["Road", "Street", "Drive"],
+        RoadClass::Local => &["Street", "Lane", "Way", "Close"],
+    }
+}
+
+/// The roads of the network: the chains of the edges of the same class
+/// which continue straightest through the intersections.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Roads {
+    pub(crate) road_nodes: Vec<Vec<usize>>,
+    pub(crate) road_classes: Vec<RoadClass>,
+    pub(crate) road_polylines: Vec<Vec<Site2D>>,
+    pub(crate) road_names: Vec<String>,
+    pub(crate) edge_roads: HashMap<(usize, usize), usize>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Roads {
+    pub fn num_roads(&self) -> usize {
+        self.road_nodes.len()
+    }
+
+    /// Get the road which the edge belongs to, or `None` if there is no such edge.
+    pub fn get_road_of_edge(&self, index_a: usize, index_b: usize) -> Option<usize> {
+        self.edge_roads
+            .get(&(index_a.min(index_b), index_a.max(index_b)))
+            .copied()
+    }
+
+    /// Get the nodes along the road in order.
+    pub fn get_road_nodes(&self, road_id: usize) -> Vec<usize> {
+        self.road_nodes[road_id].clone()
+    }
+
+    /// Get the polyline of the road as flattened `[x0, y0, x1, y1, ...]`, including the vertices of its curved edges.
+    pub fn get_road_polyline(&self, road_id: usize) -> Vec<f64> {
+        self.road_polylines[road_id]
+            .iter()
+            .flat_map(|site| [site.x, site.y])
+            .collect::<Vec<_>>()
+    }
+
+    pub fn get_road_class(&self, road_id: usize) -> RoadClass {
+        self.road_classes[road_id]
+    }
+
+    /// Get the procedural name of the road, which is unique in the network.
+    pub fn get_road_name(&self, road_id: usize) -> String {
+        self.road_names[road_id].clone()
+    }
+}
+
+/// Get the angle between the directions `a0 -> a1` and `b0 -> b1`.
+fn get_turn_angle(a0: Site2D, a1: Site2D, b0: Site2D, b1: Site2D) -> f64 {
+    let (ax, ay) = (a1.x - a0.x, a1.y - a0.y);
+    let (bx, by) = (b1.x - b0.x, b1.y - b0.y);
+    (ax * by - ay * bx).atan2(ax * bx + ay * by).abs()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Group the edges into roads.
+    ///
+    /// Each road starts from the unassigned edge with the smallest node indices and is extended at both ends
+    /// through the unassigned edge of the same class which turns least, as long as the turn is within `max_turn_angle`.
+    /// The road IDs are therefore stable for the same network, and the names are generated from `seed`.
+    pub fn assign_roads(&self, max_turn_angle: f64, seed: u32) -> Roads {
+        let mut edge_roads = HashMap::new();
+        let mut road_nodes = vec![];
+        let mut road_classes = vec![];
+        let key = |a: usize, b: usize| (a.min(b), a.max(b));
+
+        let edges = self.iter_edges().collect::<Vec<_>>();
+        edges.iter().for_each(|(start, end, attr)| {
+            if edge_roads.contains_key(&(*start, *end)) {
+                return;
+            }
+            let road_id = road_nodes.len();
+            let road_class = attr.path_attr.road_class;
+            edge_roads.insert((*start, *end), road_id);
+
+            // extend the road forward from `end`, then backward from `start`
+            let extend = |from: usize, to: usize, edge_roads: &mut HashMap<_, _>| {
+                let mut nodes = vec![];
+                let (mut previous, mut current) = (from, to);
+                loop {
+                    let incoming = self.get_polyline(previous, current);
+                    let (in0, in1) = (incoming[incoming.len() - 2], incoming[incoming.len() - 1]);
+                    let next = self
+                        .graph
+                        .neighbors_of(current)
+                        .iter()
+                        .filter(|n| {
+                            n.1.path_attr.road_class == road_class
+                                && !edge_roads.contains_key(&key(current, n.0))
+                        })
+                        .map(|n| {
+                            let outgoing = self.get_polyline(current, n.0);
+                            (n.0, get_turn_angle(in0, in1, outgoing[0], outgoing[1]))
+                        })
+                        .filter(|(_, angle)| *angle <= max_turn_angle)
+                        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+                    match next {
+                        Some((next, _)) => {
+                            edge_roads.insert(key(current, next), road_id);
+                            nodes.push(next);
+                            (previous, current) = (current, next);
+                        }
+                        None => return nodes,
+                    }
+                }
+            };
+            let forward = extend(*start, *end, &mut edge_roads);
+            let backward = extend(*end, *start, &mut edge_roads);
+            let nodes = backward
+                .into_iter()
+                .rev()
+                .chain([*start, *end])
+                .chain(forward)
+                .collect::<Vec<_>>();
+            road_nodes.push(nodes);
+            road_classes.push(road_class);
+        });
+
+        let road_polylines = road_nodes
+            .iter()
+            .map(|nodes| {
+                let mut polyline = vec![self.nodes[nodes[0]]];
+                nodes.windows(2).for_each(|pair| {
+                    polyline.extend(self.get_polyline(pair[0], pair[1]).into_iter().skip(1))
+                });
+                polyline
+            })
+            .collect::<Vec<_>>();
+
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let mut name_counts = HashMap::new();
+        let road_names = road_classes
+            .iter()
+            .map(|road_class| {
+                let suffixes = name_suffixes(*road_class);
+                let name = format!(
+                    "{} {}",
+                    NAME_STEMS[rng.gen_range(0..NAME_STEMS.len())],
+                    suffixes[rng.gen_range(0..suffixes.len())]
+                );
+                // the repeated names are numbered, as "Oak Street 2"
+                let count = name_counts.entry(name.clone()).or_insert(0);
+                *count += 1;
+                if *count == 1 {
+                    name
+                } else {
+                    format!("{} {}", name, count)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Roads {
+            road_nodes,
+            road_classes,
+            road_polylines,
+            road_names,
+            edge_roads,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// The classes of the roads in the order of the hierarchy.
+/// The roads of every class but `Local` are highways.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Serialize, Deserialize,
+)]
+pub enum RoadClass {
+    Motorway,
+    Arterial,
+    Collector,
+    #[default]
+    Local,
+}
+
+pub(crate) const ROAD_CLASSES: [RoadClass; 4] = [
+    RoadClass::Motorway,
+    RoadClass::Arterial,
+    RoadClass::Collector,
+    RoadClass::Local,
+];
+
+impl RoadClass {
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            RoadClass::Motorway => "motorway",
+            RoadClass::Arterial => "arterial",
+            RoadClass::Collector => "collector",
+            RoadClass::Local => "local",
+        }
+    }
+}
+
+/// The parameters of the growth of the roads in a class.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(crate) struct ClassParameters {
+    /// The ratio of the branch length, which also weights the altitude difference in the cost.
+    pub(crate) branch_length_weight: f64,
+    /// The probability that a side branch keeps the class of its road.
+    /// For local roads, the probability that a side branch grows at all.
+    pub(crate) rotation_probability: f64,
+    /// The multiplier of the cost of the branches.
+    pub(crate) cost_weight: f64,
+}
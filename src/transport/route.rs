@@ -0,0 +1,160 @@
+use std::collections::BinaryHeap;
+
+use crate::{
+    terrain::{Terrain, SEA_LEVEL},
+    transport::math::simplify_polyline,
+    Site2D,
+};
+
+struct Cell {
+    index: usize,
+    estimated_cost: f64,
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.estimated_cost.total_cmp(&self.estimated_cost)
+    }
+}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.estimated_cost == other.estimated_cost
+    }
+}
+
+impl Eq for Cell {}
+
+/// A least-cost route over the terrain.
+pub(crate) struct TerrainRoute {
+    pub polyline: Vec<Site2D>,
+    pub cost: f64,
+}
+
+/// Find a least-cost route between two sites with A* search on a grid of `resolution` spacing.
+/// The cost of a step is its length weighted by the slope, and the sea is impassable.
+pub(crate) fn find_terrain_route(
+    terrain: &Terrain,
+    from: Site2D,
+    to: Site2D,
+    resolution: f64,
+    slope_weight: f64,
+) -> Option<TerrainRoute> {
+    let bound_min = terrain.get_bound_min();
+    let bound_max = terrain.get_bound_max();
+    if resolution <= 0.0 {
+        return None;
+    }
+    let width = ((bound_max.x - bound_min.x) / resolution).ceil() as usize + 1;
+    let height = ((bound_max.y - bound_min.y) / resolution).ceil() as usize + 1;
+
+    let to_cell = |site: Site2D| -> Option<usize> {
+        let ix = ((site.x - bound_min.x) / resolution).round();
+        let iy = ((site.y - bound_min.y) / resolution).round();
+        if ix < 0.0 || iy < 0.0 || ix >= width as f64 || iy >= height as f64 {
+            return None;
+        }
+        Some(iy as usize * width + ix as usize)
+    };
+    let to_site = |index: usize| Site2D {
+        x: bound_min.x + (index % width) as f64 * resolution,
+        y: bound_min.y + (index / width) as f64 * resolution,
+    };
+
+    let mut altitudes: Vec<Option<Option<f64>>> = vec![None; width * height];
+    let mut get_altitude = |index: usize| -> Option<f64> {
+        if let Some(altitude) = altitudes[index] {
+            return altitude;
+        }
+        let site = to_site(index);
+        let altitude = terrain
+            .get_altitude(site.x, site.y)
+            .filter(|altitude| *altitude >= SEA_LEVEL);
+        altitudes[index] = Some(altitude);
+        altitude
+    };
+
+    let cell_from = to_cell(from)?;
+    let cell_to = to_cell(to)?;
+    let site_to = to_site(cell_to);
+    let heuristic =
+        |site: Site2D| ((site.x - site_to.x).powi(2) + (site.y - site_to.y).powi(2)).sqrt();
+
+    let mut costs = vec![f64::MAX; width * height];
+    let mut previous = vec![usize::MAX; width * height];
+    let mut heap = BinaryHeap::new();
+    costs[cell_from] = 0.0;
+    get_altitude(cell_from)?;
+    heap.push(Cell {
+        index: cell_from,
+        estimated_cost: heuristic(to_site(cell_from)),
+    });
+
+    while let Some(cell) = heap.pop() {
+        if cell.index == cell_to {
+            break;
+        }
+        let site = to_site(cell.index);
+        if cell.estimated_cost > costs[cell.index] + heuristic(site) {
+            continue;
+        }
+        let altitude = get_altitude(cell.index)?;
+        let (ix, iy) = ((cell.index % width) as i64, (cell.index / width) as i64);
+        for (dx, dy) in [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ] {
+            let (nx, ny) = (ix + dx, iy + dy);
+            if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                continue;
+            }
+            let next = ny as usize * width + nx as usize;
+            let next_altitude = if let Some(next_altitude) = get_altitude(next) {
+                next_altitude
+            } else {
+                continue;
+            };
+            let distance = ((dx * dx + dy * dy) as f64).sqrt() * resolution;
+            let slope = (next_altitude - altitude).abs() / distance;
+            let cost = costs[cell.index] + distance * (1.0 + slope_weight * slope);
+            if cost < costs[next] {
+                costs[next] = cost;
+                previous[next] = cell.index;
+                heap.push(Cell {
+                    index: next,
+                    estimated_cost: cost + heuristic(to_site(next)),
+                });
+            }
+        }
+    }
+
+    if costs[cell_to] == f64::MAX {
+        return None;
+    }
+
+    let mut polyline = vec![to];
+    let mut current = previous[cell_to];
+    while current != cell_from && current != usize::MAX {
+        polyline.push(to_site(current));
+        current = previous[current];
+    }
+    polyline.push(from);
+    polyline.reverse();
+
+    Some(TerrainRoute {
+        polyline: simplify_polyline(&polyline, resolution * 0.5),
+        cost: costs[cell_to],
+    })
+}
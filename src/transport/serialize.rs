@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize, Serializer};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        geometry::EdgeGeometries,
+        log::GenerationLog,
+        report::BuildReport,
+        transport::{EdgeAttr, TransportNetwork},
+    },
+    Site2D,
+};
+
+const NETWORK_FORMAT_VERSION: u32 = 5;
+
+/// The serialized form of a network.
+/// The overlays are regenerated from their tolerances on deserialization.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SerializedNetwork {
+    nodes: Vec<Site2D>,
+    edges: Vec<(usize, usize, EdgeAttr)>,
+    geometries: Vec<(usize, usize, Vec<Site2D>)>,
+    highway_overlay_tolerance: f64,
+    normal_overlay_tolerance: f64,
+    log: GenerationLog,
+    report: BuildReport,
+}
+
+impl From<&TransportNetwork> for SerializedNetwork {
+    fn from(network: &TransportNetwork) -> Self {
+        // the geometries are sorted to keep the output deterministic
+        let mut geometries = network
+            .geometries
+            .iter()
+            .map(|(start, end, intermediate)| (start, end, intermediate.clone()))
+            .collect::<Vec<_>>();
+        geometries.sort_by_key(|(start, end, _)| (*start, *end));
+        Self {
+            nodes: network.nodes.clone(),
+            edges: network.iter_edges().collect::<Vec<_>>(),
+            geometries,
+            highway_overlay_tolerance: network.highway_overlay.tolerance,
+            normal_overlay_tolerance: network.normal_overlay.tolerance,
+            log: network.log.clone(),
+            report: network.report.clone(),
+        }
+    }
+}
+
+impl TryFrom<SerializedNetwork> for TransportNetwork {
+    type Error = String;
+
+    fn try_from(serialized: SerializedNetwork) -> Result<Self, Self::Error> {
+        let num_nodes = serialized.nodes.len();
+        if serialized
+            .edges
+            .iter()
+            .any(|(start, end, _)| *start >= num_nodes || *end >= num_nodes)
+        {
+            return Err("an edge refers to a node out of range".to_string());
+        }
+        let mut geometries = EdgeGeometries::default();
+        serialized
+            .geometries
+            .into_iter()
+            .for_each(|(start, end, intermediate)| geometries.insert(start, end, intermediate));
+
+        let mut network =
+            TransportNetwork::from_edges(serialized.nodes, serialized.edges, geometries);
+        network.update_overlays(
+            serialized.highway_overlay_tolerance,
+            serialized.normal_overlay_tolerance,
+        );
+        network.log = serialized.log;
+        network.report = serialized.report;
+        Ok(network)
+    }
+}
+
+impl Serialize for TransportNetwork {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedNetwork::from(self).serialize(serializer)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Serialize the network into bytes, which are identical for identical networks.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = NETWORK_FORMAT_VERSION.to_le_bytes().to_vec();
+        bytes.extend(bincode::serialize(self).unwrap());
+        bytes
+    }
+
+    /// Load a network written by `to_bytes`.
+    /// Returns `None` if the data is broken or written in an unsupported version.
+    pub fn from_bytes(bytes: &[u8]) -> Option<TransportNetwork> {
+        let version = u32::from_le_bytes(bytes.get(0..4)?.try_into().ok()?);
+        if version != NETWORK_FORMAT_VERSION {
+            return None;
+        }
+        bincode::deserialize(&bytes[4..]).ok()
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BinaryHeap;
+
+struct Visit {
+    index: usize,
+    distance: f64,
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.distance == other.distance
+    }
+}
+
+impl Eq for Visit {}
+
+/// The shortest paths from an origin to every node.
+pub(crate) struct ShortestPathTree {
+    /// The distance to each node, which is `f64::MAX` for the nodes not reached.
+    pub(crate) distances: Vec<f64>,
+    /// The previous node on the path to each node.
+    pub(crate) previous: Vec<Option<usize>>,
+    /// The reached nodes in the order of their distances.
+    pub(crate) order: Vec<usize>,
+}
+
+/// Find the shortest paths from `origin` by Dijkstra's algorithm.
+/// `neighbors` gives the nodes reachable from a node with the weights of the edges to them.
+/// The edges of a negative or non-finite weight are not followed.
+pub(crate) fn shortest_path_tree<F>(
+    num_nodes: usize,
+    origin: usize,
+    neighbors: F,
+) -> ShortestPathTree
+where
+    F: Fn(usize) -> Vec<(usize, f64)>,
+{
+    let mut distances = vec![f64::MAX; num_nodes];
+    let mut previous = vec![None; num_nodes];
+    let mut order = vec![];
+    let mut heap = BinaryHeap::new();
+    distances[origin] = 0.0;
+    heap.push(Visit {
+        index: origin,
+        distance: 0.0,
+    });
+    while let Some(visit) = heap.pop() {
+        if visit.distance > distances[visit.index] {
+            continue;
+        }
+        order.push(visit.index);
+        neighbors(visit.index)
+            .into_iter()
+            .filter(|(_, weight)| weight.is_finite() && *weight >= 0.0)
+            .for_each(|(next, weight)| {
+                let distance = visit.distance + weight;
+                if distance < distances[next] {
+                    distances[next] = distance;
+                    previous[next] = Some(visit.index);
+                    heap.push(Visit {
+                        index: next,
+                        distance,
+                    });
+                }
+            });
+    }
+    ShortestPathTree {
+        distances,
+        previous,
+        order,
+    }
+}
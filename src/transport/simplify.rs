@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::{
+    chain::extract_chains,
+    geometry::EdgeGeometries,
+    math::simplify_polyline,
+    oneway::OneWay,
+    road_class::RoadClass,
+    transport::{EdgeAttr, TransportNetwork},
+};
+
+fn edge_key(index_a: usize, index_b: usize) -> (usize, usize) {
+    (index_a.min(index_b), index_a.max(index_b))
+}
+
+impl TransportNetwork {
+    /// Get whether the edge can be traveled forward and backward.
+    fn directions(&self, index_from: usize, index_to: usize, attr: &EdgeAttr) -> (bool, bool) {
+        let (site_from, site_to) = (&self.nodes[index_from], &self.nodes[index_to]);
+        (
+            attr.one_way.allows(site_from, site_to),
+            attr.one_way.allows(site_to, site_from),
+        )
+    }
+
+    /// Create a copy of the network made of the edges accepted by `filter`,
+    /// in which each run of edges through nodes with two accepted edges is merged into a single edge.
+    ///
+    /// A run is broken where the attributes or the one-way direction of its edges change.
+    /// The polyline of a merged edge is simplified with `tolerance`, and its cost is the sum of the costs.
+    /// A closed run is split into three edges, and a run between the same nodes as another one into two,
+    /// so that the nodes are connected by at most one edge.
+    /// The nodes left without any road are removed, so the node indices are renumbered.
+    pub(crate) fn contract<F>(&self, filter: F, tolerance: f64) -> TransportNetwork
+    where
+        F: Fn(&EdgeAttr) -> bool,
+    {
+        let attr_of = |index_a: usize, index_b: usize| self.graph.has_edge(index_a, index_b).1;
+
+        let mut runs = vec![];
+        extract_chains(&self.graph, &filter)
+            .into_iter()
+            .for_each(|chain| {
+                let mut run = vec![chain[0]];
+                chain.windows(2).for_each(|pair| {
+                    if run.len() >= 2 {
+                        let previous = run[run.len() - 2];
+                        let (attr_a, attr_b) =
+                            (attr_of(previous, pair[0]), attr_of(pair[0], pair[1]));
+                        if attr_a.path_attr != attr_b.path_attr
+                            || self.directions(previous, pair[0], &attr_a)
+                                != self.directions(pair[0], pair[1], &attr_b)
+                        {
+                            runs.push(std::mem::replace(&mut run, vec![pair[0]]));
+                        }
+                    }
+                    run.push(pair[1]);
+                });
+                runs.push(run);
+            });
+        // the single edges first, as they cannot be split to avoid another run between the same nodes
+        runs.sort_by_key(|run| run.len());
+
+        let mut pieces = vec![];
+        let mut connected = HashSet::new();
+        runs.into_iter().for_each(|run| {
+            let (first, last) = (run[0], run[run.len() - 1]);
+            let splits = if first == last {
+                vec![0, run.len() / 3, run.len() * 2 / 3, run.len() - 1]
+            } else if connected.contains(&edge_key(first, last)) && run.len() > 2 {
+                vec![0, run.len() / 2, run.len() - 1]
+            } else {
+                vec![0, run.len() - 1]
+            };
+            splits.windows(2).for_each(|pair| {
+                let piece = run[pair[0]..=pair[1]].to_vec();
+                connected.insert(edge_key(piece[0], piece[piece.len() - 1]));
+                pieces.push(piece);
+            });
+        });
+
+        let mut new_indices = vec![None; self.nodes.len()];
+        pieces.iter().for_each(|piece| {
+            new_indices[piece[0]] = Some(0);
+            new_indices[piece[piece.len() - 1]] = Some(0);
+        });
+        let mut nodes = vec![];
+        (0..self.nodes.len()).for_each(|index| {
+            if new_indices[index].is_some() {
+                new_indices[index] = Some(nodes.len());
+                nodes.push(self.nodes[index]);
+            }
+        });
+
+        let mut geometries = EdgeGeometries::default();
+        let edges = pieces
+            .iter()
+            .map(|piece| {
+                let (first, last) = (piece[0], piece[piece.len() - 1]);
+                let first_attr = attr_of(piece[0], piece[1]);
+                let one_way = match self.directions(piece[0], piece[1], &first_attr) {
+                    (true, true) => OneWay::TwoWay,
+                    (true, false) => OneWay::from_travel(&self.nodes[first], &self.nodes[last]),
+                    _ => OneWay::from_travel(&self.nodes[last], &self.nodes[first]),
+                };
+                let mut polyline = vec![self.nodes[first]];
+                piece.windows(2).for_each(|pair| {
+                    polyline.extend(self.get_polyline(pair[0], pair[1]).into_iter().skip(1));
+                });
+                let polyline = simplify_polyline(&polyline, tolerance);
+                let (new_first, new_last) =
+                    (new_indices[first].unwrap(), new_indices[last].unwrap());
+                geometries.insert(
+                    new_first,
+                    new_last,
+                    polyline[1..polyline.len() - 1].to_vec(),
+                );
+                (
+                    new_first,
+                    new_last,
+                    EdgeAttr {
+                        path_attr: first_attr.path_attr,
+                        cost: piece
+                            .windows(2)
+                            .map(|pair| attr_of(pair[0], pair[1]).cost)
+                            .sum::<f64>(),
+                        one_way,
+                        iteration: piece
+                            .windows(2)
+                            .filter_map(|pair| attr_of(pair[0], pair[1]).iteration)
+                            .min(),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut network = TransportNetwork::from_edges(nodes, edges, geometries);
+        network.update_overlays(
+            self.highway_overlay.tolerance,
+            self.normal_overlay.tolerance,
+        );
+        network.log = self.log.clone();
+        network.report = self.report.clone();
+        network
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Create a reduced copy of the network for distant zoom levels, while the network itself serves close ones.
+    ///
+    /// Only the roads of `min_class` and the more important classes are kept,
+    /// the runs of them between junctions are merged into single edges,
+    /// and their polylines are simplified by the Douglas-Peucker algorithm with `tolerance`.
+    /// The node indices are renumbered.
+    pub fn simplify(&self, min_class: RoadClass, tolerance: f64) -> TransportNetwork {
+        self.contract(|attr| attr.path_attr.road_class <= min_class, tolerance)
+    }
+
+    /// Create a copy of the network in which each run of edges through nodes of degree 2 is merged into a single edge
+    /// carrying the whole polyline of the run, for faster routing and metrics and for rendering as polylines.
+    ///
+    /// The runs are broken where the attributes or the one-way direction of the edges change,
+    /// and the cost of a merged edge is the sum of the costs. The node indices are renumbered.
+    pub fn collapse_chains(&self) -> TransportNetwork {
+        self.contract(|_| true, 0.0)
+    }
+}
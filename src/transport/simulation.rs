@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rstar::RTree;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::transport::{
+    road_class::RoadClass, shortest::shortest_path_tree, transport::TransportNetwork,
+};
+
+/// The traffic volume assigned to each edge, in the order of `TransportNetwork::get_edges`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct TrafficFlow {
+    edges: Vec<(usize, usize)>,
+    volumes: Vec<f64>,
+    travel_times: Vec<f64>,
+    unassigned_demand: f64,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TrafficFlow {
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn get_volumes(&self) -> Vec<f64> {
+        self.volumes.clone()
+    }
+
+    /// Get the congested travel times of the edges after the assignment.
+    pub fn get_travel_times(&self) -> Vec<f64> {
+        self.travel_times.clone()
+    }
+
+    /// Get the volume on the edge between the nodes, or `None` if there is no such edge.
+    pub fn get_volume(&self, index_a: usize, index_b: usize) -> Option<f64> {
+        let key = (index_a.min(index_b), index_a.max(index_b));
+        self.edges
+            .iter()
+            .position(|edge| (edge.0.min(edge.1), edge.0.max(edge.1)) == key)
+            .map(|position| self.volumes[position])
+    }
+
+    /// Get the demand between the zones which are not connected by the network.
+    pub fn get_unassigned_demand(&self) -> f64 {
+        self.unassigned_demand
+    }
+}
+
+/// Assigns the trips between zones to the network with the method of successive averages,
+/// where the travel time of each edge grows with its volume by the BPR function
+/// `t = t0 * (1 + alpha * (volume / capacity) ^ beta)`.
+///
+/// The zones are nodes sampled in proportion to the number of nodes around them,
+/// and the demand between two zones is proportional to the product of these numbers.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct TrafficSimulationBuilder {
+    num_zones: usize,
+    density_radius: f64,
+    total_demand: f64,
+    iterations: usize,
+    speeds: [f64; 4],
+    capacities: [f64; 4],
+    bpr_alpha: f64,
+    bpr_beta: f64,
+}
+
+impl Default for TrafficSimulationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TrafficSimulationBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            num_zones: 0,
+            density_radius: 0.0,
+            total_demand: 0.0,
+            iterations: 1,
+            speeds: [2.0, 1.6, 1.3, 1.0],
+            capacities: [4.0, 2.0, 1.5, 1.0],
+            bpr_alpha: 0.15,
+            bpr_beta: 4.0,
+        }
+    }
+
+    pub fn set_num_zones(self, num_zones: usize) -> Self {
+        Self { num_zones, ..self }
+    }
+
+    /// Set the radius within which the nodes are counted as the density around a node.
+    pub fn set_density_radius(self, density_radius: f64) -> Self {
+        Self {
+            density_radius,
+            ..self
+        }
+    }
+
+    /// Set the number of the trips between all the zones.
+    pub fn set_total_demand(self, total_demand: f64) -> Self {
+        Self {
+            total_demand,
+            ..self
+        }
+    }
+
+    /// Set the number of the assignments. A single iteration assigns all the trips to the free-flow shortest paths.
+    pub fn set_iterations(self, iterations: usize) -> Self {
+        Self { iterations, ..self }
+    }
+
+    /// Set the free-flow speed of the roads in `class`. The higher classes are faster by default.
+    /// The roads of a zero, negative or NaN speed are closed, and the trips needing them are unassigned.
+    pub fn set_speed(mut self, class: RoadClass, speed: f64) -> Self {
+        self.speeds[class.index()] = speed;
+        self
+    }
+
+    /// Set the capacity of the roads in `class`, by which the volume is divided in the BPR function.
+    pub fn set_capacity(mut self, class: RoadClass, capacity: f64) -> Self {
+        self.capacities[class.index()] = capacity;
+        self
+    }
+
+    pub fn set_bpr_parameters(self, bpr_alpha: f64, bpr_beta: f64) -> Self {
+        Self {
+            bpr_alpha,
+            bpr_beta,
+            ..self
+        }
+    }
+
+    pub fn simulate(&self, network: &TransportNetwork, seed: u32) -> TrafficFlow {
+        let edges = network
+            .edges()
+            .map(|edge| (edge.start_index, edge.end_index, edge.road_class))
+            .collect::<Vec<_>>();
+        let edge_positions = edges
+            .iter()
+            .enumerate()
+            .map(|(position, (start, end, _))| ((*start.min(end), *start.max(end)), position))
+            .collect::<HashMap<_, _>>();
+        // an edge of zero length takes no time, and an edge of an infinite or invalid time is closed
+        let free_flow_times = edges
+            .iter()
+            .map(|(start, end, class)| {
+                let length = network.get_edge_length(*start, *end);
+                if length > 0.0 {
+                    length / self.speeds[class.index()]
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<_>>();
+        let capacities = edges
+            .iter()
+            .map(|(_, _, class)| self.capacities[class.index()])
+            .collect::<Vec<_>>();
+
+        let (zones, weights) = self.sample_zones(network, seed);
+        let total_weight = weights.iter().sum::<f64>();
+        let pair_weight =
+            total_weight * total_weight - weights.iter().map(|weight| weight * weight).sum::<f64>();
+
+        let travel_time = |position: usize, volume: f64| {
+            let ratio = if capacities[position] > 0.0 {
+                volume / capacities[position]
+            } else {
+                0.0
+            };
+            free_flow_times[position] * (1.0 + self.bpr_alpha * ratio.powf(self.bpr_beta))
+        };
+
+        let mut volumes = vec![0.0; edges.len()];
+        let mut unassigned_demand = 0.0;
+        for iteration in 0..self.iterations.max(1) {
+            let times = (0..edges.len())
+                .map(|position| travel_time(position, volumes[position]))
+                .collect::<Vec<_>>();
+            let mut auxiliary = vec![0.0; edges.len()];
+            unassigned_demand = 0.0;
+            zones.iter().enumerate().for_each(|(origin, origin_index)| {
+                // the fastest paths following the one-way streets
+                let tree = shortest_path_tree(network.num_nodes(), *origin_index, |index| {
+                    network
+                        .get_out_neighbors(index)
+                        .iter()
+                        .map(|neighbor| {
+                            let key = (index.min(neighbor.index), index.max(neighbor.index));
+                            (neighbor.index, times[edge_positions[&key]])
+                        })
+                        .collect::<Vec<_>>()
+                });
+                zones
+                    .iter()
+                    .enumerate()
+                    .filter(|(destination, _)| *destination != origin)
+                    .filter(|_| pair_weight > 0.0)
+                    .for_each(|(destination, destination_index)| {
+                        let demand = self.total_demand * weights[origin] * weights[destination]
+                            / pair_weight;
+                        if tree.distances[*destination_index] == f64::MAX {
+                            unassigned_demand += demand;
+                            return;
+                        }
+                        let mut current = *destination_index;
+                        while let Some(from) = tree.previous[current] {
+                            auxiliary[edge_positions[&(from.min(current), from.max(current))]] +=
+                                demand;
+                            current = from;
+                        }
+                    });
+            });
+            // the method of successive averages
+            let step = 1.0 / (iteration + 1) as f64;
+            volumes
+                .iter_mut()
+                .zip(auxiliary.iter())
+                .for_each(|(volume, auxiliary)| *volume += (auxiliary - *volume) * step);
+        }
+
+        let travel_times = (0..edges.len())
+            .map(|position| travel_time(position, volumes[position]))
+            .collect::<Vec<_>>();
+        TrafficFlow {
+            edges: edges
+                .iter()
+                .map(|(start, end, _)| (*start, *end))
+                .collect::<Vec<_>>(),
+            volumes,
+            travel_times,
+            unassigned_demand,
+        }
+    }
+}
+
+impl TrafficSimulationBuilder {
+    /// Sample the zones among the nodes with roads in proportion to the number of the nodes around them.
+    /// Returns the node indices of the zones and their weights.
+    fn sample_zones(&self, network: &TransportNetwork, seed: u32) -> (Vec<usize>, Vec<f64>) {
+        let nodes = network.nodes();
+        let candidates = (0..nodes.len())
+            .filter(|index| !network.get_neighbors(*index).is_empty())
+            .collect::<Vec<_>>();
+        if candidates.is_empty() || self.num_zones == 0 {
+            return (vec![], vec![]);
+        }
+        let tree = RTree::bulk_load(
+            candidates
+                .iter()
+                .map(|index| [nodes[*index].x, nodes[*index].y])
+                .collect::<Vec<_>>(),
+        );
+        let densities = candidates
+            .iter()
+            .map(|index| {
+                tree.locate_within_distance(
+                    [nodes[*index].x, nodes[*index].y],
+                    self.density_radius * self.density_radius,
+                )
+                .count() as f64
+            })
+            .collect::<Vec<_>>();
+
+        // sample without replacement by the cumulative densities
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let mut remaining = densities.clone();
+        let mut zones = vec![];
+        let mut weights = vec![];
+        for _ in 0..self.num_zones.min(candidates.len()) {
+            let total = remaining.iter().sum::<f64>();
+            if total <= 0.0 {
+                break;
+            }
+            let mut target = rng.gen_range(0.0..total);
+            let position = remaining
+                .iter()
+                .position(|density| {
+                    target -= density;
+                    target < 0.0
+                })
+                .or_else(|| remaining.iter().rposition(|density| *density > 0.0))
+                .unwrap();
+            zones.push(candidates[position]);
+            weights.push(densities[position]);
+            remaining[position] = 0.0;
+        }
+        (zones, weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        transport::{geometry::EdgeGeometries, transport::EdgeAttr},
+        Site2D,
+    };
+
+    /// A road from (0, 0) to (2, 0) through (1, 0), with a node of zero distance at each site.
+    fn network() -> TransportNetwork {
+        let nodes = [0.0, 0.0, 1.0, 1.0, 2.0, 2.0]
+            .iter()
+            .map(|&x| Site2D { x, y: 0.0 })
+            .collect::<Vec<_>>();
+        let edges = (0..nodes.len() - 1).map(|index| (index, index + 1, EdgeAttr::default()));
+        TransportNetwork::from_edges(nodes, edges, EdgeGeometries::default())
+    }
+
+    fn builder() -> TrafficSimulationBuilder {
+        TrafficSimulationBuilder::new()
+            .set_num_zones(6)
+            .set_density_radius(10.0)
+            .set_total_demand(100.0)
+            .set_iterations(3)
+    }
+
+    #[test]
+    fn zero_speed_on_zero_length_edges_does_not_panic() {
+        let flow = builder()
+            .set_speed(RoadClass::Local, 0.0)
+            .simulate(&network(), 0);
+        // only the trips between the nodes on the same site are assigned
+        assert!(flow.get_unassigned_demand() > 0.0);
+        assert!(flow.get_volumes().iter().all(|volume| volume.is_finite()));
+        assert_eq!(flow.get_volume(0, 1), Some(flow.get_volume(4, 5).unwrap()));
+    }
+
+    #[test]
+    fn invalid_speeds_close_the_roads() {
+        for speed in [-1.0, f64::NAN, 0.0] {
+            let flow = builder()
+                .set_speed(RoadClass::Local, speed)
+                .simulate(&network(), 0);
+            assert_eq!(flow.get_volume(1, 2), Some(0.0));
+            assert_eq!(flow.get_volume(3, 4), Some(0.0));
+        }
+    }
+
+    #[test]
+    fn zones_are_sampled_without_replacement() {
+        let network = network();
+        for seed in 0..32 {
+            let (mut zones, weights) = builder().sample_zones(&network, seed);
+            assert_eq!(zones.len(), 6);
+            assert_eq!(weights.len(), 6);
+            zones.sort_unstable();
+            zones.dedup();
+            assert_eq!(zones.len(), 6);
+        }
+    }
+}
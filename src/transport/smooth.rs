@@ -0,0 +1,95 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{chain::extract_chains, transport::TransportNetwork},
+    Site2D,
+};
+
+/// The number of sampled pieces between two consecutive vertices of the original polyline.
+const SMOOTH_SUBDIVISIONS: usize = 8;
+
+/// Sample a cardinal spline through `points`, which passes every original point.
+/// `tension` of 0 gives a Catmull-Rom spline and 1 gives the original straight segments.
+/// The result contains `SMOOTH_SUBDIVISIONS` pieces for every original segment.
+fn sample_cardinal_spline(points: &[Site2D], tension: f64, is_closed: bool) -> Vec<Site2D> {
+    let len = points.len();
+    let point_at = |i: isize| {
+        if is_closed {
+            // the last point repeats the first one
+            points[i.rem_euclid(len as isize - 1) as usize]
+        } else {
+            points[i.clamp(0, len as isize - 1) as usize]
+        }
+    };
+    let tangent_at = |i: isize| {
+        let (prev, next) = (point_at(i - 1), point_at(i + 1));
+        Site2D {
+            x: (1.0 - tension) * (next.x - prev.x) * 0.5,
+            y: (1.0 - tension) * (next.y - prev.y) * 0.5,
+        }
+    };
+
+    let mut samples = vec![points[0]];
+    (0..len - 1).for_each(|i| {
+        let (p0, p1) = (points[i], points[i + 1]);
+        let (m0, m1) = (tangent_at(i as isize), tangent_at(i as isize + 1));
+        (1..=SMOOTH_SUBDIVISIONS).for_each(|step| {
+            let t = step as f64 / SMOOTH_SUBDIVISIONS as f64;
+            let (t2, t3) = (t * t, t * t * t);
+            let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+            let h10 = t3 - 2.0 * t2 + t;
+            let h01 = -2.0 * t3 + 3.0 * t2;
+            let h11 = t3 - t2;
+            samples.push(Site2D {
+                x: h00 * p0.x + h10 * m0.x + h01 * p1.x + h11 * m1.x,
+                y: h00 * p0.y + h10 * m0.y + h01 * p1.y + h11 * m1.y,
+            });
+        });
+    });
+    samples
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Replace the geometry of the roads with curves through their vertices.
+    /// The curves run along the chains of roads in the same class joined at nodes with exactly two roads,
+    /// so the roads do not bend at those nodes.
+    ///
+    /// `tension` of 0 makes the smoothest curves and 1 keeps the straight segments.
+    pub fn smooth(&mut self, tension: f64) {
+        let tension = tension.clamp(0.0, 1.0);
+        [true, false].iter().for_each(|&is_highway| {
+            extract_chains(&self.graph, |attr| {
+                attr.path_attr.is_highway() == is_highway
+            })
+            .iter()
+            .for_each(|chain| {
+                // the position of each node of the chain in the whole polyline
+                let mut polyline = vec![self.nodes[chain[0]]];
+                let mut node_positions = vec![0];
+                chain.windows(2).for_each(|pair| {
+                    polyline.extend(self.get_polyline(pair[0], pair[1]).into_iter().skip(1));
+                    node_positions.push(polyline.len() - 1);
+                });
+                let is_closed = chain.len() > 2 && chain[0] == chain[chain.len() - 1];
+                let samples = sample_cardinal_spline(&polyline, tension, is_closed);
+
+                chain
+                    .windows(2)
+                    .zip(node_positions.windows(2))
+                    .for_each(|(pair, positions)| {
+                        let intermediate = samples[positions[0] * SMOOTH_SUBDIVISIONS + 1
+                            ..positions[1] * SMOOTH_SUBDIVISIONS]
+                            .to_vec();
+                        self.geometries.insert(pair[0], pair[1], intermediate);
+                    });
+            });
+        });
+        self.invalidate_spatial_index();
+        self.update_overlays(
+            self.highway_overlay.tolerance,
+            self.normal_overlay.tolerance,
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use rstar::{
+    primitives::{GeomWithData, Line},
+    PointDistance, RTree, AABB,
+};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        math::get_clipped_range,
+        transport::{Edge, TransportNetwork},
+    },
+    Site2D,
+};
+
+type NodeObject = GeomWithData<[f64; 2], usize>;
+type SegmentObject = GeomWithData<Line<[f64; 2]>, (usize, usize)>;
+
+/// The R-trees of the nodes and of the segments of the edges, derived from the network.
+/// The segments of an edge are keyed by its nodes, the smaller index first.
+#[derive(Clone)]
+pub(crate) struct SpatialIndex {
+    pub(crate) nodes: RTree<NodeObject>,
+    pub(crate) segments: RTree<SegmentObject>,
+}
+
+impl SpatialIndex {
+    fn new(network: &TransportNetwork) -> Self {
+        let nodes = network
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, site)| GeomWithData::new([site.x, site.y], index))
+            .collect::<Vec<_>>();
+        let segments = network
+            .iter_edges()
+            .flat_map(|(start, end, _)| {
+                network
+                    .get_polyline(start, end)
+                    .windows(2)
+                    .map(|pair| {
+                        GeomWithData::new(
+                            Line::new([pair[0].x, pair[0].y], [pair[1].x, pair[1].y]),
+                            (start, end),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        Self {
+            nodes: RTree::bulk_load(nodes),
+            segments: RTree::bulk_load(segments),
+        }
+    }
+}
+
+impl TransportNetwork {
+    /// Get the spatial index, which is kept with the network until it is modified.
+    /// The index is built when a network is finished by the builder, or else on the first query.
+    pub(crate) fn spatial_index(&self) -> &SpatialIndex {
+        self.spatial_index.get_or_init(|| SpatialIndex::new(self))
+    }
+
+    /// Discard the spatial index after modifying the nodes or the edges.
+    pub(crate) fn invalidate_spatial_index(&mut self) {
+        self.spatial_index.take();
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Get the node nearest to (`x`, `y`), or `None` if the network has no nodes.
+    pub fn nearest_node(&self, x: f64, y: f64) -> Option<usize> {
+        self.spatial_index()
+            .nodes
+            .nearest_neighbor(&[x, y])
+            .map(|node| node.data)
+    }
+
+    /// Get the nodes `[start, end]` of the edge nearest to (`x`, `y`), measured to its polyline,
+    /// or `None` if the network has no edges. The smaller index comes first.
+    pub fn nearest_edge(&self, x: f64, y: f64) -> Option<Vec<usize>> {
+        self.spatial_index()
+            .segments
+            .nearest_neighbor(&[x, y])
+            .map(|segment| vec![segment.data.0, segment.data.1])
+    }
+
+    /// Get the node nearest to (`x`, `y`) if it is within `tolerance`, such as the node under the cursor.
+    pub fn pick_node(&self, x: f64, y: f64, tolerance: f64) -> Option<usize> {
+        self.spatial_index()
+            .nodes
+            .nearest_neighbor(&[x, y])
+            .filter(|node| node.distance_2(&[x, y]) <= tolerance * tolerance)
+            .map(|node| node.data)
+    }
+
+    /// Get the nodes `[start, end]` of the edge nearest to (`x`, `y`) if its polyline is within `tolerance`,
+    /// such as the edge under the cursor. The smaller index comes first.
+    pub fn pick_edge(&self, x: f64, y: f64, tolerance: f64) -> Option<Vec<usize>> {
+        self.spatial_index()
+            .segments
+            .nearest_neighbor(&[x, y])
+            .filter(|segment| segment.distance_2(&[x, y]) <= tolerance * tolerance)
+            .map(|segment| vec![segment.data.0, segment.data.1])
+    }
+
+    /// Get the edges whose polylines intersect the rectangle from (`min_x`, `min_y`) to (`max_x`, `max_y`),
+    /// such as the edges visible in a viewport, ordered by the indices of their nodes.
+    pub fn edges_in_bbox(&self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Vec<Edge> {
+        let bound_min = Site2D { x: min_x, y: min_y };
+        let bound_max = Site2D { x: max_x, y: max_y };
+        let mut keys = self
+            .spatial_index()
+            .segments
+            .locate_in_envelope_intersecting(&AABB::from_corners([min_x, min_y], [max_x, max_y]))
+            .filter(|segment| {
+                let [start, end] =
+                    [segment.geom().from, segment.geom().to].map(|[x, y]| Site2D { x, y });
+                get_clipped_range(start, end, bound_min, bound_max).is_some()
+            })
+            .map(|segment| segment.data)
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        keys.into_iter()
+            .map(|(start, end)| self.to_edge(start, end, self.graph.has_edge(start, end).1))
+            .collect::<Vec<_>>()
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Counters of the work done by a growth, for measuring the cost of parameter sets.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct BuildStats {
+    /// The iterations run, each of which takes a path from the heap.
+    pub iterations: usize,
+    /// The candidates of branches evaluated on the terrain.
+    pub candidate_evaluations: usize,
+    /// The queries of the R-tree of the paths for the intersections.
+    pub path_tree_queries: usize,
+    /// The paths committed into the R-tree, including the halves of split paths.
+    pub path_tree_insertions: usize,
+    /// The time taken by `build_with_stats` in milliseconds, or zero for a growth.
+    pub elapsed_ms: f64,
+}
@@ -0,0 +1,78 @@
+use crate::{transport::road_class::RoadClass, Site2D};
+
+/// A branch about to grow from the end of a committed path.
+#[derive(Clone, Copy, Debug)]
+pub struct BranchContext {
+    /// The site which the branch grows from.
+    pub site: Site2D,
+    pub altitude: f64,
+    /// The direction of the committed path which the branch grows from.
+    pub path_angle: f64,
+    /// -1 and 1 for the branches to the sides, and 0 for the straight one.
+    pub turn: i32,
+    pub road_class: RoadClass,
+    pub branch_length: f64,
+    pub iteration: usize,
+}
+
+/// A candidate of a branch, evaluated on the terrain.
+#[derive(Clone, Copy, Debug)]
+pub struct BranchCandidate {
+    pub angle: f64,
+    /// The end of the branch, which is beyond the branch length if it is a bridge.
+    pub site: Site2D,
+    pub altitude: f64,
+    pub is_bridge: bool,
+    /// Whether the branch climbs by a switchback since it is too steep to go straight.
+    pub is_switchback: bool,
+    /// The cost of the path, by which the growth is ordered.
+    pub cost: f64,
+}
+
+/// A path taken from the frontier of the growth, which is about to be connected to the network.
+#[derive(Clone, Copy, Debug)]
+pub struct GrowingPath {
+    pub site_start: Site2D,
+    pub site_end: Site2D,
+    pub road_class: RoadClass,
+    pub is_bridge: bool,
+    pub cost: f64,
+    pub iteration: usize,
+}
+
+/// The decisions of the growth which can be replaced to compare variants of the algorithm,
+/// while the frontier ordered by cost, the connections to the existing paths, the bridges and the switchbacks are shared.
+///
+/// Each method receives the decision of the default algorithm, and the default implementations keep it.
+pub trait GrowthStrategy: Send + Sync {
+    /// Propose the directions in which the candidates of `branch` are evaluated.
+    /// `angles` are those of the default algorithm, around the straight direction by the angle deviation.
+    fn propose_angles(&self, branch: &BranchContext, angles: Vec<f64>) -> Vec<f64> {
+        let _ = branch;
+        angles
+    }
+
+    /// Score `candidate`, and the candidate of the least score is taken as the branch. `None` rejects the candidate.
+    /// `score` is that of the default algorithm, the cost with the attraction to the coast.
+    fn evaluate(
+        &self,
+        branch: &BranchContext,
+        candidate: &BranchCandidate,
+        score: f64,
+    ) -> Option<f64> {
+        let _ = (branch, candidate);
+        Some(score)
+    }
+
+    /// Decide whether `path` is connected to the network. A rejected path is discarded without branches.
+    fn accept(&self, path: &GrowingPath) -> bool {
+        let _ = path;
+        true
+    }
+}
+
+/// The default algorithm, which keeps every decision.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultStrategy;
+
+impl GrowthStrategy for DefaultStrategy {}
@@ -0,0 +1,236 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Classes of the edges which are drawn differently.
+/// Bridges are drawn in their own class regardless of the road class.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StyleClass {
+    Normal,
+    Highway,
+    Bridge,
+}
+
+const STYLE_CLASSES: [StyleClass; 3] =
+    [StyleClass::Normal, StyleClass::Highway, StyleClass::Bridge];
+
+impl StyleClass {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            StyleClass::Normal => "normal",
+            StyleClass::Highway => "highway",
+            StyleClass::Bridge => "bridge",
+        }
+    }
+
+    fn filter(self) -> &'static str {
+        match self {
+            StyleClass::Normal => {
+                "[\"all\",[\"==\",[\"get\",\"is_highway\"],false],[\"==\",[\"get\",\"is_bridge\"],false]]"
+            }
+            StyleClass::Highway => {
+                "[\"all\",[\"==\",[\"get\",\"is_highway\"],true],[\"==\",[\"get\",\"is_bridge\"],false]]"
+            }
+            StyleClass::Bridge => "[\"==\",[\"get\",\"is_bridge\"],true]",
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ClassStyle {
+    color: String,
+    width: f64,
+    casing_color: String,
+    casing_width: f64,
+    z_order: i32,
+}
+
+/// Default cartography of the network.
+/// The casing is drawn under the line, `casing_width` wider on each side.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct RoadStyle {
+    normal: ClassStyle,
+    highway: ClassStyle,
+    bridge: ClassStyle,
+}
+
+impl Default for RoadStyle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RoadStyle {
+    fn get(&self, class: StyleClass) -> &ClassStyle {
+        match class {
+            StyleClass::Normal => &self.normal,
+            StyleClass::Highway => &self.highway,
+            StyleClass::Bridge => &self.bridge,
+        }
+    }
+
+    fn set(self, class: StyleClass, f: impl FnOnce(ClassStyle) -> ClassStyle) -> Self {
+        match class {
+            StyleClass::Normal => Self {
+                normal: f(self.normal.clone()),
+                ..self
+            },
+            StyleClass::Highway => Self {
+                highway: f(self.highway.clone()),
+                ..self
+            },
+            StyleClass::Bridge => Self {
+                bridge: f(self.bridge.clone()),
+                ..self
+            },
+        }
+    }
+
+    pub(crate) fn classes_by_z_order(&self) -> Vec<StyleClass> {
+        let mut classes = STYLE_CLASSES.to_vec();
+        classes.sort_by_key(|class| self.get(*class).z_order);
+        classes
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl RoadStyle {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            normal: ClassStyle {
+                color: "rgba(50, 50, 50, 0.7)".to_string(),
+                width: 0.5,
+                casing_color: "rgba(255, 255, 255, 0.7)".to_string(),
+                casing_width: 0.0,
+                z_order: 0,
+            },
+            highway: ClassStyle {
+                color: "rgba(50, 50, 50, 0.7)".to_string(),
+                width: 2.0,
+                casing_color: "rgba(255, 255, 255, 0.7)".to_string(),
+                casing_width: 0.5,
+                z_order: 1,
+            },
+            bridge: ClassStyle {
+                color: "rgba(50, 50, 50, 0.9)".to_string(),
+                width: 2.0,
+                casing_color: "rgba(30, 30, 30, 0.9)".to_string(),
+                casing_width: 1.0,
+                z_order: 2,
+            },
+        }
+    }
+
+    pub fn set_color(self, class: StyleClass, color: String) -> Self {
+        self.set(class, |style| ClassStyle { color, ..style })
+    }
+
+    pub fn set_width(self, class: StyleClass, width: f64) -> Self {
+        self.set(class, |style| ClassStyle { width, ..style })
+    }
+
+    pub fn set_casing(self, class: StyleClass, casing_color: String, casing_width: f64) -> Self {
+        self.set(class, |style| ClassStyle {
+            casing_color,
+            casing_width,
+            ..style
+        })
+    }
+
+    pub fn set_z_order(self, class: StyleClass, z_order: i32) -> Self {
+        self.set(class, |style| ClassStyle { z_order, ..style })
+    }
+
+    pub fn get_class(is_highway: bool, is_bridge: bool) -> StyleClass {
+        if is_bridge {
+            StyleClass::Bridge
+        } else if is_highway {
+            StyleClass::Highway
+        } else {
+            StyleClass::Normal
+        }
+    }
+
+    pub fn get_color(&self, class: StyleClass) -> String {
+        self.get(class).color.clone()
+    }
+
+    pub fn get_width(&self, class: StyleClass) -> f64 {
+        self.get(class).width
+    }
+
+    pub fn get_casing_color(&self, class: StyleClass) -> String {
+        self.get(class).casing_color.clone()
+    }
+
+    pub fn get_casing_width(&self, class: StyleClass) -> f64 {
+        self.get(class).casing_width
+    }
+
+    pub fn get_z_order(&self, class: StyleClass) -> i32 {
+        self.get(class).z_order
+    }
+
+    /// Export the style as a JSON array of MapLibre line layers for `source`.
+    /// The features are expected to have `is_highway` and `is_bridge` properties.
+    /// All the casings are placed under all the lines.
+    pub fn to_maplibre_layers(&self, source: &str) -> String {
+        let classes = self.classes_by_z_order();
+        let casings = classes
+            .iter()
+            .filter(|class| self.get(**class).casing_width > 0.0)
+            .map(|class| {
+                let style = self.get(*class);
+                format!(
+                    "{{\"id\":\"{}-casing\",\"type\":\"line\",\"source\":\"{}\",\"filter\":{},\"paint\":{{\"line-color\":\"{}\",\"line-width\":{}}}}}",
+                    class.name(),
+                    source,
+                    class.filter(),
+                    style.casing_color,
+                    style.width + style.casing_width * 2.0
+                )
+            });
+        let lines = classes.iter().map(|class| {
+            let style = self.get(*class);
+            format!(
+                "{{\"id\":\"{}\",\"type\":\"line\",\"source\":\"{}\",\"filter\":{},\"paint\":{{\"line-color\":\"{}\",\"line-width\":{}}}}}",
+                class.name(),
+                source,
+                class.filter(),
+                style.color,
+                style.width
+            )
+        });
+        format!("[{}]", casings.chain(lines).collect::<Vec<_>>().join(","))
+    }
+
+    /// Export the style as CSS for SVG paths with the classes `road-<class>` and `road-<class>-casing`.
+    /// The rules are ordered by the z-order in which the paths should be appended.
+    pub fn to_svg_css(&self) -> String {
+        let classes = self.classes_by_z_order();
+        let casings = classes
+            .iter()
+            .filter(|class| self.get(**class).casing_width > 0.0)
+            .map(|class| {
+                let style = self.get(*class);
+                format!(
+                    ".road-{}-casing {{ fill: none; stroke: {}; stroke-width: {}; stroke-linecap: round; }}",
+                    class.name(),
+                    style.casing_color,
+                    style.width + style.casing_width * 2.0
+                )
+            });
+        let lines = classes.iter().map(|class| {
+            let style = self.get(*class);
+            format!(
+                ".road-{} {{ fill: none; stroke: {}; stroke-width: {}; stroke-linecap: round; }}",
+                class.name(),
+                style.color,
+                style.width
+            )
+        });
+        casings.chain(lines).collect::<Vec<_>>().join("\n")
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{density::DensityMap, terrain::Terrain, Site2D};
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum BasisKind {
+    /// The major direction is `angle` everywhere.
+    Grid { angle: f64 },
+    /// The major direction points away from `center`, so the minor one goes around it.
+    Radial { center: Site2D },
+    /// The major direction is the steepest direction of the terrain, so the minor one follows the contours.
+    TerrainGradient,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct BasisField {
+    kind: BasisKind,
+    weight: f64,
+    weight_map: Option<DensityMap>,
+}
+
+/// A field of road orientations blended from basis fields, after Chen et al., "Interactive Procedural Street Modeling".
+///
+/// Each basis field gives a direction at every site, weighted by a constant and optionally by a map.
+/// The directions are blended as symmetric tensors, so opposite directions are the same orientation,
+/// and the roads follow the major direction of the blend or the minor one across it.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct TensorField {
+    fields: Vec<BasisField>,
+}
+
+impl TensorField {
+    fn add(self, kind: BasisKind, weight: f64) -> Self {
+        let mut fields = self.fields;
+        fields.push(BasisField {
+            kind,
+            weight,
+            weight_map: None,
+        });
+        Self { fields }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TensorField {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self { fields: vec![] }
+    }
+
+    /// Add a grid aligned to `angle` in radians.
+    pub fn add_grid(self, angle: f64, weight: f64) -> Self {
+        self.add(BasisKind::Grid { angle }, weight)
+    }
+
+    /// Add radial roads from (`center_x`, `center_y`) and ring roads around it.
+    pub fn add_radial(self, center_x: f64, center_y: f64, weight: f64) -> Self {
+        self.add(
+            BasisKind::Radial {
+                center: Site2D {
+                    x: center_x,
+                    y: center_y,
+                },
+            },
+            weight,
+        )
+    }
+
+    /// Add roads climbing straight up the slopes of the terrain and roads along its contours.
+    pub fn add_terrain_gradient(self, weight: f64) -> Self {
+        self.add(BasisKind::TerrainGradient, weight)
+    }
+
+    /// Scale the weight of the last added basis field by `weight_map`, so that the basis fields can be blended by area.
+    pub fn set_weight_map(self, weight_map: DensityMap) -> Self {
+        let mut fields = self.fields;
+        if let Some(field) = fields.last_mut() {
+            field.weight_map = Some(weight_map);
+        }
+        Self { fields }
+    }
+
+    /// Get the major direction at (`x`, `y`) in radians within `[-PI / 2, PI / 2]`,
+    /// or `None` if no basis field gives a direction there, such as where the weights cancel out.
+    pub fn get_angle(&self, x: f64, y: f64, terrain: &Terrain) -> Option<f64> {
+        let (mut sum_cos, mut sum_sin) = (0.0, 0.0);
+        self.fields.iter().for_each(|field| {
+            let weight = field.weight
+                * field
+                    .weight_map
+                    .as_ref()
+                    .map_or(1.0, |weight_map| weight_map.sample(x, y));
+            let angle = match field.kind {
+                BasisKind::Grid { angle } => Some(angle),
+                BasisKind::Radial { center } => (center.x != x || center.y != y)
+                    .then(|| libm::atan2(y - center.y, x - center.x)),
+                BasisKind::TerrainGradient => terrain
+                    .get_gradient(x, y)
+                    .filter(|gradient| gradient.slope > 0.0)
+                    .map(|gradient| gradient.direction),
+            };
+            if let Some(angle) = angle {
+                // the tensor of an orientation turns twice as fast as the orientation
+                sum_cos += weight * (2.0 * angle).cos();
+                sum_sin += weight * (2.0 * angle).sin();
+            }
+        });
+        if sum_cos.hypot(sum_sin) <= f64::EPSILON {
+            return None;
+        }
+        Some(libm::atan2(sum_sin, sum_cos) * 0.5)
+    }
+}
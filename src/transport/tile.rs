@@ -0,0 +1,190 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    config::derive_seed,
+    terrain::Terrain,
+    transport::{
+        error::TransportError,
+        geometry::EdgeGeometries,
+        math::get_distance,
+        oneway::OneWay,
+        road_class::RoadClass,
+        transport::{EdgeAttr, PathAttr, TransportNetwork, TransportNetworkBuilder},
+    },
+};
+
+/// Generates the network one square tile at a time.
+/// Each tile grows from its center within its bounds, with a seed derived from the position of the tile,
+/// so any tile is generated identically regardless of which tiles were generated before.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct TileBuilder {
+    network_builder: TransportNetworkBuilder,
+    tile_size: f64,
+    seed: u64,
+}
+
+impl Default for TileBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TileBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            network_builder: TransportNetworkBuilder::new(),
+            tile_size: 0.0,
+            seed: 0,
+        }
+    }
+
+    /// Set the builder used for growing each tile. The start and the bounds of the builder are replaced by the tile.
+    pub fn set_network_builder(self, network_builder: TransportNetworkBuilder) -> Self {
+        Self {
+            network_builder,
+            ..self
+        }
+    }
+
+    /// Set the length of the sides of the tiles. The tile (0, 0) spans from the origin to (`tile_size`, `tile_size`).
+    pub fn set_tile_size(self, tile_size: f64) -> Self {
+        Self { tile_size, ..self }
+    }
+
+    pub fn set_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    pub fn get_tile_seed(&self, tile_x: i32, tile_y: i32) -> u64 {
+        derive_seed(
+            derive_seed(self.seed, tile_x as u32 as u64),
+            tile_y as u32 as u64,
+        )
+    }
+
+    /// Grow the network of the tile at (`tile_x`, `tile_y`).
+    /// Nothing grows if the center of the tile is not on the land.
+    pub fn build_tile(
+        &self,
+        tile_x: i32,
+        tile_y: i32,
+        terrain: &Terrain,
+    ) -> Result<TransportNetwork, TransportError> {
+        let (min_x, min_y) = (
+            tile_x as f64 * self.tile_size,
+            tile_y as f64 * self.tile_size,
+        );
+        let (max_x, max_y) = (min_x + self.tile_size, min_y + self.tile_size);
+        let mut network_builder = self
+            .network_builder
+            .clone()
+            .set_start((min_x + max_x) * 0.5, (min_y + max_y) * 0.5)
+            .set_bounds(min_x, min_y, max_x, max_y);
+        network_builder.additional_starts.clear();
+        match network_builder.build_u64(self.get_tile_seed(tile_x, tile_y), terrain) {
+            Err(TransportError::StartOutsideTerrain) => Ok(TransportNetwork::from_edges(
+                vec![],
+                vec![],
+                EdgeGeometries::default(),
+            )),
+            result => result,
+        }
+    }
+}
+
+/// A node is stitched only if it has fewer roads than this, so that it becomes at most a crossroad.
+const STITCH_MAX_DEGREE: usize = 4;
+
+/// Get the nodes which can be stitched with the highest class of the roads leading to each of them and their mean cost.
+pub(crate) fn stitchable_nodes(network: &TransportNetwork) -> Vec<(usize, RoadClass, f64)> {
+    (0..network.nodes.len())
+        .filter_map(|index| {
+            let neighbors = network.graph.neighbors_of(index);
+            if neighbors.is_empty() || neighbors.len() >= STITCH_MAX_DEGREE {
+                return None;
+            }
+            let road_class = neighbors
+                .iter()
+                .map(|n| n.1.path_attr.road_class)
+                .min()
+                .unwrap();
+            let cost = neighbors.iter().map(|n| n.1.cost).sum::<f64>() / neighbors.len() as f64;
+            Some((index, road_class, cost))
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Get the attributes of the road stitching two nodes, given the highest class of the roads leading to each of them and their mean cost.
+/// The road takes the lower of the two classes and the mean of the two costs.
+pub(crate) fn stitch_attr(node_a: (RoadClass, f64), node_b: (RoadClass, f64)) -> EdgeAttr {
+    EdgeAttr {
+        path_attr: PathAttr {
+            road_class: node_a.0.max(node_b.0),
+            is_even: false,
+            is_bridge: false,
+        },
+        cost: (node_a.1 + node_b.1) * 0.5,
+        one_way: OneWay::TwoWay,
+        iteration: None,
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Join the network with the network of a neighboring tile.
+    ///
+    /// The roads of a tile end short of its borders, so the nodes of both networks within `distance` of each other
+    /// are connected, closest pairs first. Each node is connected at most once, and nodes with four or more roads are not.
+    /// The connection takes the lower of the highest classes of the roads leading to the two nodes.
+    /// The node indices of `neighbor` are shifted by the number of the nodes of this network.
+    pub fn stitch(&self, neighbor: &TransportNetwork, distance: f64) -> TransportNetwork {
+        let (merged, offsets) = TransportNetwork::merge(&[self.clone(), neighbor.clone()]);
+        let (highway_tolerance, normal_tolerance) = (
+            merged.highway_overlay.tolerance,
+            merged.normal_overlay.tolerance,
+        );
+        let mut edges = merged.iter_edges().collect::<Vec<_>>();
+        let TransportNetwork {
+            nodes, geometries, ..
+        } = merged;
+
+        let (nodes_a, nodes_b) = (stitchable_nodes(self), stitchable_nodes(neighbor));
+        let mut pairs = nodes_a
+            .iter()
+            .flat_map(|node_a| {
+                nodes_b.iter().filter_map(move |node_b| {
+                    let length = get_distance(self.nodes[node_a.0], neighbor.nodes[node_b.0]);
+                    (length <= distance).then_some((length, *node_a, *node_b))
+                })
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let (mut used_a, mut used_b) = (
+            vec![false; self.nodes.len()],
+            vec![false; neighbor.nodes.len()],
+        );
+        pairs.iter().for_each(
+            |(_, (index_a, class_a, cost_a), (index_b, class_b, cost_b))| {
+                if used_a[*index_a] || used_b[*index_b] {
+                    return;
+                }
+                used_a[*index_a] = true;
+                used_b[*index_b] = true;
+                edges.push((
+                    *index_a + offsets[0],
+                    *index_b + offsets[1],
+                    stitch_attr((*class_a, *cost_a), (*class_b, *cost_b)),
+                ));
+            },
+        );
+
+        let mut network = TransportNetwork::from_edges(nodes, edges, geometries);
+        network.update_overlays(highway_tolerance, normal_tolerance);
+        network
+    }
+}
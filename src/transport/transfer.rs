@@ -0,0 +1,325 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    transport::{
+        geometry::EdgeGeometries,
+        oneway::OneWay,
+        road_class::ROAD_CLASSES,
+        transport::{EdgeAttr, PathAttr, TransportNetwork},
+    },
+    Site2D,
+};
+
+pub(crate) const EDGE_FLAG_HIGHWAY: u32 = 1;
+pub(crate) const EDGE_FLAG_EVEN: u32 = 1 << 1;
+pub(crate) const EDGE_FLAG_BRIDGE: u32 = 1 << 2;
+pub(crate) const EDGE_FLAG_CLASS_SHIFT: u32 = 3;
+const EDGE_FLAG_ONE_WAY_SHIFT: u32 = 5;
+
+const TRANSFERABLE_MAGIC: &[u8; 4] = b"TGTN";
+const TRANSFERABLE_VERSION: u32 = 2;
+const TRANSFERABLE_HEADER_LENGTH: usize = 40;
+/// The iteration of the edges which were not grown.
+const NOT_GROWN: u32 = u32::MAX;
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_f64(bytes: &[u8], offset: usize) -> Option<f64> {
+    Some(f64::from_le_bytes(
+        bytes.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+/// Read a float of `float_size` bytes, which is 8 for f64 or 4 for f32.
+fn read_float(bytes: &[u8], offset: usize, float_size: usize) -> Option<f64> {
+    if float_size == 4 {
+        Some(f32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?) as f64)
+    } else {
+        read_f64(bytes, offset)
+    }
+}
+
+/// Flat buffers which can be moved between web workers as transferable objects.
+///
+/// The network keeps f64 for the growth and its own accessors such as `get_edges` and `get_site`.
+/// The f32 output, which halves the memory and the size to upload for very large networks,
+/// is only available from these buffers: `export_sites_f32`, `export_costs_f32` and `to_transferable`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TransportNetwork {
+    /// Export the sites as `[x0, y0, x1, y1, ...]`.
+    pub fn export_sites(&self) -> Vec<f64> {
+        self.nodes
+            .iter()
+            .flat_map(|site| [site.x, site.y])
+            .collect::<Vec<_>>()
+    }
+
+    /// Export the sites as `export_sites` in f32.
+    pub fn export_sites_f32(&self) -> Vec<f32> {
+        self.nodes
+            .iter()
+            .flat_map(|site| [site.x as f32, site.y as f32])
+            .collect::<Vec<_>>()
+    }
+
+    /// Export the cost of each edge in f32, in the order of `export_edges`.
+    pub fn export_costs_f32(&self) -> Vec<f32> {
+        self.iter_edges()
+            .map(|(_, _, attr)| attr.cost as f32)
+            .collect::<Vec<_>>()
+    }
+
+    /// Export each undirected edge once as `[start0, end0, flags0, start1, end1, flags1, ...]`.
+    /// The bit 0 of the flags is set for highways, the bit 1 for even paths and the bit 2 for bridges.
+    /// The bits 3 and 4 hold the index of the road class from 0 for motorways to 3 for local roads.
+    pub fn export_edges(&self) -> Vec<u32> {
+        self.iter_edges()
+            .flat_map(|(start, end, attr)| [start as u32, end as u32, attr.path_attr.to_flags()])
+            .collect::<Vec<_>>()
+    }
+
+    /// Write the network into a single buffer of typed sections, which can be viewed in place by typed arrays
+    /// and transferred between a worker and the main thread without copying.
+    ///
+    /// All the values are little-endian. The header takes 40 bytes:
+    /// the magic `TGTN`, the version (u32), the numbers of the nodes `n`, the edges `e`
+    /// and the intermediate vertices of the polylines `v` (u32 each), the size of the floats in bytes (u32),
+    /// and the tolerances of the highway and the normal overlays (f64 each).
+    /// The floats are f64 of 8 bytes, or f32 of 4 bytes with `f32_output`,
+    /// which halves the size of the buffer for very large networks while the network itself keeps f64.
+    /// The sections follow without gaps, the float ones first so that every section is aligned to its type:
+    ///
+    /// - `Float64Array(2n)` or `Float32Array(2n)`: the nodes as `[x0, y0, x1, y1, ...]`
+    /// - `Float64Array(e)` or `Float32Array(e)`: the cost of each edge
+    /// - `Float64Array(2v)` or `Float32Array(2v)`: the intermediate vertices of all the polylines as `[x0, y0, x1, y1, ...]`
+    /// - `Uint32Array(3e)`: the edges as `export_edges`, whose flags also hold in the bits 5 and 6
+    ///   0 for two-way roads and 1 or 2 for one-way roads from the end with the smaller or the larger coordinates
+    /// - `Uint32Array(e + 1)`: the offsets of the polylines, so that the vertices of the edge `i`
+    ///   from its start to its end are the vertices from `offsets[i]` to `offsets[i + 1]`
+    /// - `Uint32Array(e)`: the iteration in which each edge was grown, or `0xffffffff` if it was not grown
+    ///
+    /// The generation log and the build report are not included.
+    pub fn to_transferable(&self, f32_output: bool) -> Vec<u8> {
+        let edges = self.iter_edges().collect::<Vec<_>>();
+        let polylines = edges
+            .iter()
+            .map(|(start, end, _)| self.geometries.get(*start, *end))
+            .collect::<Vec<_>>();
+        let num_vertices = polylines
+            .iter()
+            .map(|polyline| polyline.len())
+            .sum::<usize>();
+
+        let float_size = if f32_output { 4 } else { 8 };
+        let mut bytes = TRANSFERABLE_MAGIC.to_vec();
+        let write_float = |bytes: &mut Vec<u8>, value: f64| {
+            if f32_output {
+                bytes.extend((value as f32).to_le_bytes());
+            } else {
+                bytes.extend(value.to_le_bytes());
+            }
+        };
+        [
+            TRANSFERABLE_VERSION,
+            self.nodes.len() as u32,
+            edges.len() as u32,
+            num_vertices as u32,
+            float_size,
+        ]
+        .iter()
+        .for_each(|value| bytes.extend(value.to_le_bytes()));
+        bytes.extend(self.highway_overlay.tolerance.to_le_bytes());
+        bytes.extend(self.normal_overlay.tolerance.to_le_bytes());
+
+        self.nodes.iter().for_each(|site| {
+            [site.x, site.y]
+                .iter()
+                .for_each(|value| write_float(&mut bytes, *value))
+        });
+        edges
+            .iter()
+            .for_each(|(_, _, attr)| write_float(&mut bytes, attr.cost));
+        polylines.iter().flatten().for_each(|site| {
+            [site.x, site.y]
+                .iter()
+                .for_each(|value| write_float(&mut bytes, *value))
+        });
+        edges.iter().for_each(|(start, end, attr)| {
+            let one_way = match attr.one_way {
+                OneWay::TwoWay => 0,
+                OneWay::Ascending => 1,
+                OneWay::Descending => 2,
+            };
+            let flags = attr.path_attr.to_flags() | one_way << EDGE_FLAG_ONE_WAY_SHIFT;
+            [*start as u32, *end as u32, flags]
+                .iter()
+                .for_each(|value| bytes.extend(value.to_le_bytes()));
+        });
+        let mut offset = 0;
+        bytes.extend(0u32.to_le_bytes());
+        polylines.iter().for_each(|polyline| {
+            offset += polyline.len() as u32;
+            bytes.extend(offset.to_le_bytes());
+        });
+        edges.iter().for_each(|(_, _, attr)| {
+            let iteration = attr
+                .iteration
+                .map_or(NOT_GROWN, |iteration| iteration as u32);
+            bytes.extend(iteration.to_le_bytes());
+        });
+        bytes
+    }
+
+    /// Load a network written by `to_transferable`.
+    /// Returns `None` if the buffer is broken or written in an unsupported version.
+    pub fn from_transferable(bytes: &[u8]) -> Option<TransportNetwork> {
+        if bytes.get(0..4)? != TRANSFERABLE_MAGIC || read_u32(bytes, 4)? != TRANSFERABLE_VERSION {
+            return None;
+        }
+        let num_nodes = read_u32(bytes, 8)? as usize;
+        let num_edges = read_u32(bytes, 12)? as usize;
+        let num_vertices = read_u32(bytes, 16)? as usize;
+        let float_size = read_u32(bytes, 20)? as usize;
+        if float_size != 8 && float_size != 4 {
+            return None;
+        }
+        let highway_tolerance = read_f64(bytes, 24)?;
+        let normal_tolerance = read_f64(bytes, 32)?;
+
+        // the counts are untrusted, so the offsets are checked not to overflow on 32-bit targets
+        let after =
+            |offset: usize, count: usize, size: usize| offset.checked_add(count.checked_mul(size)?);
+        let nodes_offset = TRANSFERABLE_HEADER_LENGTH;
+        let costs_offset = after(nodes_offset, num_nodes, float_size * 2)?;
+        let vertices_offset = after(costs_offset, num_edges, float_size)?;
+        let edges_offset = after(vertices_offset, num_vertices, float_size * 2)?;
+        let polyline_offsets_offset = after(edges_offset, num_edges, 12)?;
+        let iterations_offset = after(polyline_offsets_offset, num_edges.checked_add(1)?, 4)?;
+        if bytes.len() != after(iterations_offset, num_edges, 4)? {
+            return None;
+        }
+
+        let read_site = |offset: usize| {
+            Some(Site2D {
+                x: read_float(bytes, offset, float_size)?,
+                y: read_float(bytes, offset + float_size, float_size)?,
+            })
+        };
+        let nodes = (0..num_nodes)
+            .map(|i| read_site(nodes_offset + i * float_size * 2))
+            .collect::<Option<Vec<_>>>()?;
+        let mut geometries = EdgeGeometries::default();
+        let edges = (0..num_edges)
+            .map(|i| {
+                let edge_offset = edges_offset + i * 12;
+                let start = read_u32(bytes, edge_offset)? as usize;
+                let end = read_u32(bytes, edge_offset + 4)? as usize;
+                let flags = read_u32(bytes, edge_offset + 8)?;
+                if start >= num_nodes || end >= num_nodes {
+                    return None;
+                }
+                let one_way = match flags >> EDGE_FLAG_ONE_WAY_SHIFT & 0b11 {
+                    0 => OneWay::TwoWay,
+                    1 => OneWay::Ascending,
+                    2 => OneWay::Descending,
+                    _ => return None,
+                };
+                let vertex_start = read_u32(bytes, polyline_offsets_offset + i * 4)? as usize;
+                let vertex_end = read_u32(bytes, polyline_offsets_offset + i * 4 + 4)? as usize;
+                if vertex_start > vertex_end || vertex_end > num_vertices {
+                    return None;
+                }
+                let intermediate = (vertex_start..vertex_end)
+                    .map(|vertex| read_site(vertices_offset + vertex * float_size * 2))
+                    .collect::<Option<Vec<_>>>()?;
+                if !intermediate.is_empty() {
+                    geometries.insert(start, end, intermediate);
+                }
+                let iteration = read_u32(bytes, iterations_offset + i * 4)?;
+                Some((
+                    start,
+                    end,
+                    EdgeAttr {
+                        path_attr: PathAttr::from_flags(flags),
+                        cost: read_float(bytes, costs_offset + i * float_size, float_size)?,
+                        one_way,
+                        iteration: (iteration != NOT_GROWN).then_some(iteration as usize),
+                    },
+                ))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut network = TransportNetwork::from_edges(nodes, edges, geometries);
+        network.update_overlays(highway_tolerance, normal_tolerance);
+        Some(network)
+    }
+}
+
+impl PathAttr {
+    pub(crate) fn from_flags(flags: u32) -> Self {
+        Self {
+            road_class: ROAD_CLASSES[(flags >> EDGE_FLAG_CLASS_SHIFT & 0b11) as usize],
+            is_even: flags & EDGE_FLAG_EVEN != 0,
+            is_bridge: flags & EDGE_FLAG_BRIDGE != 0,
+        }
+    }
+
+    pub(crate) fn to_flags(self) -> u32 {
+        let mut flags = 0;
+        if self.is_highway() {
+            flags |= EDGE_FLAG_HIGHWAY;
+        }
+        if self.is_even {
+            flags |= EDGE_FLAG_EVEN;
+        }
+        if self.is_bridge {
+            flags |= EDGE_FLAG_BRIDGE;
+        }
+        flags | (self.road_class.index() as u32) << EDGE_FLAG_CLASS_SHIFT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a header of the counts followed by `body`.
+    fn forge(num_nodes: u32, num_edges: u32, num_vertices: u32, body: &[u8]) -> Vec<u8> {
+        let mut bytes = TRANSFERABLE_MAGIC.to_vec();
+        [TRANSFERABLE_VERSION, num_nodes, num_edges, num_vertices, 8]
+            .iter()
+            .for_each(|value| bytes.extend(value.to_le_bytes()));
+        bytes.extend(1.0f64.to_le_bytes());
+        bytes.extend(1.0f64.to_le_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    #[test]
+    fn forged_counts_are_rejected() {
+        let network = TransportNetwork::from_edges(
+            vec![Site2D { x: 0.0, y: 0.0 }, Site2D { x: 1.0, y: 0.0 }],
+            vec![(0, 1, EdgeAttr::default())],
+            EdgeGeometries::default(),
+        );
+        let bytes = network.to_transferable(false);
+        let loaded = TransportNetwork::from_transferable(&bytes).unwrap();
+        assert_eq!(loaded.export_edges(), network.export_edges());
+        assert!(TransportNetwork::from_transferable(&bytes[..bytes.len() - 1]).is_none());
+
+        for counts in [
+            (u32::MAX, 0, 0),
+            (0, u32::MAX, 0),
+            (0, 0, u32::MAX),
+            (u32::MAX, u32::MAX, u32::MAX),
+        ] {
+            let forged = forge(counts.0, counts.1, counts.2, &[0; 64]);
+            assert!(TransportNetwork::from_transferable(&forged).is_none());
+        }
+    }
+}
@@ -1,82 +1,204 @@
-use std::collections::BinaryHeap;
+use std::sync::{Arc, OnceLock};
 
 use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use terrain_graph::edge_attributed_undirected::EdgeAttributedUndirectedGraph;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 use crate::{
-    terrain::Terrain,
+    biome::{Biome, BiomeMap},
+    density::DensityMap,
+    terrain::{Terrain, SEA_LEVEL},
     transport::{
-        math::get_cross,
-        treeobj::{PathTree, PathTreeQuery},
+        altitude::AltitudeCache,
+        error::TransportError,
+        geometry::EdgeGeometries,
+        growth::{now_ms, TransportGrowth},
+        log::GenerationLog,
+        math::{get_cross, get_distance, is_inside_polygon},
+        oneway::OneWay,
+        overlay::Overlay,
+        report::{BuildReport, BuildWarning, MAX_CHECK_TIMES},
+        road_class::{ClassParameters, RoadClass, ROAD_CLASSES},
+        serialize::SerializedNetwork,
+        spatial::SpatialIndex,
+        stats::BuildStats,
+        strategy::{DefaultStrategy, GrowthStrategy},
+        tensor::TensorField,
     },
     Site2D,
 };
 
-static SEA_LEVEL: f64 = 1e-3;
-
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub(crate) struct PathAttr {
-    is_highway: bool,
-    is_even: bool,
+    pub(crate) road_class: RoadClass,
+    pub(crate) is_even: bool,
+    pub(crate) is_bridge: bool,
+}
+
+impl PathAttr {
+    pub(crate) fn is_highway(self) -> bool {
+        self.road_class != RoadClass::Local
+    }
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub(crate) struct EdgeAttr {
+    pub(crate) path_attr: PathAttr,
+    pub(crate) cost: f64,
+    pub(crate) one_way: OneWay,
+    /// The iteration of the growth in which the path of the edge was committed, or `None` if it was not grown.
+    pub(crate) iteration: Option<usize>,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Deserialize)]
+#[serde(try_from = "SerializedNetwork")]
 pub struct TransportNetwork {
-    nodes: Vec<Site2D>,
-    graph: EdgeAttributedUndirectedGraph<PathAttr>,
+    pub(crate) nodes: Vec<Site2D>,
+    pub(crate) graph: EdgeAttributedUndirectedGraph<EdgeAttr>,
+    pub(crate) geometries: EdgeGeometries,
+    pub(crate) highway_overlay: Overlay,
+    pub(crate) normal_overlay: Overlay,
+    pub(crate) log: GenerationLog,
+    pub(crate) report: BuildReport,
+    pub(crate) spatial_index: OnceLock<SpatialIndex>,
 }
 
-#[wasm_bindgen]
+/// How the normal branches are directed.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum BranchPattern {
+    /// Branches choose the cheapest direction within the angle deviation.
+    Organic,
+    /// Branches are aligned to the highway they originate from, forming a rectangular grid.
+    Grid,
+    /// Branches are aligned to the direction from the nearest origin, forming radial and ring roads.
+    Radial,
+    /// Branches are aligned to the field of `set_tensor_field`, or grow organically without it.
+    TensorField,
+}
+
+/// Which connection is tried first when a new path comes close to an existing path.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum SnapPreference {
+    /// Connect to an end of the nearest path if it is within the look-ahead distance,
+    /// otherwise split the nearest path at the crossing.
+    SitesFirst,
+    /// Always split the nearest path at the crossing. Paths which do not reach it are discarded.
+    PathsFirst,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TransportNetworkBuilder {
-    start: Site2D,
-    branch_length: f64,
-    branch_angle_deviation: f64,
+    pub(crate) start: Site2D,
+    pub(crate) additional_starts: Vec<Site2D>,
+    pub(crate) branch_length: f64,
+    pub(crate) branch_angle_deviation: f64,
     branch_max_angle: f64,
     highway_rotation_probability: f64,
     normal_rotation_probability: f64,
     highway_construction_priority: f64,
-    even_path_length_weight: f64,
-    highway_path_length_weight: f64,
-    iterations: usize,
-}
-struct Path {
-    start: usize,
-    end: usize,
-    angle: f64,
-    cost: f64,
-    path_attr: PathAttr,
+    pub(crate) even_path_length_weight: f64,
+    pub(crate) highway_path_length_weight: f64,
+    pub(crate) highway_overlay_tolerance: f64,
+    pub(crate) normal_overlay_tolerance: f64,
+    bridge_max_length: f64,
+    bridge_cost_multiplier: f64,
+    #[serde(with = "unbounded")]
+    max_grade: f64,
+    pub(crate) one_way_fraction: f64,
+    class_parameters: [Option<ClassParameters>; 4],
+    bounds: Option<(Site2D, Site2D)>,
+    boundary_polygon: Vec<Site2D>,
+    // the origins of the other networks of a parallel build, which take the sites nearer to them
+    #[serde(skip)]
+    pub(crate) other_origins: Vec<Site2D>,
+    forbidden_areas: Vec<Vec<Site2D>>,
+    pub(crate) existing_sites: Vec<Site2D>,
+    pub(crate) existing_edges: Vec<(usize, usize, PathAttr, f64)>,
+    pub(crate) highway_iterations: Option<usize>,
+    pub(crate) record_log: bool,
+    pub(crate) merge_distance: f64,
+    pub(crate) strict_planarity: bool,
+    pub(crate) look_ahead: f64,
+    pub(crate) snap_preference: SnapPreference,
+    pub(crate) branch_pattern: BranchPattern,
+    pub(crate) tensor_field: Option<TensorField>,
+    pub(crate) ring_radii: Vec<f64>,
+    pub(crate) num_radials: usize,
+    density_map: Option<DensityMap>,
+    density_weight: f64,
+    water_distance_map: Option<DensityMap>,
+    coastal_attraction: f64,
+    biome_map: Option<BiomeMap>,
+    biome_costs: [f64; 4],
+    variation: f64,
+    pub(crate) iterations: usize,
+    pub(crate) coverage_target: Option<(Site2D, Site2D, f64)>,
+    #[serde(with = "unbounded")]
+    pub(crate) max_total_length: f64,
+    altitude_cache_resolution: Option<(usize, usize)>,
+    // derived from the terrain when a growth starts
+    #[serde(skip)]
+    altitude_cache: Option<Arc<AltitudeCache>>,
+    #[serde(skip)]
+    strategy: Option<Arc<dyn GrowthStrategy>>,
 }
 
-impl Ord for Path {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.cost.partial_cmp(&self.cost).unwrap()
+/// Serialize an upper limit which can be infinite, as `null` for no limit since JSON has no infinity.
+mod unbounded {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        value.is_finite().then_some(*value).serialize(serializer)
     }
-}
 
-impl PartialOrd for Path {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        other.cost.partial_cmp(&self.cost)
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::INFINITY))
     }
 }
 
-impl PartialEq for Path {
-    fn eq(&self, other: &Self) -> bool {
-        self.start == other.start
-            && self.end == other.end
-            && self.angle == other.angle
-            && self.cost == other.cost
-    }
+/// The number of legs of a switchback is limited to this.
+const SWITCHBACK_MAX_LEGS: usize = 8;
+/// Switchbacks are laid longer than the maximum grade requires by this ratio
+/// so that the unevenness of the ground does not make every leg exceed it.
+const SWITCHBACK_LENGTH_MARGIN: f64 = 1.25;
+
+/// A branch which can be built.
+pub(crate) struct Candidate {
+    pub(crate) site: Site2D,
+    pub(crate) altitude: f64,
+    pub(crate) cost: f64,
+    pub(crate) attr: PathAttr,
+    /// The turning points of the switchback between the start and `site`, if the branch is too steep to go straight.
+    pub(crate) switchback: Vec<(Site2D, f64)>,
 }
 
-impl Eq for Path {}
+pub(crate) enum CandidateRejection {
+    Sea,
+    OutOfTerrain,
+    TooSteep,
+    Forbidden,
+}
 
-#[wasm_bindgen]
+impl Default for TransportNetworkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl TransportNetworkBuilder {
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new() -> Self {
         Self {
             start: Site2D { x: 0.0, y: 0.0 },
+            additional_starts: vec![],
             branch_length: 0.0,
             branch_angle_deviation: 0.0,
             branch_max_angle: 0.0,
@@ -86,9 +208,71 @@ impl TransportNetworkBuilder {
             highway_construction_priority: 0.0,
             even_path_length_weight: 0.0,
             highway_path_length_weight: 0.0,
+            highway_overlay_tolerance: 0.0,
+            normal_overlay_tolerance: 0.0,
+            bridge_max_length: 0.0,
+            bridge_cost_multiplier: 0.0,
+            max_grade: f64::INFINITY,
+            one_way_fraction: 0.0,
+            class_parameters: [None; 4],
+            bounds: None,
+            boundary_polygon: vec![],
+            other_origins: vec![],
+            forbidden_areas: vec![],
+            existing_sites: vec![],
+            existing_edges: vec![],
+            highway_iterations: None,
+            record_log: false,
+            merge_distance: 0.0,
+            strict_planarity: false,
+            look_ahead: 0.8,
+            snap_preference: SnapPreference::SitesFirst,
+            branch_pattern: BranchPattern::Organic,
+            tensor_field: None,
+            ring_radii: vec![],
+            num_radials: 0,
+            density_map: None,
+            density_weight: 0.0,
+            water_distance_map: None,
+            coastal_attraction: 0.0,
+            biome_map: None,
+            biome_costs: [1.0; 4],
+            variation: 0.0,
+            coverage_target: None,
+            max_total_length: f64::INFINITY,
+            altitude_cache_resolution: None,
+            altitude_cache: None,
+            strategy: None,
         }
     }
 
+    /// Export the parameters as JSON, which can be loaded by `from_json`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Load the parameters written by `to_json`. The missing parameters take the values of `new`.
+    /// Returns `None` if the JSON is broken or has a parameter of a wrong type.
+    pub fn from_json(json: &str) -> Option<TransportNetworkBuilder> {
+        serde_json::from_str(json).ok()
+    }
+
+    /// Create a builder with the parameters tuned for a city at the center of `TerrainBuilder::default_terrain`.
+    /// The start and the other parameters can be changed by the setters as usual.
+    pub fn default_city() -> Self {
+        Self::new()
+            .set_start(100.0, 50.0)
+            .set_iterations(34000)
+            .set_branch_length(0.5)
+            .set_branch_angle_deviation(std::f64::consts::PI / 40.0)
+            .set_branch_max_angle(std::f64::consts::PI / 40.0)
+            .set_normal_rotation_probability(0.8)
+            .set_highway_rotation_probability(0.02)
+            .set_highway_construction_priority(30.0)
+            .set_even_path_length_weight(1.5)
+            .set_highway_path_length_weight(1.5)
+    }
+
     pub fn set_start(self, start_x: f64, start_y: f64) -> Self {
         Self {
             start: Site2D {
@@ -99,6 +283,16 @@ impl TransportNetworkBuilder {
         }
     }
 
+    /// Add another origin of the growth in addition to the start, which must be on the terrain as the start.
+    /// Networks grown from each origin are merged where their paths intersect.
+    pub fn add_start(mut self, start_x: f64, start_y: f64) -> Self {
+        self.additional_starts.push(Site2D {
+            x: start_x,
+            y: start_y,
+        });
+        self
+    }
+
     pub fn set_branch_angle_deviation(self, branch_angle_deviation: f64) -> Self {
         Self {
             branch_angle_deviation,
@@ -159,268 +353,994 @@ impl TransportNetworkBuilder {
         }
     }
 
-    fn evaluate_cost(&self, altitude_from: f64, altitude_to: f64, attr: PathAttr) -> Option<f64> {
-        if altitude_to < SEA_LEVEL {
-            return None;
+    pub fn set_highway_overlay_tolerance(self, highway_overlay_tolerance: f64) -> Self {
+        Self {
+            highway_overlay_tolerance,
+            ..self
         }
+    }
 
-        let mut altitude_diff = altitude_to - altitude_from;
-        if attr.is_even {
-            altitude_diff *= self.even_path_length_weight;
+    pub fn set_normal_overlay_tolerance(self, normal_overlay_tolerance: f64) -> Self {
+        Self {
+            normal_overlay_tolerance,
+            ..self
         }
-        if attr.is_highway {
-            altitude_diff *= self.highway_path_length_weight;
+    }
+
+    /// Set the maximum length of bridges which highways can build to cross the sea.
+    /// Bridges are disabled if the length is shorter than twice the branch length.
+    pub fn set_bridge_max_length(self, bridge_max_length: f64) -> Self {
+        Self {
+            bridge_max_length,
+            ..self
         }
-        Some(
-            altitude_diff.abs()
-                * altitude_to
-                * (1.0 / self.highway_construction_priority + (!attr.is_highway as i32) as f64),
-        )
     }
 
-    pub fn build(self, seed: u32, terrain: &Terrain) -> TransportNetwork {
-        let mut rng = StdRng::seed_from_u64(seed as u64);
+    pub fn set_bridge_cost_multiplier(self, bridge_cost_multiplier: f64) -> Self {
+        Self {
+            bridge_cost_multiplier,
+            ..self
+        }
+    }
 
-        let initial_angle = rng.gen_range(0.0..std::f64::consts::PI);
-        let initial_opposite_angle = initial_angle + std::f64::consts::PI;
+    /// Set the maximum grade (rise over run) of the roads on the ground.
+    /// The branches steeper than this climb by switchbacks, or are not built if no switchback fits.
+    /// There is no limit by default.
+    pub fn set_max_grade(self, max_grade: f64) -> Self {
+        Self { max_grade, ..self }
+    }
 
-        let mut sites_collection = vec![
-            Site2D {
-                x: self.start.x,
-                y: self.start.y,
-            },
-            Site2D {
-                x: self.start.x + self.branch_length * initial_angle.cos(),
-                y: self.start.y + self.branch_length * initial_angle.sin(),
-            },
-            Site2D {
-                x: self.start.x + self.branch_length * initial_opposite_angle.cos(),
-                y: self.start.y + self.branch_length * initial_opposite_angle.sin(),
-            },
-        ]
-        .iter()
-        .filter_map(|site| {
-            let altitude = terrain.get_altitude(site.x, site.y);
-            if let Some(altitude) = altitude {
-                Some((*site, altitude))
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-
-        let mut path_heap = BinaryHeap::new();
-        path_heap.push(Path {
-            start: 0,
-            end: 1,
-            angle: initial_angle,
-            cost: 0.0,
-            path_attr: PathAttr {
-                is_highway: true,
-                is_even: false,
-            },
+    /// Set the parameters of the roads in `class`.
+    /// Arterial and collector roads are used only when their parameters are set,
+    /// while motorways and local roads follow the highway and normal parameters until theirs are set.
+    ///
+    /// A side branch keeps the class of its road by `rotation_probability`.
+    /// Otherwise it grows by the rotation probability of the local roads, in the next lower class in use.
+    /// `branch_length_weight` also weights the altitude difference in the cost, which is multiplied by `cost_weight`.
+    pub fn set_class_parameters(
+        mut self,
+        class: RoadClass,
+        branch_length_weight: f64,
+        rotation_probability: f64,
+        cost_weight: f64,
+    ) -> Self {
+        self.class_parameters[class.index()] = Some(ClassParameters {
+            branch_length_weight,
+            rotation_probability,
+            cost_weight,
         });
-        path_heap.push(Path {
-            start: 0,
-            end: 2,
-            angle: initial_opposite_angle,
-            cost: 0.0,
-            path_attr: PathAttr {
-                is_highway: true,
-                is_even: false,
-            },
+        self
+    }
+
+    /// Make about `one_way_fraction` of the local streets one-way.
+    /// The one-way streets in the same direction alternate their direction like the streets of a grid.
+    pub fn set_one_way_fraction(self, one_way_fraction: f64) -> Self {
+        Self {
+            one_way_fraction,
+            ..self
+        }
+    }
+
+    /// Clip the growth to the rectangle from (`min_x`, `min_y`) to (`max_x`, `max_y`).
+    /// No site is created outside it, so a map can be generated one tile at a time.
+    pub fn set_bounds(self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        Self {
+            bounds: Some((Site2D { x: min_x, y: min_y }, Site2D { x: max_x, y: max_y })),
+            ..self
+        }
+    }
+
+    /// Clip the growth to the polygon given as the flattened coordinates `[x0, y0, x1, y1, ...]`.
+    /// It applies together with the bounds, and an empty polygon removes the clipping.
+    pub fn set_boundary_polygon(self, points: Vec<f64>) -> Self {
+        Self {
+            boundary_polygon: points
+                .chunks_exact(2)
+                .map(|point| Site2D {
+                    x: point[0],
+                    y: point[1],
+                })
+                .collect::<Vec<_>>(),
+            ..self
+        }
+    }
+
+    /// Forbid the roads to enter the polygon given as the flattened coordinates `[x0, y0, x1, y1, ...]`,
+    /// such as a park or an airport. Highways do not bridge over it either.
+    pub fn add_forbidden_area(mut self, points: Vec<f64>) -> Self {
+        let area = points
+            .chunks_exact(2)
+            .map(|point| Site2D {
+                x: point[0],
+                y: point[1],
+            })
+            .collect::<Vec<_>>();
+        if area.len() >= 3 {
+            self.forbidden_areas.push(area);
+        }
+        self
+    }
+
+    /// Grow the roads connecting to `network`, which has been built beforehand, for example a skeleton of highways.
+    /// The new roads snap to and split the roads of `network` like their own, and the result contains both.
+    ///
+    /// The nodes of `network` keep their indices unless they are merged by `set_merge_distance`.
+    /// The curved roads of `network` are divided into straight roads at their intermediate vertices,
+    /// and the one-way streets are assigned again over the whole network by `set_one_way_fraction`.
+    pub fn set_existing_network(self, network: &TransportNetwork) -> Self {
+        let mut existing_sites = network.nodes.clone();
+        let mut existing_edges = vec![];
+        network.iter_edges().for_each(|(start, end, attr)| {
+            let polyline = network.get_polyline(start, end);
+            let length = network.get_edge_length(start, end);
+            let mut indices = vec![start];
+            polyline[1..polyline.len() - 1].iter().for_each(|site| {
+                indices.push(existing_sites.len());
+                existing_sites.push(*site);
+            });
+            indices.push(end);
+            indices.windows(2).for_each(|pair| {
+                let (site_a, site_b) = (existing_sites[pair[0]], existing_sites[pair[1]]);
+                let ratio = if length > 0.0 {
+                    get_distance(site_a, site_b) / length
+                } else {
+                    1.0
+                };
+                existing_edges.push((pair[0], pair[1], attr.path_attr, attr.cost * ratio));
+            });
         });
+        Self {
+            existing_sites,
+            existing_edges,
+            ..self
+        }
+    }
 
-        let mut path_tree = PathTree::new();
-        (0..self.iterations).for_each(|_| {
-            let current_path = path_heap.pop();
-            if current_path.is_none() {
-                return;
+    /// Grow the network in two phases instead of growing the highways and the local streets together.
+    /// Only the highways grow in the first `highway_iterations` iterations or until they stop growing,
+    /// and then the local streets grow off both sides of every highway in the rest of the iterations.
+    ///
+    /// The spacing of the highways is then decided by the highway parameters alone,
+    /// and the density of the local streets by the parameters of the local roads.
+    pub fn set_two_phase(self, highway_iterations: usize) -> Self {
+        Self {
+            highway_iterations: Some(highway_iterations),
+            ..self
+        }
+    }
+
+    /// Record the decisions made during the growth into a `GenerationLog` of the network.
+    pub fn set_record_log(self, record_log: bool) -> Self {
+        Self { record_log, ..self }
+    }
+
+    /// Set the distance within which a new path connects to existing paths, relative to the branch length.
+    /// Larger values close loops more eagerly, and zero disables the connections. The default is 0.8.
+    pub fn set_look_ahead(self, look_ahead: f64) -> Self {
+        Self { look_ahead, ..self }
+    }
+
+    pub fn set_snap_preference(self, snap_preference: SnapPreference) -> Self {
+        Self {
+            snap_preference,
+            ..self
+        }
+    }
+
+    /// Set the pattern of the normal branches. Highways always grow organically.
+    pub fn set_branch_pattern(self, branch_pattern: BranchPattern) -> Self {
+        Self {
+            branch_pattern,
+            ..self
+        }
+    }
+
+    /// Set the field of road orientations which the normal branches follow with `BranchPattern::TensorField`.
+    pub fn set_tensor_field(self, tensor_field: TensorField) -> Self {
+        Self {
+            tensor_field: Some(tensor_field),
+            ..self
+        }
+    }
+
+    /// Add a ring highway of `radius` around the start, which must not exceed the diagonal of the terrain.
+    /// Rings of zero or negative radii are ignored.
+    /// With any ring, the start grows radial highways instead of the initial pair of highways.
+    pub fn add_ring(mut self, radius: f64) -> Self {
+        self.ring_radii.push(radius);
+        self
+    }
+
+    /// Set the number of the radial highways through the rings, which are laid at equal angles.
+    pub fn set_num_radials(self, num_radials: usize) -> Self {
+        Self {
+            num_radials,
+            ..self
+        }
+    }
+
+    /// Merge the sites created closer than `merge_distance` to each other into one node.
+    /// The growth connects a path ending close to a committed site to the site and grows no branch from it,
+    /// and a crossing close to a committed site connects to the site instead of splitting the path.
+    /// An origin close to an earlier one grows from it,
+    /// and the nodes of the existing network close to each other are merged when the network is created.
+    ///
+    /// The sites on the same position are merged even if the distance is not positive.
+    pub fn set_merge_distance(self, merge_distance: f64) -> Self {
+        Self {
+            merge_distance,
+            ..self
+        }
+    }
+
+    /// Split the roads at every crossing left by the growth, so that the network is always planar.
+    /// The growth can miss the crossings of nearly parallel roads, which are kept as they are by default.
+    pub fn set_strict_planarity(self, strict_planarity: bool) -> Self {
+        Self {
+            strict_planarity,
+            ..self
+        }
+    }
+
+    /// Stop the growth when the length of the roads within the rectangle
+    /// from (`min_x`, `min_y`) to (`max_x`, `max_y`) per its area reaches `target_density`.
+    /// The iterations still limit the growth unless they are zero, in which case the growth continues until the target is reached.
+    pub fn stop_when_covered(
+        self,
+        min_x: f64,
+        min_y: f64,
+        max_x: f64,
+        max_y: f64,
+        target_density: f64,
+    ) -> Self {
+        Self {
+            coverage_target: Some((
+                Site2D { x: min_x, y: min_y },
+                Site2D { x: max_x, y: max_y },
+                target_density,
+            )),
+            ..self
+        }
+    }
+
+    /// Stop the growth when the total length of the roads, in the units of the terrain, reaches `total_length`.
+    /// The iterations still limit the growth unless they are zero, in which case the growth continues until the length is reached.
+    pub fn stop_at_total_length(self, total_length: f64) -> Self {
+        Self {
+            max_total_length: total_length,
+            ..self
+        }
+    }
+
+    /// Cache the altitudes of the terrain on a `width` x `height` grid over its bounds during the growth,
+    /// and interpolate them bilinearly instead of interpolating the terrain itself, which is slower for large iteration counts.
+    /// Each grid point is sampled at its first use.
+    /// The altitudes differ slightly from the terrain, so the network changes. Zero for either size disables the cache, as by default.
+    pub fn set_altitude_cache_resolution(self, width: usize, height: usize) -> Self {
+        Self {
+            altitude_cache_resolution: (width > 0 && height > 0).then_some((width, height)),
+            altitude_cache: None,
+            ..self
+        }
+    }
+
+    /// Get the parameters of `class`, or `None` if the class is not used.
+    pub(crate) fn get_class_parameters(&self, class: RoadClass) -> Option<ClassParameters> {
+        self.class_parameters[class.index()].or(match class {
+            RoadClass::Motorway => Some(ClassParameters {
+                branch_length_weight: self.highway_path_length_weight,
+                rotation_probability: self.highway_rotation_probability,
+                cost_weight: 1.0 / self.highway_construction_priority,
+            }),
+            RoadClass::Local => Some(ClassParameters {
+                branch_length_weight: 1.0,
+                rotation_probability: self.normal_rotation_probability,
+                cost_weight: 1.0 / self.highway_construction_priority + 1.0,
+            }),
+            _ => None,
+        })
+    }
+
+    /// Get the parameters of `class`. The classes which are not used follow the motorways.
+    pub(crate) fn class_parameters(&self, class: RoadClass) -> ClassParameters {
+        self.get_class_parameters(class)
+            .or(self.get_class_parameters(RoadClass::Motorway))
+            .unwrap()
+    }
+
+    /// Get the next class lower than `class` in use. Local roads stay local.
+    pub(crate) fn lower_class(&self, class: RoadClass) -> RoadClass {
+        ROAD_CLASSES
+            .iter()
+            .copied()
+            .find(|lower| *lower > class && self.get_class_parameters(*lower).is_some())
+            .unwrap_or(RoadClass::Local)
+    }
+
+    pub(crate) fn is_within_bounds(&self, site: Site2D) -> bool {
+        let within_rect = self.bounds.is_none_or(|(min, max)| {
+            site.x >= min.x && site.x <= max.x && site.y >= min.y && site.y <= max.y
+        });
+        within_rect
+            && (self.boundary_polygon.len() < 3 || is_inside_polygon(site, &self.boundary_polygon))
+            && self
+                .other_origins
+                .iter()
+                .all(|origin| get_distance(site, *origin) >= get_distance(site, self.start))
+    }
+
+    /// Check whether the straight road from `site_from` to `site_to` enters any forbidden area.
+    pub(crate) fn crosses_forbidden_area(&self, site_from: Site2D, site_to: Site2D) -> bool {
+        self.forbidden_areas.iter().any(|area| {
+            is_inside_polygon(site_to, area)
+                || area.iter().enumerate().any(|(i, vertex)| {
+                    get_cross(site_from, site_to, *vertex, area[(i + 1) % area.len()])
+                        .is_some_and(|cross| cross.1)
+                })
+        })
+    }
+
+    /// Get the altitude at `site` if a road can be built there, that is, on the terrain and within the bounds.
+    pub(crate) fn get_altitude(&self, terrain: &Terrain, site: Site2D) -> Option<f64> {
+        if !self.is_within_bounds(site) {
+            return None;
+        }
+        match &self.altitude_cache {
+            Some(cache) => cache.get_altitude(terrain, site),
+            None => terrain.get_altitude(site.x, site.y),
+        }
+    }
+
+    /// Get the strategy of the growth, `DefaultStrategy` unless it is set.
+    pub(crate) fn strategy(&self) -> &dyn GrowthStrategy {
+        self.strategy.as_deref().unwrap_or(&DefaultStrategy)
+    }
+
+    /// Sample the altitudes of `terrain` for the growth if the cache is enabled and not sampled yet.
+    pub(crate) fn prepare_altitude_cache(&mut self, terrain: &Terrain) {
+        if let (Some((width, height)), None) =
+            (self.altitude_cache_resolution, &self.altitude_cache)
+        {
+            self.altitude_cache = Some(Arc::new(AltitudeCache::new(terrain, width, height)));
+        }
+    }
+
+    pub(crate) fn nearest_origin(&self, site: Site2D) -> Site2D {
+        std::iter::once(self.start)
+            .chain(self.additional_starts.iter().copied())
+            .min_by(|a, b| {
+                get_distance(*a, site)
+                    .partial_cmp(&get_distance(*b, site))
+                    .unwrap()
+            })
+            .unwrap()
+    }
+
+    /// Evaluate the branch from `site_from` toward `angle`.
+    /// If a highway branch ends in the sea, it is extended as a bridge until it reaches the land.
+    /// If the branch exceeds the maximum grade, it climbs by a switchback instead.
+    pub(crate) fn evaluate_candidate(
+        &self,
+        terrain: &Terrain,
+        site_from: Site2D,
+        altitude_from: f64,
+        angle: f64,
+        branch_length: f64,
+        attr: PathAttr,
+    ) -> Result<Candidate, CandidateRejection> {
+        let site_at = |length: f64| Site2D {
+            x: site_from.x + length * libm::cos(angle),
+            y: site_from.y + length * libm::sin(angle),
+        };
+
+        let site = site_at(branch_length);
+        let altitude = self
+            .get_altitude(terrain, site)
+            .ok_or(CandidateRejection::OutOfTerrain)?;
+        if self.crosses_forbidden_area(site_from, site) {
+            return Err(CandidateRejection::Forbidden);
+        }
+        match self.evaluate_cost(site_from, altitude_from, site, altitude, attr) {
+            Ok(cost) => {
+                return Ok(Candidate {
+                    site,
+                    altitude,
+                    cost,
+                    attr,
+                    switchback: vec![],
+                })
             }
-            let current_path = current_path.unwrap();
-            let site_start = sites_collection[current_path.start];
-            let site_end = sites_collection[current_path.end];
-
-            let intersection_distance = self.branch_length * 0.8;
-
-            // find path intersection
-            let intersection = path_tree.find(
-                &site_start.0,
-                &site_end.0,
-                intersection_distance,
-                &[current_path.start],
-            );
-            let mut intersection_pushed = false;
-            if let PathTreeQuery::Site(site_index) = intersection {
-                path_tree.insert(
-                    current_path.start,
-                    site_index,
-                    site_start.0,
-                    sites_collection[site_index].0,
-                    current_path.path_attr,
-                );
-                intersection_pushed = true;
-            } else if let PathTreeQuery::Path(intersection) = intersection {
-                let cross = get_cross(
-                    intersection.site_start,
-                    intersection.site_end,
-                    site_start.0,
-                    site_end.0,
-                );
-                if let Some(cross) = cross {
-                    intersection_pushed = true;
-                    if cross.1 {
-                        let cross_site = cross.0;
-                        let altitude = terrain.get_altitude(cross_site.x, cross_site.y);
-                        if let Some(altitude) = altitude {
-                            // push
-                            let site_next_index = sites_collection.len();
-                            sites_collection.push((cross_site, altitude));
-                            path_tree.split(*intersection, &cross_site, site_next_index);
-                            path_tree.insert(
-                                current_path.start,
-                                site_next_index,
-                                site_start.0,
-                                cross_site,
-                                current_path.path_attr,
-                            );
-                        }
-                    }
-                }
+            Err(CandidateRejection::TooSteep) => {
+                let (switchback, cost) = self
+                    .evaluate_switchback(terrain, site_from, altitude_from, site, altitude, attr)
+                    .ok_or(CandidateRejection::TooSteep)?;
+                return Ok(Candidate {
+                    site,
+                    altitude,
+                    cost,
+                    attr,
+                    switchback,
+                });
             }
+            Err(CandidateRejection::Sea) if attr.is_highway() => {}
+            Err(rejection) => return Err(rejection),
+        }
 
-            if intersection_pushed {
-                return;
+        let bridge_attr = PathAttr {
+            is_bridge: true,
+            ..attr
+        };
+        let max_steps = (self.bridge_max_length / branch_length).floor() as usize;
+        for step in 2..=max_steps {
+            let site = site_at(branch_length * step as f64);
+            let altitude = self
+                .get_altitude(terrain, site)
+                .ok_or(CandidateRejection::OutOfTerrain)?;
+            if self.crosses_forbidden_area(site_from, site) {
+                return Err(CandidateRejection::Forbidden);
             }
-            path_tree.insert(
-                current_path.start,
-                current_path.end,
-                site_start.0,
-                site_end.0,
-                current_path.path_attr,
-            );
-
-            let check_times =
-                (self.branch_max_angle / self.branch_angle_deviation).floor() as usize;
-
-            (-1..2).for_each(|riter| {
-                let mut site_next: Option<Site2D> = None;
-                let mut min_cost = std::f64::MAX;
-                let mut min_cost_angle = 0.0;
-                let mut min_cost_altitude = 0.0;
-
-                let mut is_highway = current_path.path_attr.is_highway;
-                let mut is_even = current_path.path_attr.is_even;
-                if riter != 0 {
-                    is_even = !is_even;
-                    is_highway = false;
-                    if current_path.path_attr.is_highway
-                        && rng.gen_bool(self.highway_rotation_probability)
-                    {
-                        is_highway = true;
-                    } else if !rng.gen_bool(self.normal_rotation_probability) {
-                        return;
-                    }
+            match self.evaluate_cost(site_from, altitude_from, site, altitude, bridge_attr) {
+                Ok(cost) => {
+                    let cost = cost * self.bridge_cost_multiplier * step as f64;
+                    return Ok(Candidate {
+                        site,
+                        altitude,
+                        cost,
+                        attr: bridge_attr,
+                        switchback: vec![],
+                    });
                 }
-                let site_next_attr = PathAttr {
-                    is_highway,
-                    is_even,
+                Err(CandidateRejection::Sea) => {}
+                Err(rejection) => return Err(rejection),
+            }
+        }
+        Err(CandidateRejection::Sea)
+    }
+
+    /// Lay a zig-zag path from `site_from` to `site_to` whose legs are all within the maximum grade.
+    /// Returns the turning points and the total cost of the legs, using as few legs as possible.
+    fn evaluate_switchback(
+        &self,
+        terrain: &Terrain,
+        site_from: Site2D,
+        altitude_from: f64,
+        site_to: Site2D,
+        altitude_to: f64,
+        attr: PathAttr,
+    ) -> Option<(Vec<(Site2D, f64)>, f64)> {
+        let length = get_distance(site_from, site_to);
+        if self.max_grade <= 0.0 || length <= 0.0 {
+            return None;
+        }
+        let required_length =
+            (altitude_to - altitude_from).abs() / self.max_grade * SWITCHBACK_LENGTH_MARGIN;
+        let (dx, dy) = (
+            (site_to.x - site_from.x) / length,
+            (site_to.y - site_from.y) / length,
+        );
+
+        (2..=SWITCHBACK_MAX_LEGS).find_map(|num_legs| {
+            let advance = length / num_legs as f64;
+            // the legs cross the direct line alternately, turning at `half_width` from it
+            let half_width = required_length / (2 * num_legs - 2) as f64;
+            let mut vertices = vec![(site_from, altitude_from)];
+            for leg in 1..num_legs {
+                let side = if leg % 2 == 1 { 1.0 } else { -1.0 };
+                let site = Site2D {
+                    x: site_from.x + dx * advance * leg as f64 - dy * half_width * side,
+                    y: site_from.y + dy * advance * leg as f64 + dx * half_width * side,
                 };
+                let altitude = self.get_altitude(terrain, site)?;
+                vertices.push((site, altitude));
+            }
+            vertices.push((site_to, altitude_to));
 
-                let current_angle = current_path.angle + riter as f64 * std::f64::consts::PI * 0.5;
-                (0..check_times + 1).for_each(|i| {
-                    let branch_length = {
-                        let mut branch_length = self.branch_length;
-                        if site_next_attr.is_even {
-                            branch_length *= self.even_path_length_weight
-                        }
-                        if site_next_attr.is_highway {
-                            branch_length *= self.highway_path_length_weight
-                        }
-                        branch_length
-                    };
-                    let angle = current_angle + self.branch_angle_deviation * (i as f64);
-                    let site_a = Site2D {
-                        x: site_end.0.x + branch_length * angle.cos(),
-                        y: site_end.0.y + branch_length * angle.sin(),
-                    };
-                    let altitude_a = terrain.get_altitude(site_a.x, site_a.y);
-                    if let Some(altitude_a) = altitude_a {
-                        if let Some(cost) =
-                            self.evaluate_cost(site_start.1, altitude_a, site_next_attr)
-                        {
-                            if cost < min_cost {
-                                min_cost = cost;
-                                min_cost_angle = angle;
-                                min_cost_altitude = altitude_a;
-                                site_next = Some(site_a);
-                            }
-                        }
+            let cost = vertices
+                .windows(2)
+                .map(|pair| {
+                    if self.crosses_forbidden_area(pair[0].0, pair[1].0) {
+                        return None;
                     }
+                    self.evaluate_cost(pair[0].0, pair[0].1, pair[1].0, pair[1].1, attr)
+                        .ok()
+                })
+                .sum::<Option<f64>>()?;
+            Some((vertices[1..num_legs].to_vec(), cost))
+        })
+    }
 
-                    if i == 0 {
-                        return;
-                    }
-                    let angle = current_angle - self.branch_angle_deviation * (i as f64);
-                    let site_b = Site2D {
-                        x: site_end.0.x + branch_length * angle.cos(),
-                        y: site_end.0.y + branch_length * angle.sin(),
-                    };
-                    let altitude_b = terrain.get_altitude(site_b.x, site_b.y);
-                    if let Some(altitude_b) = altitude_b {
-                        if let Some(cost) =
-                            self.evaluate_cost(site_start.1, altitude_b, site_next_attr)
-                        {
-                            if cost < min_cost {
-                                min_cost = cost;
-                                min_cost_angle = angle;
-                                min_cost_altitude = altitude_b;
-                                site_next = Some(site_b);
-                            }
-                        }
-                    }
-                });
+    /// Set the population density which attracts the growth.
+    pub fn set_density_map(self, density_map: DensityMap) -> Self {
+        Self {
+            density_map: Some(density_map),
+            ..self
+        }
+    }
 
-                if let Some(site_next) = site_next {
-                    let site_next_index = sites_collection.len();
-                    sites_collection.push((site_next, min_cost_altitude));
-                    path_heap.push(Path {
-                        start: current_path.end,
-                        end: site_next_index,
-                        angle: min_cost_angle,
-                        cost: min_cost,
-                        path_attr: site_next_attr,
-                    });
-                }
-            });
-        });
+    /// Set how strongly the density attracts the growth.
+    /// A penalty of `density_weight / (1 + density)` is added to the cost of each path.
+    pub fn set_density_weight(self, density_weight: f64) -> Self {
+        Self {
+            density_weight,
+            ..self
+        }
+    }
 
-        let mut graph = EdgeAttributedUndirectedGraph::new(sites_collection.len());
+    /// Set the distance to the sea, such as by `Terrain::get_water_distance_map`, which attracts the highways.
+    pub fn set_water_distance_map(self, water_distance_map: DensityMap) -> Self {
+        Self {
+            water_distance_map: Some(water_distance_map),
+            ..self
+        }
+    }
 
-        path_tree.for_each(|path| {
-            if graph.has_edge(path.site_index_start, path.site_index_end).0 {
-                return;
+    /// Set how strongly the sea attracts the highways, so that they run along the shorelines.
+    /// A penalty of `coastal_attraction * distance` is added to the cost when choosing the direction of a highway branch.
+    /// It is not added to the cost of the path, so the highways are built in the same order.
+    pub fn set_coastal_attraction(self, coastal_attraction: f64) -> Self {
+        Self {
+            coastal_attraction,
+            ..self
+        }
+    }
+
+    /// Set the land cover, whose cost is set by `set_biome_cost`.
+    pub fn set_biome_map(self, biome_map: BiomeMap) -> Self {
+        Self {
+            biome_map: Some(biome_map),
+            ..self
+        }
+    }
+
+    /// Multiply the cost of the paths into `biome` by `factor`, which is 1 for every biome by default.
+    /// The roads skirt the biomes with larger factors, such as forests and marshes.
+    pub fn set_biome_cost(mut self, biome: Biome, factor: f64) -> Self {
+        self.biome_costs[biome.index()] = factor;
+        self
+    }
+
+    /// Jitter the growth parameters by up to `variation` times their values, differently for each seed.
+    /// The jitter is applied when building, so the builder itself stays unchanged.
+    pub fn set_variation(self, variation: f64) -> Self {
+        Self { variation, ..self }
+    }
+
+    /// Get the builder with the parameters jittered for `seed`, which builds the same network with the seed.
+    pub fn vary(&self, seed: u32) -> TransportNetworkBuilder {
+        self.vary_u64(seed as u64)
+    }
+
+    /// Same as `vary` with a 64-bit seed.
+    pub fn vary_u64(&self, seed: u64) -> TransportNetworkBuilder {
+        if self.variation <= 0.0 {
+            return self.clone();
+        }
+        // a stream independent from the growth
+        let mut rng = StdRng::seed_from_u64(seed ^ 0x9e37_79b9_7f4a_7c15);
+        let mut jitter = |value: f64| value * (1.0 + self.variation * rng.gen_range(-1.0..=1.0));
+        Self {
+            branch_length: jitter(self.branch_length),
+            branch_angle_deviation: jitter(self.branch_angle_deviation),
+            branch_max_angle: jitter(self.branch_max_angle),
+            highway_rotation_probability: jitter(self.highway_rotation_probability).clamp(0.0, 1.0),
+            normal_rotation_probability: jitter(self.normal_rotation_probability).clamp(0.0, 1.0),
+            highway_construction_priority: jitter(self.highway_construction_priority),
+            even_path_length_weight: jitter(self.even_path_length_weight),
+            highway_path_length_weight: jitter(self.highway_path_length_weight),
+            variation: 0.0,
+            ..self.clone()
+        }
+    }
+
+    /// Detect degenerate parameter combinations without building.
+    pub fn diagnose(&self) -> BuildReport {
+        self.adjusted_parameters().2
+    }
+
+    /// Check the parameters which the growth cannot adjust, returning the first invalid one as an error.
+    /// The angles and the probabilities are adjusted instead, which is reported by `diagnose`.
+    /// `build` calls this before growing the network.
+    pub fn validate(&self, terrain: &Terrain) -> Result<(), TransportError> {
+        let check = |name: &'static str, is_valid: bool, requirement: &'static str| {
+            if is_valid {
+                Ok(())
+            } else {
+                Err(TransportError::InvalidParameter { name, requirement })
             }
-            graph.add_edge(path.site_index_start, path.site_index_end, path.path_attr);
-        });
+        };
+        let positive = |value: f64| value > 0.0 && value.is_finite();
+        let non_negative = |value: f64| value >= 0.0;
+        const POSITIVE: &str = "positive and finite";
+        const NON_NEGATIVE: &str = "zero or positive";
+        const FINITE: &str = "finite";
 
-        TransportNetwork {
-            nodes: sites_collection
+        if self.iterations == 0 && !self.has_stop_target() {
+            return Err(TransportError::NoIterations);
+        }
+        if let Some((bound_min, bound_max, target_density)) = self.coverage_target {
+            check(
+                "the rectangle of stop_when_covered",
+                bound_min.x < bound_max.x && bound_min.y < bound_max.y,
+                "non-empty",
+            )?;
+            check(
+                "target_density of stop_when_covered",
+                positive(target_density),
+                POSITIVE,
+            )?;
+        }
+        check(
+            "the length of stop_at_total_length",
+            self.max_total_length > 0.0,
+            "positive",
+        )?;
+        check("branch_length", positive(self.branch_length), POSITIVE)?;
+        check(
+            "even_path_length_weight",
+            positive(self.even_path_length_weight),
+            POSITIVE,
+        )?;
+        ROAD_CLASSES.iter().try_for_each(|class| {
+            let is_explicit = self.class_parameters[class.index()].is_some();
+            let Some(parameters) = self.get_class_parameters(*class) else {
+                return Ok(());
+            };
+            check(
+                if is_explicit {
+                    "branch_length_weight of set_class_parameters"
+                } else {
+                    "highway_path_length_weight"
+                },
+                positive(parameters.branch_length_weight),
+                POSITIVE,
+            )?;
+            check(
+                if is_explicit {
+                    "cost_weight of set_class_parameters"
+                } else {
+                    "highway_construction_priority"
+                },
+                non_negative(parameters.cost_weight) && parameters.cost_weight.is_finite(),
+                if is_explicit { NON_NEGATIVE } else { POSITIVE },
+            )
+        })?;
+        check("max_grade", non_negative(self.max_grade), NON_NEGATIVE)?;
+        check(
+            "bridge_max_length",
+            non_negative(self.bridge_max_length),
+            NON_NEGATIVE,
+        )?;
+        check(
+            "bridge_cost_multiplier",
+            non_negative(self.bridge_cost_multiplier),
+            NON_NEGATIVE,
+        )?;
+        check(
+            "look_ahead",
+            non_negative(self.look_ahead) && self.look_ahead.is_finite(),
+            NON_NEGATIVE,
+        )?;
+        check(
+            "highway_overlay_tolerance",
+            non_negative(self.highway_overlay_tolerance),
+            NON_NEGATIVE,
+        )?;
+        check(
+            "normal_overlay_tolerance",
+            non_negative(self.normal_overlay_tolerance),
+            NON_NEGATIVE,
+        )?;
+        check("density_weight", self.density_weight.is_finite(), FINITE)?;
+        check(
+            "coastal_attraction",
+            self.coastal_attraction.is_finite(),
+            FINITE,
+        )?;
+        check(
+            "the factor of set_biome_cost",
+            self.biome_costs
                 .iter()
-                .map(|(site, _)| *site)
-                .collect::<Vec<_>>(),
-            graph,
+                .all(|factor| non_negative(*factor) && factor.is_finite()),
+            NON_NEGATIVE,
+        )?;
+        let (bound_min, bound_max) = (terrain.get_bound_min(), terrain.get_bound_max());
+        let diagonal = get_distance(bound_min, bound_max);
+        check(
+            "the radius of add_ring",
+            self.ring_radii
+                .iter()
+                .all(|radius| radius.is_finite() && *radius <= diagonal),
+            "finite and within the diagonal of the terrain",
+        )?;
+        if self.get_altitude(terrain, self.start).is_none() {
+            return Err(TransportError::StartOutsideTerrain);
+        }
+        if let Some(index) = self
+            .additional_starts
+            .iter()
+            .position(|start| self.get_altitude(terrain, *start).is_none())
+        {
+            return Err(TransportError::AdditionalStartOutsideTerrain { index });
+        }
+        Ok(())
+    }
+
+    /// Get the number of the iterations to run, which is unlimited if it is zero and the growth stops by a target.
+    pub(crate) fn growth_iterations(&self) -> usize {
+        if self.iterations == 0 && self.has_stop_target() {
+            usize::MAX
+        } else {
+            self.iterations
+        }
+    }
+
+    /// Check whether the growth stops by the coverage or the total length.
+    pub(crate) fn has_stop_target(&self) -> bool {
+        self.coverage_target.is_some() || self.max_total_length.is_finite()
+    }
+
+    /// Compute the number of angle steps per side and the rotation probabilities used in the growth.
+    /// Values which would hang or abort the growth are adjusted into their valid ranges.
+    pub(crate) fn adjusted_parameters(&self) -> (usize, [f64; 4], BuildReport) {
+        let mut report = BuildReport::default();
+
+        let mut clamp_probability = |probability: f64| {
+            let clamped = if probability.is_nan() {
+                0.0
+            } else {
+                probability.clamp(0.0, 1.0)
+            };
+            if clamped != probability {
+                report.warn(BuildWarning::ProbabilityClamped);
+            }
+            clamped
+        };
+        let rotation_probabilities = ROAD_CLASSES.map(|class| {
+            self.get_class_parameters(class).map_or(0.0, |parameters| {
+                clamp_probability(parameters.rotation_probability)
+            })
+        });
+        if rotation_probabilities[RoadClass::Motorway.index()] == 0.0
+            && rotation_probabilities[RoadClass::Local.index()] == 0.0
+        {
+            report.warn(BuildWarning::RotationDisabled);
         }
+
+        let check_times = if self.branch_angle_deviation > 0.0 {
+            let ratio = self.branch_max_angle / self.branch_angle_deviation;
+            if ratio.is_nan() || ratio < 1.0 {
+                report.warn(BuildWarning::SingleCandidate);
+                0
+            } else if ratio > MAX_CHECK_TIMES as f64 {
+                report.warn(BuildWarning::CandidatesClamped);
+                MAX_CHECK_TIMES
+            } else {
+                ratio.floor() as usize
+            }
+        } else {
+            report.warn(BuildWarning::AngleDeviationAdjusted);
+            0
+        };
+
+        (check_times, rotation_probabilities, report)
+    }
+
+    /// Evaluate the cost of the path from `site_from` to `site_to`.
+    /// Bridges are allowed to exceed the maximum grade as they do not follow the ground.
+    pub(crate) fn evaluate_cost(
+        &self,
+        site_from: Site2D,
+        altitude_from: f64,
+        site_to: Site2D,
+        altitude_to: f64,
+        attr: PathAttr,
+    ) -> Result<f64, CandidateRejection> {
+        if altitude_to < SEA_LEVEL {
+            return Err(CandidateRejection::Sea);
+        }
+
+        let mut altitude_diff = altitude_to - altitude_from;
+        let run = get_distance(site_from, site_to);
+        if !attr.is_bridge && run > 0.0 && altitude_diff.abs() / run > self.max_grade {
+            return Err(CandidateRejection::TooSteep);
+        }
+        if attr.is_even {
+            altitude_diff *= self.even_path_length_weight;
+        }
+        let class_parameters = self.class_parameters(attr.road_class);
+        altitude_diff *= class_parameters.branch_length_weight;
+        let density_penalty = self.density_map.as_ref().map_or(0.0, |density_map| {
+            self.density_weight / (1.0 + density_map.sample(site_to.x, site_to.y).max(0.0))
+        });
+        let biome_factor = self
+            .biome_map
+            .as_ref()
+            .and_then(|biome_map| biome_map.sample(site_to.x, site_to.y))
+            .map_or(1.0, |biome| self.biome_costs[biome.index()]);
+        Ok((altitude_diff.abs() * altitude_to + density_penalty)
+            * class_parameters.cost_weight
+            * biome_factor)
+    }
+
+    /// Get the penalty for a branch of `attr` toward `site` when choosing the direction of the branch.
+    pub(crate) fn get_steering_penalty(&self, site: Site2D, attr: PathAttr) -> f64 {
+        match &self.water_distance_map {
+            Some(water_distance_map) if attr.is_highway() => {
+                self.coastal_attraction * water_distance_map.sample(site.x, site.y).max(0.0)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Start a growth which can be advanced step by step.
+    pub fn start_build(self, seed: u32, terrain: &Terrain) -> TransportGrowth {
+        self.start_build_u64(seed as u64, terrain)
+    }
+
+    /// Same as `start_build` with a 64-bit seed.
+    /// The seeds below 2^32 start the same growth as `start_build`.
+    pub fn start_build_u64(self, seed: u64, terrain: &Terrain) -> TransportGrowth {
+        TransportGrowth::new(self, seed, terrain)
+    }
+
+    pub fn build(self, seed: u32, terrain: &Terrain) -> Result<TransportNetwork, TransportError> {
+        self.build_u64(seed as u64, terrain)
+    }
+
+    /// Start a growth and advance it until `budget_ms` milliseconds have passed or the growth is complete.
+    /// The network grown so far is taken by `snapshot` of the returned growth,
+    /// which can be continued by `step_with_time_budget`, for example once per frame.
+    pub fn build_with_time_budget(
+        self,
+        seed: u32,
+        terrain: &Terrain,
+        budget_ms: f64,
+    ) -> Result<TransportGrowth, TransportError> {
+        self.validate(terrain)?;
+        let mut growth = self.start_build(seed, terrain);
+        growth.step_with_time_budget(budget_ms, terrain)?;
+        Ok(growth)
+    }
+
+    /// Build the network without keeping it, calling the JS function `callback(edge, start, end)`
+    /// for each path as soon as it is committed. See `build_with_observer`.
+    ///
+    /// If `callback` throws, the growth stops and the exception is rethrown.
+    #[cfg(feature = "wasm")]
+    pub fn build_with_callback(
+        self,
+        seed: u32,
+        terrain: &Terrain,
+        callback: &js_sys::Function,
+    ) -> Result<(), JsValue> {
+        self.validate(terrain)?;
+        let iterations = self.growth_iterations();
+        let mut growth = self.start_build(seed, terrain);
+        let mut exception = None;
+        let mut observer = |edge: Edge, start: Site2D, end: Site2D| {
+            let result = callback.call3(&JsValue::NULL, &edge.into(), &start.into(), &end.into());
+            result.map_err(|error| exception = Some(error)).is_ok()
+        };
+        growth.step_observed(iterations, terrain, &mut observer)?;
+        exception.map_or(Ok(()), Err)
+    }
+
+    /// Same as `build` with a 64-bit seed.
+    /// The seeds below 2^32 build the same network as `build`.
+    pub fn build_u64(
+        self,
+        seed: u64,
+        terrain: &Terrain,
+    ) -> Result<TransportNetwork, TransportError> {
+        self.validate(terrain)?;
+        let iterations = self.growth_iterations();
+        let mut growth = self.start_build_u64(seed, terrain);
+        growth.step(iterations, terrain)?;
+        Ok(growth.finish())
+    }
+}
+
+impl TransportNetworkBuilder {
+    /// Replace the decisions of the growth by `strategy`.
+    /// The strategy is not serialized, so a builder or a growth loaded from bytes or JSON uses `DefaultStrategy`.
+    pub fn set_growth_strategy<S: GrowthStrategy + 'static>(self, strategy: S) -> Self {
+        Self {
+            strategy: Some(Arc::new(strategy)),
+            ..self
+        }
+    }
+
+    /// Build the network without keeping it, passing each path to `observer` with the sites of its ends
+    /// as soon as it is committed, so that the network can be drawn progressively.
+    ///
+    /// The node indices are those of the sites during the growth, which are not merged by `set_merge_distance`.
+    /// A path split at a crossing later is not passed again, as its shape does not change.
+    pub fn build_with_observer<F>(
+        self,
+        seed: u32,
+        terrain: &Terrain,
+        mut observer: F,
+    ) -> Result<(), TransportError>
+    where
+        F: FnMut(Edge, Site2D, Site2D),
+    {
+        self.validate(terrain)?;
+        let iterations = self.growth_iterations();
+        let mut growth = self.start_build(seed, terrain);
+        growth.step_observed(iterations, terrain, &mut |edge, start, end| {
+            observer(edge, start, end);
+            true
+        })
+    }
+
+    /// Build the network as `build`, counting the work done by the growth and measuring the time taken.
+    pub fn build_with_stats(
+        self,
+        seed: u32,
+        terrain: &Terrain,
+    ) -> Result<(TransportNetwork, BuildStats), TransportError> {
+        let start_ms = now_ms();
+        self.validate(terrain)?;
+        let iterations = self.growth_iterations();
+        let mut growth = self.start_build(seed, terrain);
+        growth.step(iterations, terrain)?;
+        let stats = growth.get_stats();
+        let network = growth.finish();
+        Ok((
+            network,
+            BuildStats {
+                elapsed_ms: now_ms() - start_ms,
+                ..stats
+            },
+        ))
     }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct Neighbor {
     pub index: usize,
+    pub road_class: RoadClass,
+    pub is_highway: bool,
+    pub is_bridge: bool,
+    pub cost: f64,
+}
+
+/// An edge from a node with the site of the other end.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct IncidentEdge {
+    pub index: usize,
+    pub x: f64,
+    pub y: f64,
+    pub road_class: RoadClass,
+    pub is_highway: bool,
+    pub is_even: bool,
+    pub is_bridge: bool,
+    pub cost: f64,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug)]
+pub struct Edge {
+    pub start_index: usize,
+    pub end_index: usize,
+    pub road_class: RoadClass,
+    /// The edge can be traveled only from `start_index` to `end_index`.
+    pub is_one_way: bool,
     pub is_highway: bool,
+    pub is_even: bool,
+    pub is_bridge: bool,
+    pub cost: f64,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct CostSummary {
+    pub highway: f64,
+    pub normal: f64,
+    pub num_highway_edges: usize,
+    pub num_normal_edges: usize,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl TransportNetwork {
     pub fn num_nodes(&self) -> usize {
         self.nodes.len()
@@ -436,8 +1356,154 @@ impl TransportNetwork {
             .iter()
             .map(|n| Neighbor {
                 index: n.0,
-                is_highway: n.1.is_highway,
+                road_class: n.1.path_attr.road_class,
+                is_highway: n.1.path_attr.is_highway(),
+                is_bridge: n.1.path_attr.is_bridge,
+                cost: n.1.cost,
+            })
+            .collect::<Vec<_>>()
+    }
+
+    pub fn get_edges_of(&self, index: usize) -> Vec<IncidentEdge> {
+        self.graph
+            .neighbors_of(index)
+            .iter()
+            .map(|n| IncidentEdge {
+                index: n.0,
+                x: self.nodes[n.0].x,
+                y: self.nodes[n.0].y,
+                road_class: n.1.path_attr.road_class,
+                is_highway: n.1.path_attr.is_highway(),
+                is_even: n.1.path_attr.is_even,
+                is_bridge: n.1.path_attr.is_bridge,
+                cost: n.1.cost,
             })
             .collect::<Vec<_>>()
     }
+
+    pub fn get_edge_cost(&self, index_a: usize, index_b: usize) -> Option<f64> {
+        let (has_edge, attr) = self.graph.has_edge(index_a, index_b);
+        if has_edge {
+            Some(attr.cost)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_cost_summary(&self) -> CostSummary {
+        let mut summary = CostSummary {
+            highway: 0.0,
+            normal: 0.0,
+            num_highway_edges: 0,
+            num_normal_edges: 0,
+        };
+        self.iter_edges().for_each(|(_, _, attr)| {
+            if attr.path_attr.is_highway() {
+                summary.highway += attr.cost;
+                summary.num_highway_edges += 1;
+            } else {
+                summary.normal += attr.cost;
+                summary.num_normal_edges += 1;
+            }
+        });
+        summary
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.graph.size()
+    }
+
+    pub fn get_edges(&self) -> Vec<Edge> {
+        self.edges().collect::<Vec<_>>()
+    }
+}
+
+impl TransportNetwork {
+    pub fn nodes(&self) -> &[Site2D] {
+        &self.nodes
+    }
+
+    /// Iterate over each edge once, from the smaller node index to the larger one
+    /// except that one-way edges run in their direction of travel.
+    pub fn edges(&self) -> impl Iterator<Item = Edge> + '_ {
+        self.iter_edges()
+            .map(|(start_index, end_index, attr)| self.to_edge(start_index, end_index, attr))
+    }
+
+    /// Make the `Edge` between the nodes, running in its direction of travel if it is one-way.
+    pub(crate) fn to_edge(&self, index_a: usize, index_b: usize, attr: EdgeAttr) -> Edge {
+        let (start_index, end_index) = if attr
+            .one_way
+            .allows(&self.nodes[index_a], &self.nodes[index_b])
+        {
+            (index_a, index_b)
+        } else {
+            (index_b, index_a)
+        };
+        Edge {
+            start_index,
+            end_index,
+            is_one_way: attr.one_way != OneWay::TwoWay,
+            road_class: attr.path_attr.road_class,
+            is_highway: attr.path_attr.is_highway(),
+            is_even: attr.path_attr.is_even,
+            is_bridge: attr.path_attr.is_bridge,
+            cost: attr.cost,
+        }
+    }
+}
+
+impl TransportNetwork {
+    /// Create a network from edges between `nodes`.
+    /// Self-loops and edges duplicating an already added one are ignored.
+    pub(crate) fn from_edges<I>(nodes: Vec<Site2D>, edges: I, geometries: EdgeGeometries) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize, EdgeAttr)>,
+    {
+        let mut graph = EdgeAttributedUndirectedGraph::new(nodes.len());
+        edges.into_iter().for_each(|(start, end, attr)| {
+            if start == end || graph.has_edge(start, end).0 {
+                return;
+            }
+            graph.add_edge(start, end, attr);
+        });
+        Self {
+            nodes,
+            graph,
+            geometries,
+            highway_overlay: Overlay::default(),
+            normal_overlay: Overlay::default(),
+            log: GenerationLog::default(),
+            report: BuildReport::default(),
+            spatial_index: OnceLock::new(),
+        }
+    }
+
+    /// Iterate over each undirected edge once, from the smaller node index to the larger one.
+    pub(crate) fn iter_edges(&self) -> impl Iterator<Item = (usize, usize, EdgeAttr)> + '_ {
+        (0..self.nodes.len()).flat_map(move |index| {
+            self.graph
+                .neighbors_of(index)
+                .iter()
+                .filter(move |n| index < n.0)
+                .map(move |n| (index, n.0, n.1))
+        })
+    }
+}
+
+impl Clone for TransportNetwork {
+    fn clone(&self) -> Self {
+        Self {
+            highway_overlay: self.highway_overlay.clone(),
+            normal_overlay: self.normal_overlay.clone(),
+            log: self.log.clone(),
+            report: self.report.clone(),
+            spatial_index: self.spatial_index.clone(),
+            ..Self::from_edges(
+                self.nodes.clone(),
+                self.iter_edges(),
+                self.geometries.clone(),
+            )
+        }
+    }
 }
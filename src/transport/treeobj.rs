@@ -1,8 +1,14 @@
 use rstar::{RTree, RTreeObject, AABB};
+use serde::{Deserialize, Serialize};
 
 use crate::Site2D;
 
-use super::transport::PathAttr;
+use super::{
+    dedup::is_within_merge_distance,
+    error::TransportError,
+    math::{get_clipped_length, get_cross, get_distance},
+    transport::{PathAttr, SnapPreference},
+};
 
 pub(crate) enum PathTreeQuery<'a> {
     None,
@@ -10,7 +16,7 @@ pub(crate) enum PathTreeQuery<'a> {
     Path(&'a PathTreeObject),
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub(crate) struct PathTreeObject {
     pub path_index: usize,
     pub site_index_start: usize,
@@ -18,6 +24,10 @@ pub(crate) struct PathTreeObject {
     pub site_start: Site2D,
     pub site_end: Site2D,
     pub path_attr: PathAttr,
+    pub cost: f64,
+    /// The iteration in which the path was committed, which is kept by the halves of a split,
+    /// or `None` if the path was given before the growth.
+    pub iteration: Option<usize>,
 }
 
 impl RTreeObject for PathTreeObject {
@@ -45,9 +55,18 @@ impl PartialEq for PathTreeObject {
 
 impl Eq for PathTreeObject {}
 
+#[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct PathTree {
     tree: RTree<PathTreeObject>,
     next_path_index: usize,
+    // the paths committed since the last `take_committed`, if they are recorded
+    #[serde(skip)]
+    committed: Option<Vec<PathTreeObject>>,
+    total_length: f64,
+    // the rectangle in which the length of the paths is measured, and the length
+    coverage: Option<(Site2D, Site2D, f64)>,
+    // the iteration given to the paths inserted from now on
+    iteration: Option<usize>,
 }
 
 impl PathTree {
@@ -55,9 +74,50 @@ impl PathTree {
         Self {
             tree: RTree::new(),
             next_path_index: 0,
+            committed: None,
+            total_length: 0.0,
+            coverage: None,
+            iteration: None,
         }
     }
 
+    /// Mark the paths inserted from now on as committed in `iteration`, or as given before the growth if it is `None`.
+    pub fn set_iteration(&mut self, iteration: Option<usize>) {
+        self.iteration = iteration;
+    }
+
+    /// Measure the length of the paths inserted from now on within the rectangle.
+    pub fn measure_coverage(&mut self, bound_min: Site2D, bound_max: Site2D) {
+        self.coverage = Some((bound_min, bound_max, 0.0));
+    }
+
+    /// Get the total length of the paths.
+    pub fn total_length(&self) -> f64 {
+        self.total_length
+    }
+
+    /// Get the number of the paths ever inserted, including the halves of split paths.
+    pub fn num_insertions(&self) -> usize {
+        self.next_path_index
+    }
+
+    /// Get the length of the paths within the rectangle of `measure_coverage`.
+    pub fn covered_length(&self) -> f64 {
+        self.coverage.map_or(0.0, |coverage| coverage.2)
+    }
+
+    /// Record the paths inserted from now on, except the halves of split paths.
+    pub fn record_committed(&mut self) {
+        self.committed.get_or_insert_with(Vec::new);
+    }
+
+    pub fn take_committed(&mut self) -> Vec<PathTreeObject> {
+        self.committed
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     pub fn insert(
         &mut self,
         site_index_start: usize,
@@ -65,17 +125,49 @@ impl PathTree {
         site_start: Site2D,
         site_end: Site2D,
         path_attr: PathAttr,
+        cost: f64,
     ) {
+        let object = self.insert_object(
+            site_index_start,
+            site_index_end,
+            site_start,
+            site_end,
+            path_attr,
+            cost,
+        );
+        if let Some(committed) = &mut self.committed {
+            committed.push(object);
+        }
+        // splitting a path does not change the lengths
+        self.total_length += get_distance(site_start, site_end);
+        if let Some((bound_min, bound_max, covered_length)) = &mut self.coverage {
+            *covered_length += get_clipped_length(site_start, site_end, *bound_min, *bound_max);
+        }
+    }
+
+    fn insert_object(
+        &mut self,
+        site_index_start: usize,
+        site_index_end: usize,
+        site_start: Site2D,
+        site_end: Site2D,
+        path_attr: PathAttr,
+        cost: f64,
+    ) -> PathTreeObject {
         let path_index = self.next_path_index;
         self.next_path_index += 1;
-        self.tree.insert(PathTreeObject {
+        let object = PathTreeObject {
             path_index,
             site_start,
             site_end,
             site_index_start,
             site_index_end,
             path_attr,
-        });
+            cost,
+            iteration: self.iteration,
+        };
+        self.tree.insert(object);
+        object
     }
 
     pub fn find(
@@ -84,7 +176,8 @@ impl PathTree {
         site_end: &Site2D,
         diameter: f64,
         indices_not_including: &[usize],
-    ) -> PathTreeQuery {
+        snap_preference: SnapPreference,
+    ) -> PathTreeQuery<'_> {
         let envelope = AABB::from_corners(
             [site_end.x - diameter, site_end.y - diameter],
             [site_end.x + diameter, site_end.y + diameter],
@@ -103,14 +196,18 @@ impl PathTree {
                 continue;
             }
 
-            let distance_line = ((item.site_end.y - item.site_start.y) * site_cmp.x
-                - (item.site_end.x - item.site_start.x) * site_cmp.y
-                + item.site_end.x * item.site_start.y
-                - item.site_end.y * item.site_start.x)
-                .abs()
-                / ((item.site_end.y - item.site_start.y).powi(2)
-                    + (item.site_end.x - item.site_start.x).powi(2))
-                .sqrt();
+            // a path of zero length has no line, so the distance to its site is taken instead
+            let length = get_distance(item.site_start, item.site_end);
+            let distance_line = if length > 0.0 {
+                ((item.site_end.y - item.site_start.y) * site_cmp.x
+                    - (item.site_end.x - item.site_start.x) * site_cmp.y
+                    + item.site_end.x * item.site_start.y
+                    - item.site_end.y * item.site_start.x)
+                    .abs()
+                    / length
+            } else {
+                get_distance(site_cmp, item.site_start)
+            };
 
             if distance_line < min_distance {
                 min_distance = distance_line;
@@ -119,6 +216,9 @@ impl PathTree {
         }
 
         if let Some(min_path) = min_path {
+            if snap_preference == SnapPreference::PathsFirst {
+                return PathTreeQuery::Path(min_path);
+            }
             let squared_distance_item_start = (site_end.x - min_path.site_start.x).powi(2)
                 + (site_end.y - min_path.site_start.y).powi(2);
             if squared_distance_item_start < diameter.powi(2) {
@@ -135,36 +235,301 @@ impl PathTree {
 
         PathTreeQuery::None
     }
+
+    /// Find the end of the paths nearest to `site` which a site created on `site` is merged into
+    /// by `is_within_merge_distance`, skipping the ends in `indices_not_including`.
+    /// The ends at the same distance are found by the smaller index.
+    pub fn find_site(
+        &self,
+        site: &Site2D,
+        distance: f64,
+        indices_not_including: &[usize],
+    ) -> Option<(usize, Site2D)> {
+        let radius = distance.max(0.0);
+        let envelope = AABB::from_corners(
+            [site.x - radius, site.y - radius],
+            [site.x + radius, site.y + radius],
+        );
+        self.tree
+            .locate_in_envelope_intersecting(&envelope)
+            .flat_map(|item| {
+                [
+                    (item.site_index_start, item.site_start),
+                    (item.site_index_end, item.site_end),
+                ]
+            })
+            .filter(|(index, end)| {
+                !indices_not_including.contains(index)
+                    && is_within_merge_distance(*site, *end, distance)
+            })
+            .min_by(|a, b| {
+                get_distance(*site, a.1)
+                    .total_cmp(&get_distance(*site, b.1))
+                    .then(a.0.cmp(&b.0))
+            })
+    }
+
+    /// Find the crossing of the segment from `site_start` to `site_end` with the paths which is the nearest to `site_start`,
+    /// skipping the paths with an end in `indices_not_including`.
+    pub fn find_crossing(
+        &self,
+        site_start: &Site2D,
+        site_end: &Site2D,
+        indices_not_including: &[usize],
+    ) -> Option<(PathTreeObject, Site2D)> {
+        let envelope = AABB::from_corners(
+            [site_start.x.min(site_end.x), site_start.y.min(site_end.y)],
+            [site_start.x.max(site_end.x), site_start.y.max(site_end.y)],
+        );
+        self.tree
+            .locate_in_envelope_intersecting(&envelope)
+            .filter(|item| {
+                !indices_not_including.contains(&item.site_index_start)
+                    && !indices_not_including.contains(&item.site_index_end)
+            })
+            .filter_map(|item| {
+                match get_cross(*site_start, *site_end, item.site_start, item.site_end) {
+                    Some((site, true)) => Some((*item, site)),
+                    _ => None,
+                }
+            })
+            .min_by(|a, b| {
+                get_distance(*site_start, a.1).total_cmp(&get_distance(*site_start, b.1))
+            })
+    }
+
     pub fn split(
         &mut self,
         path_object: PathTreeObject,
         split_site: &Site2D,
         split_site_index: usize,
-    ) {
-        let remove = self.tree.remove(&path_object);
-        if remove.is_none() {
-            panic!("aaa");
-        }
-        self.insert(
+    ) -> Result<(), TransportError> {
+        self.tree
+            .remove(&path_object)
+            .ok_or(TransportError::PathNotFound)?;
+
+        // the cost is shared between the two halves in proportion to their lengths
+        let length_start = ((split_site.x - path_object.site_start.x).powi(2)
+            + (split_site.y - path_object.site_start.y).powi(2))
+        .sqrt();
+        let length_end = ((path_object.site_end.x - split_site.x).powi(2)
+            + (path_object.site_end.y - split_site.y).powi(2))
+        .sqrt();
+        let prop = if length_start + length_end > 0.0 {
+            length_start / (length_start + length_end)
+        } else {
+            0.5
+        };
+
+        // the halves keep the iteration of the path
+        let iteration = std::mem::replace(&mut self.iteration, path_object.iteration);
+        self.insert_object(
             path_object.site_index_start,
             split_site_index,
             path_object.site_start,
             *split_site,
             path_object.path_attr,
+            path_object.cost * prop,
         );
-        self.insert(
+        self.insert_object(
             split_site_index,
             path_object.site_index_end,
             *split_site,
             path_object.site_end,
             path_object.path_attr,
+            path_object.cost * (1.0 - prop),
         );
+        self.iteration = iteration;
+        Ok(())
     }
 
-    pub fn for_each<F>(&self, mut f: F)
+    pub fn for_each<F>(&self, f: F)
     where
         F: FnMut(&PathTreeObject),
     {
-        self.tree.iter().for_each(|item| f(item));
+        self.tree.iter().for_each(f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(x: f64, y: f64) -> Site2D {
+        Site2D { x, y }
+    }
+
+    /// A tree of the paths from (0, 0) to (1, 0) and from (1, 0) to (1, 1).
+    fn tree() -> PathTree {
+        let mut tree = PathTree::new();
+        tree.insert(
+            0,
+            1,
+            site(0.0, 0.0),
+            site(1.0, 0.0),
+            PathAttr::default(),
+            1.0,
+        );
+        tree.insert(
+            1,
+            2,
+            site(1.0, 0.0),
+            site(1.0, 1.0),
+            PathAttr::default(),
+            1.0,
+        );
+        tree
+    }
+
+    /// Find for a short path coming up to `end`, giving a path found as 100 plus its index.
+    fn find(
+        tree: &PathTree,
+        end: Site2D,
+        diameter: f64,
+        preference: SnapPreference,
+    ) -> Option<usize> {
+        let start = site(end.x, end.y - 0.1);
+        match tree.find(&start, &end, diameter, &[], preference) {
+            PathTreeQuery::None => None,
+            PathTreeQuery::Site(site_index) => Some(site_index),
+            PathTreeQuery::Path(path) => Some(100 + path.path_index),
+        }
+    }
+
+    #[test]
+    fn zero_diameter_finds_nothing() {
+        let tree = tree();
+        for preference in [SnapPreference::SitesFirst, SnapPreference::PathsFirst] {
+            assert_eq!(find(&tree, site(1.0, 0.0), 0.0, preference), None);
+            assert_eq!(find(&tree, site(0.5, 0.0), 0.0, preference), None);
+        }
+    }
+
+    #[test]
+    fn huge_diameter_snaps_to_the_nearest_path() {
+        let tree = tree();
+        // the end is near the path to (1, 1), whose start is within the diameter
+        let end = site(1.1, 0.7);
+        assert_eq!(find(&tree, end, 1e9, SnapPreference::SitesFirst), Some(1));
+        assert_eq!(find(&tree, end, 1e9, SnapPreference::PathsFirst), Some(101));
+        assert_eq!(
+            find(&tree, end, f64::INFINITY, SnapPreference::SitesFirst),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn sites_first_prefers_an_end_within_the_diameter() {
+        let tree = tree();
+        assert_eq!(
+            find(&tree, site(0.95, 0.05), 0.2, SnapPreference::SitesFirst),
+            Some(1)
+        );
+        // the path is within the diameter but its ends are not
+        assert_eq!(
+            find(&tree, site(0.5, 0.05), 0.2, SnapPreference::SitesFirst),
+            Some(100)
+        );
+    }
+
+    #[test]
+    fn paths_first_always_returns_the_path() {
+        let tree = tree();
+        assert_eq!(
+            find(&tree, site(0.95, 0.05), 0.2, SnapPreference::PathsFirst),
+            Some(100)
+        );
+        assert_eq!(
+            find(&tree, site(0.5, 0.05), 0.2, SnapPreference::PathsFirst),
+            Some(100)
+        );
+    }
+
+    #[test]
+    fn zero_length_path_is_found_by_its_site() {
+        let mut tree = PathTree::new();
+        tree.insert(
+            0,
+            1,
+            site(1.0, 1.0),
+            site(1.0, 1.0),
+            PathAttr::default(),
+            0.0,
+        );
+        assert_eq!(
+            find(&tree, site(1.0, 1.1), 0.5, SnapPreference::SitesFirst),
+            Some(0)
+        );
+        assert_eq!(
+            find(&tree, site(1.0, 1.1), 0.5, SnapPreference::PathsFirst),
+            Some(100)
+        );
+        assert_eq!(
+            find(&tree, site(3.0, 3.0), 0.5, SnapPreference::SitesFirst),
+            None
+        );
+    }
+
+    #[test]
+    fn site_finds_the_nearest_end_within_the_distance() {
+        let tree = tree();
+        assert_eq!(
+            tree.find_site(&site(0.9, 0.05), 0.2, &[])
+                .map(|found| found.0),
+            Some(1)
+        );
+        assert_eq!(
+            tree.find_site(&site(1.0, 0.85), 0.2, &[])
+                .map(|found| found.0),
+            Some(2)
+        );
+        assert!(tree.find_site(&site(0.5, 0.0), 0.2, &[]).is_none());
+        assert!(tree.find_site(&site(1.2, 0.0), 0.2, &[]).is_none());
+    }
+
+    #[test]
+    fn site_skips_the_excluded_ends() {
+        let tree = tree();
+        assert!(tree.find_site(&site(1.0, 0.0), 0.2, &[1]).is_none());
+        assert_eq!(
+            tree.find_site(&site(0.5, 0.5), 1.0, &[1])
+                .map(|found| found.0),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn coincident_site_is_found_at_any_distance() {
+        let tree = tree();
+        for distance in [0.0, -1.0, f64::NAN] {
+            assert_eq!(
+                tree.find_site(&site(1.0, 0.0), distance, &[])
+                    .map(|found| found.0),
+                Some(1)
+            );
+            assert!(tree.find_site(&site(1.0, 1e-9), distance, &[]).is_none());
+        }
+    }
+
+    #[test]
+    fn split_exactly_at_an_end_is_merged_into_it() {
+        let mut tree = tree();
+        // a path from (2, 0) to (0.5, 0) crosses the path to (1, 1) exactly at its end (1, 0)
+        let (path, cross_site) = tree
+            .find_crossing(&site(2.0, 0.0), &site(0.5, 0.0), &[])
+            .unwrap();
+        assert_eq!(
+            tree.find_site(&cross_site, 0.0, &[]).map(|found| found.0),
+            Some(1)
+        );
+        // splitting there instead would leave a path of zero length
+        tree.split(path, &cross_site, 3).unwrap();
+        let mut zero_length_paths = 0;
+        tree.for_each(|path| {
+            if get_distance(path.site_start, path.site_end) == 0.0 {
+                zero_length_paths += 1;
+            }
+        });
+        assert_eq!(zero_length_paths, 1);
     }
 }
@@ -0,0 +1,174 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    terrain::Terrain,
+    transport::{
+        block::{get_signed_area, Blocks},
+        math::get_distance_to_segment,
+        transport::TransportNetwork,
+    },
+    Site2D,
+};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ZoneKind {
+    Residential,
+    Commercial,
+    Industrial,
+}
+
+/// The land use of each block.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Zoning {
+    zones: Vec<ZoneKind>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Zoning {
+    pub fn num_blocks(&self) -> usize {
+        self.zones.len()
+    }
+
+    pub fn get_zone(&self, block_index: usize) -> ZoneKind {
+        self.zones[block_index]
+    }
+
+    pub fn count(&self, kind: ZoneKind) -> usize {
+        self.zones.iter().filter(|zone| **zone == kind).count()
+    }
+}
+
+/// Classifies blocks by the distance from their centroids to the nearest highway,
+/// the altitude of the centroids and their areas.
+///
+/// Large flat blocks near highways are industrial, small blocks near highways are commercial
+/// and the others are residential.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone)]
+pub struct ZoningBuilder {
+    commercial_highway_distance: f64,
+    commercial_max_area: f64,
+    industrial_highway_distance: f64,
+    industrial_min_area: f64,
+    industrial_max_altitude: f64,
+}
+
+impl Default for ZoningBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn get_centroid(polygon: &[Site2D]) -> Site2D {
+    let (mut x, mut y, mut area) = (0.0, 0.0, 0.0);
+    (0..polygon.len()).for_each(|i| {
+        let a = polygon[i];
+        let b = polygon[(i + 1) % polygon.len()];
+        let cross = a.x * b.y - b.x * a.y;
+        x += (a.x + b.x) * cross;
+        y += (a.y + b.y) * cross;
+        area += cross;
+    });
+    if area == 0.0 {
+        return polygon[0];
+    }
+    Site2D {
+        x: x / (3.0 * area),
+        y: y / (3.0 * area),
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ZoningBuilder {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self {
+            commercial_highway_distance: 0.0,
+            commercial_max_area: 0.0,
+            industrial_highway_distance: 0.0,
+            industrial_min_area: 0.0,
+            industrial_max_altitude: 0.0,
+        }
+    }
+
+    pub fn set_commercial_highway_distance(self, commercial_highway_distance: f64) -> Self {
+        Self {
+            commercial_highway_distance,
+            ..self
+        }
+    }
+
+    pub fn set_commercial_max_area(self, commercial_max_area: f64) -> Self {
+        Self {
+            commercial_max_area,
+            ..self
+        }
+    }
+
+    pub fn set_industrial_highway_distance(self, industrial_highway_distance: f64) -> Self {
+        Self {
+            industrial_highway_distance,
+            ..self
+        }
+    }
+
+    pub fn set_industrial_min_area(self, industrial_min_area: f64) -> Self {
+        Self {
+            industrial_min_area,
+            ..self
+        }
+    }
+
+    pub fn set_industrial_max_altitude(self, industrial_max_altitude: f64) -> Self {
+        Self {
+            industrial_max_altitude,
+            ..self
+        }
+    }
+
+    pub fn build(&self, network: &TransportNetwork, blocks: &Blocks, terrain: &Terrain) -> Zoning {
+        let highway_segments = network
+            .iter_edges()
+            .filter(|(_, _, attr)| attr.path_attr.is_highway())
+            .flat_map(|(start, end, _)| {
+                let polyline = network.get_polyline(start, end);
+                polyline
+                    .windows(2)
+                    .map(|pair| (pair[0], pair[1]))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let zones = blocks
+            .polygons
+            .iter()
+            .map(|polygon| {
+                let centroid = get_centroid(polygon);
+                let area = get_signed_area(polygon);
+                let altitude = terrain
+                    .get_altitude(centroid.x, centroid.y)
+                    .unwrap_or(f64::MAX);
+                let highway_distance = highway_segments
+                    .iter()
+                    .map(|(start, end)| get_distance_to_segment(centroid, *start, *end))
+                    .fold(f64::MAX, f64::min);
+
+                if highway_distance <= self.industrial_highway_distance
+                    && area >= self.industrial_min_area
+                    && altitude <= self.industrial_max_altitude
+                {
+                    ZoneKind::Industrial
+                } else if highway_distance <= self.commercial_highway_distance
+                    && area <= self.commercial_max_area
+                {
+                    ZoneKind::Commercial
+                } else {
+                    ZoneKind::Residential
+                }
+            })
+            .collect::<Vec<_>>();
+        Zoning { zones }
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{terrain::Terrain, transport::transport::TransportNetwork};
+
+const WORLD_MAGIC: &[u8; 4] = b"TGWD";
+const WORLD_VERSION: u32 = 3;
+
+/// A terrain bundled with the transport networks generated on it,
+/// which can be saved and reloaded as a single file.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct World {
+    terrain: Terrain,
+    networks: Vec<TransportNetwork>,
+}
+
+#[derive(Serialize)]
+struct VersionedWorldRef<'a> {
+    version: u32,
+    world: &'a World,
+}
+
+#[derive(Deserialize)]
+struct VersionedWorld {
+    version: u32,
+    world: World,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl World {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(terrain: Terrain) -> Self {
+        Self {
+            terrain,
+            networks: vec![],
+        }
+    }
+
+    pub fn add_network(&mut self, network: TransportNetwork) {
+        self.networks.push(network);
+    }
+
+    pub fn num_networks(&self) -> usize {
+        self.networks.len()
+    }
+
+    pub fn get_terrain(&self) -> Terrain {
+        self.terrain.clone()
+    }
+
+    pub fn get_network(&self, index: usize) -> TransportNetwork {
+        self.networks[index].clone()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = WORLD_MAGIC.to_vec();
+        bytes.extend(WORLD_VERSION.to_le_bytes());
+        bytes.extend(bincode::serialize(self).unwrap());
+        bytes
+    }
+
+    /// Load a world written by `to_bytes`.
+    /// Returns `None` if the data is broken or written in an unsupported version.
+    pub fn from_bytes(bytes: &[u8]) -> Option<World> {
+        if bytes.get(0..4)? != WORLD_MAGIC {
+            return None;
+        }
+        let version = u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?);
+        if version != WORLD_VERSION {
+            return None;
+        }
+        bincode::deserialize(&bytes[8..]).ok()
+    }
+
+    /// Export the world as JSON with the same contents as `to_bytes`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&VersionedWorldRef {
+            version: WORLD_VERSION,
+            world: self,
+        })
+        .unwrap()
+    }
+
+    /// Load a world written by `to_json`.
+    /// Returns `None` if the data is broken or written in an unsupported version.
+    pub fn from_json(json: &str) -> Option<World> {
+        let versioned: VersionedWorld = serde_json::from_str(json).ok()?;
+        if versioned.version != WORLD_VERSION {
+            return None;
+        }
+        Some(versioned.world)
+    }
+}